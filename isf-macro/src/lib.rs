@@ -2,18 +2,178 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use isf::{codegen::generate, parse::parse, spec::form_spec};
+use isf::{
+    codegen::{generate_with_options, CodegenOptions},
+    docgen::generate_docs,
+    parse::parse,
+    spec::form_spec,
+};
 use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{quote, quote_spanned};
+use serde::Deserialize;
 use std::fs::read_to_string;
 use syn::LitStr;
+use winnow::Parser;
 
+/// Settings for the `isf!` macro when given in `key = value` form.
+#[derive(Deserialize)]
+struct Settings {
+    path: Option<String>,
+    /// Spec text to use in place of reading a file at `path`.
+    inline: Option<String>,
+    #[serde(flatten)]
+    options: CodegenOptions,
+}
+
+/// Generate code for an ISF spec. The macro accepts either a path to the
+/// spec as a string literal, or settings of the form
+/// `isf!{ path = "spec.isf", fuzz = true }`. The spec text may be given
+/// directly in place of a path, as in `isf!(inline = "...")`. Problems
+/// reading the spec are reported as compile errors, and spec warnings as
+/// deprecation warnings. A spec without concrete instructions is an error.
 #[proc_macro]
 pub fn isf(item: TokenStream) -> TokenStream {
-    let filename = syn::parse::<LitStr>(item).expect("parse filename").value();
-    let text = read_to_string(filename).expect("read isf file");
-    let mut s: &str = text.as_str();
-    let ast = parse(&mut s).expect("parse isf");
-    let spec = form_spec(&ast).expect("form isf spec");
-    let tokens = generate(&spec);
-    tokens.into()
+    match expand(item) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(item: TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    let (text, options, span) = match syn::parse::<LitStr>(item.clone()) {
+        Ok(filename) => (
+            read_spec(&filename.value(), filename.span())?,
+            CodegenOptions::default(),
+            filename.span(),
+        ),
+        Err(_) => {
+            let span = Span::call_site();
+            let settings: Settings = serde_tokenstream::from_tokenstream(
+                &item.into(),
+            )
+            .map_err(|e| syn::Error::new(span, format!("isf settings: {e}")))?;
+            let text = match (settings.path, settings.inline) {
+                (Some(path), None) => read_spec(&path, span)?,
+                (None, Some(text)) => text,
+                _ => {
+                    return Err(syn::Error::new(
+                        span,
+                        "isf settings need exactly one of path or inline",
+                    ))
+                }
+            };
+            (text, settings.options, span)
+        }
+    };
+    let ast = parse.parse(text.as_str()).map_err(|e| {
+        let message = e.inner().to_string();
+        syn::Error::new(span, parse_error(&text, e.offset(), &message))
+    })?;
+    let mut spec = form_spec(&ast)
+        .map_err(|e| syn::Error::new(span, format!("isf spec error: {e}")))?;
+    if spec.instructions.is_empty() {
+        return Err(syn::Error::new(
+            span,
+            "isf spec declares no concrete instructions",
+        ));
+    }
+    // Inner doc attributes are not permitted in macro output.
+    spec.doc.clear();
+    let mut tokens = generate_with_options(&spec, &options);
+    for w in &spec.warnings {
+        tokens.extend(warning(w, span));
+    }
+    Ok(tokens)
+}
+
+/// Report `message` as a compiler warning. Procedural macros have no stable
+/// way to emit warnings, so this uses an item deprecated with the message.
+fn warning(message: &str, span: Span) -> proc_macro2::TokenStream {
+    let note = format!("isf spec warning: {message}");
+    quote_spanned! {span=>
+        const _: () = {
+            #[deprecated(note = #note)]
+            struct Warning;
+            let _ = Warning;
+        };
+    }
+}
+
+/// Render HTML documentation for an ISF spec at compile time. The macro
+/// takes the path to the spec as a string literal, as in
+/// `isf_docs!("spec.isf")`, and expands to a `DOCS_HTML` string constant
+/// holding the rendered documentation. Problems reading the spec are
+/// reported as compile errors.
+#[proc_macro]
+pub fn isf_docs(item: TokenStream) -> TokenStream {
+    match expand_docs(item) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand_docs(item: TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    let path = syn::parse::<LitStr>(item)?;
+    let html = generate_docs(&path.value()).map_err(|e| {
+        syn::Error::new(path.span(), format!("isf docs {}: {e}", path.value()))
+    })?;
+    Ok(quote! {
+        /// HTML documentation of the instruction set.
+        pub const DOCS_HTML: &str = #html;
+    })
+}
+
+fn read_spec(path: &str, span: Span) -> syn::Result<String> {
+    read_to_string(path).map_err(|e| {
+        syn::Error::new(span, format!("read isf file {path}: {e}"))
+    })
+}
+
+/// Describe a failure to parse `text` at byte `offset` by the line and
+/// column it occurred at and the text of the line.
+fn parse_error(text: &str, offset: usize, message: &str) -> String {
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = text[..offset].matches('\n').count() + 1;
+    let column = text[line_start..offset].chars().count() + 1;
+    let source = text[line_start..].lines().next().unwrap_or_default();
+    let message = match message.lines().collect::<Vec<_>>().join(", ") {
+        m if m.is_empty() => "unexpected input".to_owned(),
+        m => m,
+    };
+    format!(
+        "isf parse error at line {line}, column {column}: {message}\n\
+        {source}\n{:>column$}",
+        "^"
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_error_location() {
+        let text = "instruction_width = 32;\n\n/// Doc\ninstruction Nop {\n  \
+            timing: 1 cycle\n  fields:\n    /// Doc\n    dst: 5 (alias 1),\n}\n";
+        let e = parse.parse(text).map(|_| ()).unwrap_err();
+        assert_eq!(
+            parse_error(text, e.offset(), &e.inner().to_string()),
+            "isf parse error at line 8, column 19: invalid field alias\n    \
+            dst: 5 (alias 1),\n                  ^",
+        );
+    }
+
+    #[test]
+    fn parse_error_trailing_input() {
+        // Without a docstring the instruction is not parsed, and must not be
+        // dropped along with the rest of the spec.
+        let text = "instruction_width = 32;\n\ninstruction Nop {\n}\n";
+        let e = parse.parse(text).map(|_| ()).unwrap_err();
+        assert_eq!(
+            parse_error(text, e.offset(), &e.inner().to_string()),
+            "isf parse error at line 3, column 1: unexpected input\n\
+            instruction Nop {\n^",
+        );
+    }
 }
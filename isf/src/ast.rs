@@ -4,18 +4,62 @@
 
 #[derive(Debug, Default)]
 pub struct Ast {
+    /// Documentation for the instruction set as a whole, from `//!` lines at
+    /// the start of the file.
+    pub doc: String,
     pub characteristics: Vec<Characteristic>,
+    /// Comments of each characteristic.
+    pub characteristic_comments: Vec<Comments>,
+    /// Position of each characteristic among the declarations that lead the
+    /// spec, for ordering them with the constants.
+    pub characteristic_positions: Vec<usize>,
+    pub constants: Vec<Constant>,
     pub classes: Vec<Class>,
     pub instructions: Vec<Instruction>,
+    /// Comments after the last declaration in the file.
+    pub comments: Vec<String>,
 }
 
 impl Ast {
-    pub fn instruction_width(&self) -> Option<usize> {
-        if let Some(c) = self.characteristics.first() {
-            let Characteristic::InstructionWidth(w) = c;
-            return Some(*w);
-        }
-        None
+    pub fn instruction_width(&self) -> Option<&CharacteristicValue> {
+        self.characteristics.iter().find_map(|c| match c {
+            Characteristic::InstructionWidth(w) => Some(w),
+            _ => None,
+        })
+    }
+
+    pub fn force_storage(&self) -> Option<usize> {
+        self.characteristics.iter().find_map(|c| match c {
+            Characteristic::ForceStorage(w) => Some(*w),
+            _ => None,
+        })
+    }
+
+    pub fn endianness(&self) -> Option<Endianness> {
+        self.characteristics.iter().find_map(|c| match c {
+            Characteristic::Endianness(e) => Some(*e),
+            _ => None,
+        })
+    }
+
+    /// Name of the instruction set, declared as `name = 'MyISA';`.
+    pub fn name(&self) -> Option<&str> {
+        self.characteristics.iter().find_map(|c| match c {
+            Characteristic::Name(n) => Some(n.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Version of the instruction set, declared as `version = '1.2';`.
+    pub fn version(&self) -> Option<&str> {
+        self.characteristics.iter().find_map(|c| match c {
+            Characteristic::Version(v) => Some(v.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn get_constant(&self, name: &str) -> Option<&Constant> {
+        self.constants.iter().find(|c| c.name == name)
     }
 
     pub fn get_instruction<'a>(
@@ -28,22 +72,110 @@ impl Ast {
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum Characteristic {
-    InstructionWidth(usize),
+    InstructionWidth(CharacteristicValue),
+    /// Storage type width used for all instructions, regardless of
+    /// instruction width.
+    ForceStorage(usize),
+    /// Byte order of instructions in machine form.
+    Endianness(Endianness),
+    /// Name identifying the instruction set.
+    Name(String),
+    /// Version of the instruction set.
+    Version(String),
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum CharacteristicValue {
+    NumericConstant(usize),
+    NamedConstant(String),
+}
+
+/// A named numeric constant declared at the top level of a spec.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Constant {
+    pub name: String,
+    pub value: u64,
+    /// Position among the characteristics and constants that lead the spec.
+    /// A constant can only be referred to after it is declared.
+    pub position: usize,
+    pub comments: Comments,
+}
+
+/// Comments around a declaration, kept so the formatter can emit them again.
+/// Each comment includes its `//` or `/* */`, and an empty string stands for
+/// a blank line between comments.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Comments {
+    /// Comments on lines of their own above the declaration and its
+    /// docstring.
+    pub leading: Vec<String>,
+    /// Comments on lines of their own between the docstring and the
+    /// declaration.
+    pub inner: Vec<String>,
+    /// Comment at the end of the declaration's line.
+    pub trailing: Option<String>,
+    /// Comments on lines of their own directly below the declaration, set
+    /// apart from what follows by a blank line.
+    pub after: Vec<String>,
+}
+
+impl Comments {
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty()
+            && self.inner.is_empty()
+            && self.trailing.is_none()
+            && self.after.is_empty()
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Class {
     pub doc: String,
+    /// Comments of the declaration. The trailing comment follows the
+    /// closing brace. Comments in the body are only kept on its values.
+    pub comments: Comments,
     pub name: String,
     pub width: usize,
+    /// Named values of the class. Empty for classes that are plain integers.
+    pub values: Vec<ClassValue>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ClassValue {
+    pub doc: String,
+    pub comments: Comments,
+    pub name: String,
+    pub value: u64,
 }
 
 #[derive(Debug, Clone)]
 pub struct Instruction {
     pub doc: String,
+    /// Comments of the declaration. The trailing comment follows the
+    /// opening brace.
+    pub comments: Comments,
+    /// Comments of the `timing:` line.
+    pub timing_comments: Comments,
+    /// Comments of the `fields:` line.
+    pub fields_comments: Comments,
+    /// Comments of the closing brace. The leading comments are the ones
+    /// before it inside the body.
+    pub closing_comments: Comments,
+    /// Deprecation note from an `@deprecated("...")` attribute.
+    pub deprecated: Option<String>,
     pub name: String,
     pub timing: Option<Timing>,
-    pub parameters: Vec<String>,
+    pub parameters: Vec<Parameter>,
+    /// Width in bits overriding the spec's `instruction_width`, from a
+    /// `: width N` clause.
+    pub width: Option<usize>,
     pub base: Option<Base>,
     pub fields: Vec<Field>,
     pub assembly: Assembly,
@@ -59,8 +191,16 @@ impl Instruction {
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
 pub enum Timing {
     Cycle(usize),
+    /// A latency in nanoseconds.
+    Nanos(usize),
+    /// A number of pipeline stages.
+    Stages(usize),
     Async,
     Multi,
+    /// Multiple cycles, up to the given count.
+    MultiUpTo(usize),
+    /// Multiple cycles, within an inclusive range of counts.
+    MultiRange(usize, usize),
 }
 
 impl std::fmt::Display for Timing {
@@ -68,8 +208,15 @@ impl std::fmt::Display for Timing {
         match self {
             Timing::Cycle(1) => write!(f, "1 cycle"),
             Timing::Cycle(n) => write!(f, "{n} cycles"),
+            Timing::Nanos(n) => write!(f, "{n} ns"),
+            Timing::Stages(1) => write!(f, "1 stage"),
+            Timing::Stages(n) => write!(f, "{n} stages"),
             Timing::Async => write!(f, "async"),
             Timing::Multi => write!(f, "multiple cycles"),
+            Timing::MultiUpTo(n) => write!(f, "up to {n} cycles"),
+            Timing::MultiRange(min, max) => {
+                write!(f, "{min} to {max} cycles")
+            }
         }
     }
 }
@@ -80,6 +227,15 @@ impl Default for Timing {
     }
 }
 
+/// A parameter of a generic instruction.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Parameter {
+    pub name: String,
+    /// Value used when an instruction naming this one as its base omits the
+    /// parameter. Only trailing parameters may have defaults.
+    pub default: Option<BaseParameter>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Base {
     pub name: String,
@@ -89,9 +245,38 @@ pub struct Base {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Field {
     pub doc: String,
+    pub comments: Comments,
     pub name: String,
     pub ty: FieldType,
     pub value: Option<FieldValue>,
+    /// Two's complement signed field, declared with a `signed` suffix.
+    pub is_signed: bool,
+    /// Radix used when emitting the field in assembly, declared with a `hex`
+    /// or `bin` suffix.
+    pub radix: Radix,
+    /// Minimum number of digits used when emitting the field in assembly,
+    /// declared with a `pad` suffix. Zero for no padding.
+    pub pad: usize,
+    /// Factor the field is multiplied by in assembly, declared with a
+    /// `scale` suffix. One for no scaling.
+    pub scale: u64,
+    /// Alternative names for the field, declared with an `(alias a, b)`
+    /// suffix.
+    pub aliases: Vec<String>,
+    /// Names of the individual bits of the field, least significant first,
+    /// declared with a `{a, b}` suffix.
+    pub bits: Vec<String>,
+    /// Values the field may hold, declared with an `in {a, b}` suffix.
+    pub allowed: Option<Vec<u64>>,
+}
+
+/// The radix a field is written in when emitting assembly.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Radix {
+    #[default]
+    Decimal,
+    Hex,
+    Binary,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -104,6 +289,8 @@ pub enum FieldType {
 pub enum BaseParameter {
     Number(u64),
     Text(String),
+    /// A parameter of the enclosing instruction, forwarded to its base.
+    Parameter(String),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -116,38 +303,79 @@ pub enum FieldValue {
 #[derive(Debug, Default, Clone)]
 pub struct Assembly {
     pub syntax: Vec<AssemblyElement>,
+    /// Further forms of the syntax, each following a `|`.
+    pub alternatives: Vec<Vec<AssemblyElement>>,
     pub example: Vec<AssemblyExample>,
+    /// Comments of the `assembly:` line.
+    pub comments: Comments,
+    /// Comments of the syntax, including its alternatives.
+    pub syntax_comments: Comments,
+    /// Comments of the `examples:` line.
+    pub examples_comments: Comments,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum AssemblyElement {
-    Expansion { name: String },
-    StringLiteral { value: String },
-    NumberLiteral { value: u64 },
-    OptionalFlag { name: String, field: String },
-    // TODO: with_dot is a bit of a hack, it would be nice if optional parts
-    //       of assemblys could be complete syntaxes themselves.
-    OptionalField { name: String, with_dot: bool },
+    Expansion {
+        name: String,
+    },
+    StringLiteral {
+        value: String,
+    },
+    NumberLiteral {
+        value: u64,
+    },
+    OptionalFlag {
+        name: String,
+        field: String,
+    },
+    /// A bracketed sequence of elements that is present or absent as a
+    /// whole, such as `[.imm]`.
+    Optional {
+        elements: Vec<AssemblyElement>,
+    },
+    /// A bracketed sequence of elements that is present exactly when a flag
+    /// field is set, such as `[pred => , 'r'src3]`.
+    Conditional {
+        field: String,
+        elements: Vec<AssemblyElement>,
+    },
     Dot,
     Comma,
     Space,
-    Field { name: String },
+    Field {
+        name: String,
+    },
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct AssemblyExample {
     pub doc: String,
     pub example: String,
+    pub comments: Comments,
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct Machine {
     pub layout: Vec<MachineElement>,
+    /// Documentation of each layout element, empty for undocumented ones.
+    pub docs: Vec<String>,
+    /// Bit position of each layout element, declared with an `@` suffix
+    /// on its name as in `dst @ 8`. `None` for elements placed after the
+    /// one before them.
+    pub positions: Vec<Option<usize>>,
+    /// Comments of the `machine:` line.
+    pub comments: Comments,
+    /// Comments of each layout element.
+    pub element_comments: Vec<Comments>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum MachineElementValue {
     NumericConstant(u64),
+    /// A negative constant, encoded in two's complement at the width of
+    /// its layout element.
+    SignedConstant(i64),
     GenericParameter(String),
 }
 
@@ -156,14 +384,23 @@ pub enum MachineElement {
     Field {
         name: String,
     },
+    /// Inclusive range of bits of a field. The bounds may be given in either
+    /// order, and equal bounds select a single bit.
     FieldSlice {
         name: String,
         begin: usize,
         end: usize,
+        /// The slice holds the complement of the bits, declared with a `!`
+        /// suffix.
+        negate: bool,
     },
     FieldNegate {
         name: String,
     },
+    /// Sign bit of a sign-magnitude field.
+    FieldSign {
+        name: String,
+    },
     OptionalFieldPresentTest {
         name: String,
     },
@@ -175,4 +412,9 @@ pub enum MachineElement {
         width: usize,
         value: Option<MachineElementValue>,
     },
+    /// Reserved bits, declared as `_: fill`, as wide as needed for the
+    /// layout to span the width of the instruction.
+    Fill {
+        name: String,
+    },
 }
@@ -2,7 +2,8 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use winnow::Parser as _;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -10,6 +11,14 @@ struct Cli {
     /// Path to an ISF spec
     path: String,
 
+    /// Write output to a file instead of stdout
+    #[arg(short, long, global = true)]
+    output: Option<String>,
+
+    /// Treat warnings about the spec as errors
+    #[arg(long, global = true)]
+    strict: bool,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -19,23 +28,106 @@ enum Command {
     /// Generate code from an ISF spec
     Code,
     /// Generate docs from an ISF spec
-    Docs,
+    Docs {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = DocsFormat::Html)]
+        format: DocsFormat,
+        /// Liquid template to render HTML docs with in place of the
+        /// bundled one
+        #[arg(long)]
+        template: Option<String>,
+    },
+    /// Draw the machine layout of each instruction in an ISF spec
+    Layout,
+    /// Format an ISF spec
+    Fmt,
+    /// Export the resolved ISF spec, including bit offsets
+    Spec {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DocsFormat {
+    Html,
+    Markdown,
 }
 
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
-    match cli.command {
-        Command::Code => codegen(&cli.path),
-        Command::Docs => docgen(&cli.path),
+    // Errors are left for the command itself to report.
+    let warnings = warnings(&cli.path).unwrap_or_default();
+    for warning in &warnings {
+        eprintln!("warning: {warning}");
+    }
+    if cli.strict && !warnings.is_empty() {
+        anyhow::bail!("{} warnings in strict mode", warnings.len());
+    }
+    let text = match cli.command {
+        Command::Code => codegen(&cli.path)?,
+        Command::Docs { format, template } => {
+            docgen(&cli.path, format, template.as_deref())?
+        }
+        Command::Layout => layout(&cli.path)?,
+        Command::Fmt => fmt(&cli.path)?,
+        Command::Spec { format } => export(&cli.path, format)?,
+    };
+    match cli.output {
+        Some(output) => std::fs::write(output, text)?,
+        None => print!("{text}"),
     }
+    Ok(())
 }
 
-fn codegen(path: &str) -> anyhow::Result<()> {
-    let code = isf::codegen::generate_code(path)?;
-    println!("{code}");
-    Ok(())
+fn codegen(path: &str) -> anyhow::Result<String> {
+    isf::codegen::generate_code(path)
+}
+
+fn fmt(path: &str) -> anyhow::Result<String> {
+    isf::fmt::format_spec(path)
+}
+
+fn docgen(
+    path: &str,
+    format: DocsFormat,
+    template: Option<&str>,
+) -> anyhow::Result<String> {
+    match (format, template) {
+        (DocsFormat::Html, None) => isf::docgen::generate_docs(path),
+        (DocsFormat::Html, Some(template)) => {
+            let src = std::fs::read_to_string(template)?;
+            isf::docgen::generate_docs_with_template(path, &src)
+        }
+        (DocsFormat::Markdown, None) => {
+            isf::docgen::generate_docs_markdown(path)
+        }
+        (DocsFormat::Markdown, Some(_)) => {
+            anyhow::bail!("templates apply only to HTML docs")
+        }
+    }
+}
+
+fn layout(path: &str) -> anyhow::Result<String> {
+    isf::docgen::generate_layout(path)
+}
+
+fn export(path: &str, format: Format) -> anyhow::Result<String> {
+    match format {
+        Format::Json => isf::export::export_json(path),
+    }
 }
 
-fn docgen(_path: &str) -> anyhow::Result<()> {
-    todo!();
+fn warnings(path: &str) -> anyhow::Result<Vec<String>> {
+    let text = std::fs::read_to_string(path)?;
+    let ast = isf::parse::parse
+        .parse(text.as_str())
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    Ok(isf::spec::form_spec(&ast)?.warnings)
 }
@@ -16,333 +16,147 @@ macro_rules! gen_bit {
         }
     };
 }
+gen_bit!(u8);
+gen_bit!(u16);
 gen_bit!(u32);
 gen_bit!(u64);
-
-macro_rules! gen_u8 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u8_ $width >](reg: $width, offset: usize) -> u8 {
-                let v = reg >> (offset as $width);
-                v as u8
-            }
-            pub fn [< set_u8_ $width >](reg: $width, offset: usize, value: u8) -> $width {
-                let mask = !(0xff << (offset as $width));
-                let v = (value as $width) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u8!(u32);
-gen_u8!(u64);
-
-macro_rules! gen_u16 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u16_ $width >](reg: $width, offset: usize) -> u16 {
-                let v = reg >> (offset as $width);
-                v as u16
-            }
-            pub fn [< set_u16_ $width >](reg: $width, offset: usize, value: u16) -> $width {
-                let mask = !(0xffff << (offset as $width));
-                let v = (value as $width) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u16!(u32);
-gen_u16!(u64);
-
-macro_rules! gen_u32 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u32_ $width >](reg: $width, offset: usize) -> u32 {
-                let v = reg >> (offset as $width);
-                v as u32
-            }
-            pub fn [< set_u32_ $width >](reg: $width, offset: usize, value: u32) -> $width {
-                let mask = !(0xffffffff << (offset as $width));
-                let v = (value as $width) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u32!(u32);
-gen_u32!(u64);
-
-macro_rules! gen_u1 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u1_ $width >](reg: $width, offset: usize) -> u8 {
-                let v = [< get_u8_ $width >](reg, offset);
-                v & 0b1
-            }
-        }
-        paste::item! {
-            pub fn [< set_u1_ $width >](reg: $width, offset: usize, value: u8) -> $width {
-                let mask = !(0b1 << (offset as $width));
-                let v = ((value as $width) & 0b1) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u1!(u32);
-gen_u1!(u64);
-
-macro_rules! gen_u2 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u2_ $width >](reg: $width, offset: usize) -> u8 {
-                let v = [< get_u8_ $width >](reg, offset);
-                v & 0b11
-            }
-        }
-        paste::item! {
-            pub fn [< set_u2_ $width >](reg: $width, offset: usize, value: u8) -> $width {
-                let mask = !(0b11 << (offset as $width));
-                let v = ((value as $width) & 0b11) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u2!(u32);
-gen_u2!(u64);
-
-macro_rules! gen_u3 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u3_ $width >](reg: $width, offset: usize) -> u8 {
-                let v = [< get_u8_ $width >](reg, offset);
-                v & 0b111
-            }
-            pub fn [< set_u3_ $width >](reg: $width, offset: usize, value: u8) -> $width {
-                let mask = !(0b111 << (offset as $width));
-                let v = ((value as $width) & 0b111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u3!(u32);
-gen_u3!(u64);
-
-macro_rules! gen_u4 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u4_ $width >](reg: $width, offset: usize) -> u8 {
-                let v = [< get_u8_ $width >](reg, offset);
-                v & 0b1111
-            }
-            pub fn [< set_u4_ $width >](reg: $width, offset: usize, value: u8) -> $width {
-                let mask = !(0b1111 << (offset as $width));
-                let v = ((value as $width) & 0b1111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u4!(u32);
-gen_u4!(u64);
-
-macro_rules! gen_u5 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u5_ $width >](reg: $width, offset: usize) -> u8 {
-                let v = [< get_u8_ $width >](reg, offset);
-                v & 0b11111
-            }
-            pub fn [< set_u5_ $width >](reg: $width, offset: usize, value: u8) -> $width {
-                let mask = !(0b11111 << (offset as $width));
-                let v = ((value as $width) & 0b11111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u5!(u32);
-gen_u5!(u64);
-
-macro_rules! gen_u6 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u6_ $width >](reg: $width, offset: usize) -> u8 {
-                let v = [< get_u8_ $width >](reg, offset);
-                v & 0b111111
-            }
-            pub fn [< set_u6_ $width >](reg: $width, offset: usize, value: u8) -> $width {
-                let mask = !(0b111111 << (offset as $width));
-                let v = ((value as $width) & 0b111111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u6!(u32);
-gen_u6!(u64);
-
-macro_rules! gen_u7 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u7_ $width >](reg: $width, offset: usize) -> u8 {
-                let v = [< get_u8_ $width >](reg, offset);
-                v & 0b1111111
-            }
-            pub fn [< set_u7_ $width >](reg: $width, offset: usize, value: u8) -> $width {
-                let mask = !(0b1111111 << (offset as $width));
-                let v = ((value as $width) & 0b1111111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u7!(u32);
-gen_u7!(u64);
-
-macro_rules! gen_u9 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u9_ $width >](reg: $width, offset: usize) -> u16 {
-                let v = [< get_u16_ $width >](reg, offset);
-                v & 0b111111111
-            }
-            pub fn [< set_u9_ $width >](reg: $width, offset: usize, value: u16) -> $width {
-                let mask = !(0b111111111 << (offset as $width));
-                let v = ((value as $width) & 0b111111111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u9!(u32);
-gen_u9!(u64);
-
-macro_rules! gen_u10 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u10_ $width >](reg: $width, offset: usize) -> u16 {
-                let v = [< get_u16_ $width >](reg, offset);
-                v & 0b1111111111
-            }
-            pub fn [< set_u10_ $width >](reg: $width, offset: usize, value: u16) -> $width {
-                let mask = !(0b1111111111 << (offset as $width));
-                let v = ((value as $width) & 0b1111111111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u10!(u32);
-gen_u10!(u64);
-
-macro_rules! gen_u11 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u11_ $width >](reg: $width, offset: usize) -> u16 {
-                let v = [< get_u16_ $width >](reg, offset);
-                v & 0b11111111111
-            }
-            pub fn [< set_u11_ $width >](reg: $width, offset: usize, value: u16) -> $width {
-                let mask = !(0b11111111111 << (offset as $width));
-                let v = ((value as $width) & 0b11111111111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u11!(u32);
-gen_u11!(u64);
-
-macro_rules! gen_u12 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u12_ $width >](reg: $width, offset: usize) -> u16 {
-                let v = [< get_u16_ $width >](reg, offset);
-                v & 0b111111111111
-            }
-            pub fn [< set_u12_ $width >](reg: $width, offset: usize, value: u16) -> $width {
-                let mask = !(0b111111111111 << (offset as $width));
-                let v = ((value as $width) & 0b111111111111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u12!(u32);
-gen_u12!(u64);
-
-macro_rules! gen_u13 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u13_ $width >](reg: $width, offset: usize) -> u16 {
-                let v = [< get_u16_ $width >](reg, offset);
-                v & 0b1111111111111
-            }
-            pub fn [< set_u13_ $width >](reg: $width, offset: usize, value: u16) -> $width {
-                let mask = !(0b1111111111111 << (offset as $width));
-                let v = ((value as $width) & 0b1111111111111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u13!(u32);
-gen_u13!(u64);
-
-macro_rules! gen_u14 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u14_ $width >](reg: $width, offset: usize) -> u16 {
-                let v = [< get_u16_ $width >](reg, offset);
-                v & 0b11111111111111
-            }
-            pub fn [< set_u14_ $width >](reg: $width, offset: usize, value: u16) -> $width {
-                let mask = !(0b11111111111111 << (offset as $width));
-                let v = ((value as $width) & 0b11111111111111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u14!(u32);
-gen_u14!(u64);
-
-macro_rules! gen_u15 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u15_ $width >](reg: $width, offset: usize) -> u16 {
-                let v = [< get_u16_ $width >](reg, offset);
-                v & 0b111111111111111
-            }
-            pub fn [< set_u15_ $width >](reg: $width, offset: usize, value: u16) -> $width {
-                let mask = !(0b111111111111111 << (offset as $width));
-                let v = ((value as $width) & 0b111111111111111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
-}
-gen_u15!(u32);
-gen_u15!(u64);
-
-macro_rules! gen_u19 {
-    ($width:ident) => {
-        paste::item! {
-            pub fn [< get_u19_ $width >](reg: $width, offset: usize) -> u32 {
-                let v = [< get_u32_ $width >](reg, offset);
-                v & 0b1111111111111111111
-            }
-            pub fn [< set_u19_ $width >](reg: $width, offset: usize, value: u32) -> $width {
-                let mask = !(0b1111111111111111111 << (offset as $width));
-                let v = ((value as $width) & 0b1111111111111111111) << (offset as $width);
-                (reg & mask) | v
-            }
-        }
-    };
+gen_bit!(u128);
+
+/// Generate width-generic field accessors for a storage type. A field of
+/// any width up to the width of the storage type is read or written by
+/// masking off `width` bits at `offset`.
+macro_rules! gen_bits {
+    ($storage:ident) => {
+        paste::item! {
+            pub fn [< get_bits_ $storage >](reg: $storage, offset: usize, width: usize) -> $storage {
+                (reg >> offset) & [< mask_ $storage >](width)
+            }
+            pub fn [< set_bits_ $storage >](
+                reg: $storage,
+                offset: usize,
+                width: usize,
+                value: $storage,
+            ) -> $storage {
+                let mask = [< mask_ $storage >](width) << offset;
+                (reg & !mask) | ((value << offset) & mask)
+            }
+            fn [< mask_ $storage >](width: usize) -> $storage {
+                if width >= $storage::BITS as usize {
+                    $storage::MAX
+                } else {
+                    (1 << width) - 1
+                }
+            }
+        }
+    };
+}
+gen_bits!(u8);
+gen_bits!(u16);
+gen_bits!(u32);
+gen_bits!(u64);
+gen_bits!(u128);
+
+/// Read `width` bits at bit `offset` of a byte slice. Bits are numbered from
+/// the least significant bit of the first byte, so a slice holding the little
+/// endian bytes of an integer reads as [`get_bits_u128`] reads the integer.
+///
+/// Panics if `width` is more than 128 or the bits extend past `data`.
+pub fn get_bits(data: &[u8], offset: usize, width: usize) -> u128 {
+    assert!(width <= 128, "{width} bits do not fit in a u128");
+    let mut value = 0;
+    let mut done = 0;
+    while done < width {
+        let bit = offset + done;
+        let shift = bit % 8;
+        let take = (8 - shift).min(width - done);
+        let chunk = (data[bit / 8] >> shift) & mask_u8(take);
+        value |= u128::from(chunk) << done;
+        done += take;
+    }
+    value
+}
+
+/// Write the low `width` bits of `value` at bit `offset` of a byte slice,
+/// numbered as [`get_bits`] numbers them, leaving other bits untouched.
+///
+/// Panics if `width` is more than 128 or the bits extend past `data`.
+pub fn set_bits(data: &mut [u8], offset: usize, width: usize, value: u128) {
+    assert!(width <= 128, "{width} bits do not fit in a u128");
+    let mut done = 0;
+    while done < width {
+        let bit = offset + done;
+        let shift = bit % 8;
+        let take = (8 - shift).min(width - done);
+        let mask = mask_u8(take);
+        let chunk = (value >> done) as u8 & mask;
+        let byte = &mut data[bit / 8];
+        *byte = (*byte & !(mask << shift)) | (chunk << shift);
+        done += take;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bits_round_trip() {
+        for width in [1, 7, 17, 18, 20, 24, 31] {
+            let max = u32::MAX >> (32 - width);
+            let reg = set_bits_u32(u32::MAX, 32 - width, width, 0);
+            assert_eq!(get_bits_u32(reg, 32 - width, width), 0);
+            assert_eq!(get_bits_u32(reg, 0, 32 - width), u32::MAX >> width);
+            let reg = set_bits_u32(0, 32 - width, width, u32::MAX);
+            assert_eq!(get_bits_u32(reg, 32 - width, width), max);
+        }
+        let reg = set_bits_u128(0, 3, 100, u128::MAX);
+        assert_eq!(get_bits_u128(reg, 3, 100), u128::MAX >> 28);
+        assert_eq!(get_bits_u128(reg, 0, 3), 0);
+        assert_eq!(get_bits_u64(u64::MAX, 0, 64), u64::MAX);
+        assert_eq!(set_bits_u32(0, 0, 32, u32::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn bits_do_not_bleed_into_neighbours() {
+        let reg = set_bits_u32(1 << 8, 5, 3, 0b111);
+        assert_eq!(reg, 0b1_1110_0000);
+        assert_eq!(get_bits_u32(reg, 5, 3), 0b111);
+        assert_eq!(get_bits_u32(reg, 4, 4), 0b1110);
+        assert_eq!(set_bits_u32(reg, 5, 3, 0), 1 << 8);
+    }
+
+    #[test]
+    fn slice_bits_cross_bytes() {
+        let mut data = [0u8; 20];
+        set_bits(&mut data, 5, 7, 0b1011011);
+        assert_eq!(data[..2], [0b0110_0000, 0b0000_1011]);
+        assert_eq!(get_bits(&data, 5, 7), 0b1011011);
+        assert_eq!(get_bits(&data, 4, 9), 0b1011_0110);
+
+        // a full width value spanning 17 bytes at an odd offset
+        set_bits(&mut data, 13, 128, u128::MAX - 2);
+        assert_eq!(get_bits(&data, 13, 128), u128::MAX - 2);
+        assert_eq!(get_bits(&data, 5, 7), 0b1011011);
+        assert_eq!(get_bits(&data, 141, 19), 0);
+        set_bits(&mut data, 13, 128, 0);
+        assert_eq!(get_bits(&data, 0, 128), 0b1011011 << 5);
+    }
+
+    #[test]
+    fn slice_bits_match_integers() {
+        let reg = 0x1234_5678_9abc_def0u64;
+        let data = reg.to_le_bytes();
+        for (offset, width) in [(0, 64), (3, 17), (12, 31), (60, 4), (7, 1)] {
+            assert_eq!(
+                get_bits(&data, offset, width),
+                u128::from(get_bits_u64(reg, offset, width))
+            );
+            let mut data = data;
+            set_bits(&mut data, offset, width, 0x5a5a_5a5a);
+            assert_eq!(
+                u64::from_le_bytes(data),
+                set_bits_u64(reg, offset, width, 0x5a5a_5a5a)
+            );
+        }
+    }
 }
-gen_u19!(u32);
-gen_u19!(u64);
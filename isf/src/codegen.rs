@@ -5,14 +5,74 @@
 //! This module contains a Rust codegen implementation for ISF. The
 //! [`generate`] function produces Rust code from an ISF `[spec::Spec]`.
 
-use std::{collections::BTreeMap, fs::read_to_string};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::read_to_string,
+    path::Path,
+};
 
+use crate::ast::{Endianness, Radix, Timing};
 use crate::spec::{self, AssemblyElement, MachineElement};
+use anyhow::Context;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
+use serde::Deserialize;
 use syn::Ident;
 use winnow::Parser;
 
+/// Options controlling optional parts of generated code.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct CodegenOptions {
+    /// Emit a `fuzz_decode` function usable as a cargo-fuzz target. Crates
+    /// enabling this should declare `cfg(fuzzing)` in their check-cfg lints.
+    pub fuzz: bool,
+    /// Derive serde `Serialize` and `Deserialize` on instruction structs,
+    /// representing each instruction as its raw machine value. Crates
+    /// enabling this must depend on serde with the `derive` feature.
+    pub serde: bool,
+    /// Emit a `#[cfg(test)]` unit test for each assembly example, checking
+    /// that the example parses and emits back to the same text.
+    pub tests: bool,
+    /// Match the string literals of the mnemonic, those before the first
+    /// space in assembly syntax, without regard to case. Emitted assembly
+    /// keeps the case of the spec.
+    pub caseless_mnemonics: bool,
+    /// Emit only code usable from `#![no_std]` crates: machine encoding and
+    /// decoding, field accessors and constants. Assembly parsing and
+    /// emitting, type-erased views and the tools built on them are left
+    /// out. Crates enabling this should depend on isf without its default
+    /// `std` feature.
+    pub no_std: bool,
+    /// Implement `arbitrary::Arbitrary` for instructions, producing values
+    /// that always pass `parse_machine`. The impls are gated on an
+    /// `arbitrary` feature of the crate using them, which should enable an
+    /// optional dependency on arbitrary.
+    pub arbitrary: bool,
+    /// Implement `Debug` for instruction structs by listing the values of
+    /// their named layout elements, as in `Add { opcode: 2, dst: 3 }`,
+    /// instead of deriving it.
+    pub field_debug: bool,
+    /// Type the getters and setters of unsigned fields and constants with
+    /// the storage type of the instruction instead of the narrowest type
+    /// that fits. The narrow accessors remain as `get_<field>_narrow` and
+    /// `set_<field>_narrow`.
+    pub storage_accessors: bool,
+    /// Derive `PartialOrd` and `Ord` on instruction structs and the
+    /// instruction enum. Instructions order by their backing integer, which
+    /// is their machine encoding in host byte order, not by any semantic
+    /// notion of order. The enum orders by instruction first, in spec order.
+    pub ord: bool,
+    /// Emit fields whose class names its values by those names, as in
+    /// `add r3, r4` for a class naming its values `r0` through `r31`,
+    /// instead of by number. Parsing accepts either.
+    pub class_names: bool,
+    /// Generate a `parse_assembly_pattern` method for each instruction that
+    /// also accepts `?` in place of any field operand, as in `add r? r4 r5`,
+    /// for matching and searching over assembly text.
+    pub wildcards: bool,
+}
+
 /// Generate rust code for an ISF file at the given path.
 pub fn generate_code(path: &str) -> anyhow::Result<String> {
     let text = read_to_string(path)?;
@@ -27,84 +87,1516 @@ pub fn generate_code(path: &str) -> anyhow::Result<String> {
     Ok(code)
 }
 
+/// Generate formatted Rust code for the ISF file at `isf_path` and write it
+/// to `out_path`. Spec level documentation is left out so the output can be
+/// brought in with `include!`, typically from a build script writing to
+/// `OUT_DIR`. Errors name the ISF file they arose from.
+pub fn generate_to_file(
+    isf_path: impl AsRef<Path>,
+    out_path: impl AsRef<Path>,
+) -> anyhow::Result<()> {
+    let isf_path = isf_path.as_ref();
+    let out_path = out_path.as_ref();
+    let text = read_to_string(isf_path)
+        .with_context(|| format!("read {}", isf_path.display()))?;
+    let ast = crate::parse::parse
+        .parse(text.as_str())
+        .map_err(|e| anyhow::anyhow!("parse {}: {e}", isf_path.display()))?;
+    let mut spec = spec::form_spec(&ast)
+        .with_context(|| format!("spec {}", isf_path.display()))?;
+    spec.doc.clear();
+    let file: syn::File = syn::parse2(generate(&spec))?;
+    std::fs::write(out_path, prettyplease::unparse(&file))
+        .with_context(|| format!("write {}", out_path.display()))
+}
+
 /// Generate a set of Rust structs for interacting with instructions. The
 /// generated structs implement the [`AssemblyInstruction`] and
 /// [`MachineInstruction`] traits. They also contain getter and setter
-/// methods for each field.
+/// methods for each field. A `Decoder` implementing [`DecoderPlugin`] is
+/// generated for the spec as a whole. Spec level documentation is emitted as
+/// inner doc attributes, so the output is suitable as the contents of a
+/// module file.
 pub fn generate(spec: &spec::Spec) -> TokenStream {
+    generate_with_options(spec, &CodegenOptions::default())
+}
+
+/// Generate Rust code as [`generate`] does, including the optional parts
+/// selected by `options`.
+pub fn generate_with_options(
+    spec: &spec::Spec,
+    options: &CodegenOptions,
+) -> TokenStream {
     let mut tokens = TokenStream::default();
-    let storage = uint_size(spec.instruction_width);
+    for line in spec.doc.lines() {
+        let line = if line.is_empty() {
+            String::default()
+        } else {
+            format!(" {line}")
+        };
+        tokens.extend(quote! { #![doc = #line] });
+    }
+
+    let widest = spec
+        .instructions
+        .iter()
+        .map(|i| i.width)
+        .fold(spec.instruction_width, usize::max);
+    let storage = spec.force_storage.unwrap_or_else(|| uint_size(widest));
+
+    if let Some(name) = &spec.name {
+        tokens.extend(quote! {
+            /// Name of the instruction set.
+            pub const ISA_NAME: &str = #name;
+        });
+    }
+    if let Some(version) = &spec.version {
+        tokens.extend(quote! {
+            /// Version of the instruction set.
+            pub const ISA_VERSION: &str = #version;
+        });
+    }
+
+    tokens.extend(generate_classes(spec));
 
     for instruction in &spec.instructions {
-        let instr_tokens = generate_instruction(storage, instruction);
+        let instr_tokens = generate_instruction(
+            instruction_storage(spec, instruction),
+            instruction.width,
+            spec.endianness,
+            options,
+            instruction,
+        );
         tokens.extend(instr_tokens);
     }
 
+    // A spec of only base instructions has nothing to decode or assemble,
+    // and an empty Instruction enum would leave its matches without arms.
+    if spec.instructions.is_empty() {
+        return tokens;
+    }
+
+    tokens.extend(generate_instruction_enum(storage, spec, options));
+
+    if options.arbitrary {
+        tokens.extend(generate_arbitrary(spec));
+    }
+
+    if options.no_std {
+        return tokens;
+    }
+
+    tokens.extend(generate_disassembler(storage, spec));
+    tokens.extend(generate_assembler(storage, spec));
+    tokens.extend(generate_decoder(storage, spec));
+
+    if options.fuzz {
+        tokens.extend(generate_fuzz_target(storage, spec));
+    }
+
+    if options.tests {
+        tokens.extend(generate_example_tests(spec));
+    }
+
     tokens
 }
 
+/// Generate a test module with one test per assembly example. Each test
+/// parses the example and checks that emitting it again produces the same
+/// text, up to whitespace. Examples of instructions with alternative syntax
+/// may be written in a form other than the emitted one, so for them the
+/// emitted text need only parse back to the same instruction.
+pub fn generate_example_tests(spec: &spec::Spec) -> TokenStream {
+    let mut tests = TokenStream::default();
+
+    for instr in &spec.instructions {
+        let name = format_ident!("{}", instr.name);
+        for (i, example) in instr.assembly.example.iter().enumerate() {
+            let test_name =
+                format_ident!("{}_example_{i}", instr.name.to_lowercase());
+            let text = example.example.as_str();
+            if !instr.assembly.alternatives.is_empty() {
+                tests.extend(quote! {
+                    #[test]
+                    fn #test_name() {
+                        let parsed =
+                            <#name as isf::AssemblyInstruction>::parse_assembly(
+                                #text,
+                            )
+                            .expect("parse example");
+                        let emitted =
+                            isf::AssemblyInstruction::emit_assembly(&parsed);
+                        assert_eq!(
+                            <#name as isf::AssemblyInstruction>::parse_assembly(
+                                &emitted,
+                            )
+                            .ok(),
+                            Some(parsed),
+                        );
+                    }
+                });
+                continue;
+            }
+            tests.extend(quote! {
+                #[test]
+                fn #test_name() {
+                    let parsed =
+                        <#name as isf::AssemblyInstruction>::parse_assembly(
+                            #text,
+                        )
+                        .expect("parse example");
+                    assert_eq!(
+                        normalize(&isf::AssemblyInstruction::emit_assembly(
+                            &parsed
+                        )),
+                        normalize(#text),
+                    );
+                }
+            });
+        }
+    }
+
+    let allow = allow_deprecated(&spec.instructions);
+
+    quote! {
+        #[cfg(test)]
+        #allow
+        mod example_tests {
+            use super::*;
+
+            fn normalize(text: &str) -> String {
+                text.split_whitespace().collect::<Vec<_>>().join(" ")
+            }
+
+            #tests
+        }
+    }
+}
+
+/// Generate a `fuzz_decode` function that decodes arbitrary bytes as each
+/// instruction, re-encodes any successful decode from its field values and
+/// checks that the constant bits survive the round trip. The function is
+/// only compiled under `cfg(fuzzing)`, as set by cargo-fuzz, or `cfg(test)`.
+pub fn generate_fuzz_target(storage: usize, spec: &spec::Spec) -> TokenStream {
+    let size = storage / 8;
+    let storage = format_ident!("u{storage}");
+    let mut attempts = TokenStream::default();
+
+    for instr in &spec.instructions {
+        let name = format_ident!("{}", instr.name);
+        let instr_storage = instruction_storage(spec, instr);
+        let mut set = TokenStream::default();
+        let mut check = TokenStream::default();
+        for f in accessor_fields(instr) {
+            let getter = format_ident!("get_{}", f.name);
+            let setter = format_ident!("set_{}", f.name);
+            set.extend(quote! { encoded.#setter(decoded.#getter()); });
+            check.extend(quote! {
+                assert_eq!(reparsed.#getter(), decoded.#getter());
+            });
+        }
+        let attempt_storage = format_ident!("u{instr_storage}");
+        let attempt = quote! {
+            if let Ok(decoded) =
+                <#name as isf::MachineInstruction<#attempt_storage>>::parse_machine(raw)
+            {
+                let mut encoded = #name::default();
+                #set
+                let reparsed =
+                    <#name as isf::MachineInstruction<#attempt_storage>>::parse_machine(
+                        isf::MachineInstruction::emit_machine(&encoded),
+                    )
+                    .expect("constant bits round-trip");
+                #check
+            }
+        };
+        if attempt_storage == storage {
+            attempts.extend(attempt);
+        } else {
+            let size = instr_storage / 8;
+            attempts.extend(quote! {
+                if let Some(bytes) = data.get(..#size) {
+                    let raw =
+                        #attempt_storage::from_ne_bytes(bytes.try_into().unwrap());
+                    #attempt
+                }
+            });
+        }
+    }
+
+    let allow = allow_deprecated(&spec.instructions);
+
+    quote! {
+        /// Decode arbitrary bytes as each instruction and check that
+        /// successful decodes re-encode with the same constant bits. Suitable
+        /// for use in a cargo-fuzz `fuzz_target!`.
+        #[cfg(any(fuzzing, test))]
+        #allow
+        pub fn fuzz_decode(data: &[u8]) {
+            let Some(bytes) = data.get(..#size) else {
+                return;
+            };
+            let raw = #storage::from_ne_bytes(bytes.try_into().unwrap());
+            #attempts
+        }
+    }
+}
+
+/// Generate `arbitrary::Arbitrary` impls for each instruction and for the
+/// instruction enum. Each non-constant field gets a random value that fits
+/// it, so generated instructions always decode.
+pub fn generate_arbitrary(spec: &spec::Spec) -> TokenStream {
+    let mut tks = TokenStream::default();
+    let allow = allow_deprecated(&spec.instructions);
+
+    for instr in &spec.instructions {
+        let name = format_ident!("{}", instr.name);
+        let mut set = TokenStream::default();
+        for f in accessor_fields(instr) {
+            if f.value.is_some() {
+                continue;
+            }
+            set.extend(arbitrary_field(instr, f));
+        }
+        let result = if set.is_empty() {
+            quote! { let result = Self::default(); }
+        } else {
+            quote! { let mut result = Self::default(); }
+        };
+        tks.extend(quote! {
+            #[cfg(feature = "arbitrary")]
+            #allow
+            impl<'a> arbitrary::Arbitrary<'a> for #name {
+                fn arbitrary(
+                    u: &mut arbitrary::Unstructured<'a>,
+                ) -> arbitrary::Result<Self> {
+                    #result
+                    #set
+                    Ok(result)
+                }
+            }
+        });
+    }
+
+    let names: Vec<_> = spec
+        .instructions
+        .iter()
+        .map(|i| format_ident!("{}", i.name))
+        .collect();
+    let indices = 0..names.len();
+    let count = names.len();
+    tks.extend(quote! {
+        #[cfg(feature = "arbitrary")]
+        #allow
+        impl<'a> arbitrary::Arbitrary<'a> for Instruction {
+            fn arbitrary(
+                u: &mut arbitrary::Unstructured<'a>,
+            ) -> arbitrary::Result<Self> {
+                Ok(match u.choose_index(#count)? {
+                    #(#indices => Self::#names(u.arbitrary()?),)*
+                    _ => unreachable!(),
+                })
+            }
+        }
+    });
+
+    tks
+}
+
+/// Set a field to a random value within its range.
+fn arbitrary_field(instr: &spec::Instruction, f: &spec::Field) -> TokenStream {
+    let setter = format_ident!("set_{}", f.name);
+    let width = f.width;
+    if let Some(allowed) = &f.allowed {
+        let values = allowed_values(allowed);
+        let value = if width == 1 {
+            quote! { value != 0 }
+        } else {
+            quote! { value.try_into().unwrap() }
+        };
+        return quote! {
+            let value: u64 = *u.choose(#values)?;
+            result.#setter(#value);
+        };
+    }
+    let max = proc_macro2::Literal::u128_unsuffixed(u128::MAX >> (128 - width));
+    if f.class.is_some() {
+        let try_setter = format_ident!("try_set_{}", f.name);
+        let raw = format_ident!("u{}", uint_size(width));
+        return quote! {
+            result
+                .#try_setter(u.int_in_range::<#raw>(0..=#max)?)
+                .expect("class covers field values");
+        };
+    }
+    if width == 1 && !instr.is_sign_magnitude(&f.name) {
+        return quote! { result.#setter(u.arbitrary()?); };
+    }
+    if f.is_signed {
+        let ty = format_ident!("i{}", uint_size(width));
+        let max =
+            proc_macro2::Literal::i128_unsuffixed((1i128 << (width - 1)) - 1);
+        return quote! {
+            result.#setter(u.int_in_range::<#ty>(-#max - 1..=#max)?);
+        };
+    }
+    if instr.is_sign_magnitude(&f.name) {
+        let ty = format_ident!("i{}", uint_size(width + 1));
+        return quote! {
+            result.#setter(u.int_in_range::<#ty>(-#max..=#max)?);
+        };
+    }
+    quote! {
+        result.#setter(u.int_in_range(0..=#max)?);
+    }
+}
+
+/// Allow the use of deprecated instructions in generated code that must refer
+/// to them.
+fn allow_deprecated(instrs: &[spec::Instruction]) -> Option<TokenStream> {
+    instrs
+        .iter()
+        .any(|i| i.deprecated.is_some())
+        .then(|| quote! { #[allow(deprecated)] })
+}
+
+/// Fields with public getters and setters.
+fn accessor_fields(instr: &spec::Instruction) -> Vec<&spec::Field> {
+    instr
+        .fields
+        .iter()
+        .filter(|f| {
+            instr.machine.layout.iter().any(|me| match me {
+                MachineElement::Field { name }
+                | MachineElement::FieldNegate { name }
+                | MachineElement::FieldSlice { name, .. } => name == &f.name,
+                _ => false,
+            })
+        })
+        .collect()
+}
+
+/// Generate a type for each class, in name order. Classes with named values
+/// become enums and other classes become newtypes over an integer.
+pub fn generate_classes(spec: &spec::Spec) -> TokenStream {
+    let mut classes: Vec<_> = spec.classes.values().collect();
+    classes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut tks = TokenStream::default();
+    for c in classes {
+        tks.extend(generate_class(c));
+    }
+    tks
+}
+
+fn generate_class(c: &spec::Class) -> TokenStream {
+    let name = format_ident!("{}", c.name);
+    let name_s = c.name.as_str();
+    let doc = format!(" {}", c.doc);
+    let raw = format_ident!("u{}", uint_size(c.width));
+    let shift = c.width as u32;
+    let error = quote! {
+        isf::ClassValueError {
+            class: #name_s,
+            value: value as u128,
+        }
+    };
+
+    if c.values.is_empty() {
+        return quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            pub struct #name(#raw);
+
+            impl TryFrom<#raw> for #name {
+                type Error = isf::ClassValueError;
+                fn try_from(value: #raw) -> Result<Self, Self::Error> {
+                    if (value as u128).checked_shr(#shift).unwrap_or(0) != 0 {
+                        return Err(#error);
+                    }
+                    Ok(Self(value))
+                }
+            }
+
+            impl From<#name> for #raw {
+                fn from(value: #name) -> Self {
+                    value.0
+                }
+            }
+
+            impl core::fmt::Display for #name {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    write!(f, "{}", self.0)
+                }
+            }
+
+            impl #name {
+                /// The name of this value in the spec. Values of this class
+                /// are unnamed.
+                pub const fn name(self) -> Option<&'static str> {
+                    None
+                }
+                /// The value with the given name in the spec. Values of this
+                /// class are unnamed.
+                pub fn from_name(_name: &str) -> Option<Self> {
+                    None
+                }
+            }
+        };
+    }
+
+    let variants: Vec<_> =
+        c.values.iter().map(|v| variant_name(&v.name)).collect();
+    let values: Vec<_> = c
+        .values
+        .iter()
+        .map(|v| proc_macro2::Literal::u64_unsuffixed(v.value))
+        .collect();
+    let docs = c.values.iter().map(|v| format!(" {}", v.doc));
+    let names: Vec<_> = c.values.iter().map(|v| v.name.as_str()).collect();
+
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[repr(#raw)]
+        pub enum #name {
+            #(
+                #[doc = #docs]
+                #variants = #values,
+            )*
+        }
+
+        impl TryFrom<#raw> for #name {
+            type Error = isf::ClassValueError;
+            fn try_from(value: #raw) -> Result<Self, Self::Error> {
+                match value {
+                    #(#values => Ok(Self::#variants),)*
+                    _ => Err(#error),
+                }
+            }
+        }
+
+        impl From<#name> for #raw {
+            fn from(value: #name) -> Self {
+                value as #raw
+            }
+        }
+
+        impl core::fmt::Display for #name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{}", #raw::from(*self))
+            }
+        }
+
+        impl #name {
+            /// The name of this value in the spec.
+            pub const fn name(self) -> Option<&'static str> {
+                Some(match self {
+                    #(Self::#variants => #names,)*
+                })
+            }
+            /// The value with the given name in the spec.
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    #(#names => Some(Self::#variants),)*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// Convert a class value name such as `r0` or `stack_ptr` to an enum
+/// variant name such as `R0` or `StackPtr`.
+fn variant_name(name: &str) -> Ident {
+    let name: String = name
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => {
+                    first.to_ascii_uppercase().to_string() + chars.as_str()
+                }
+                None => String::default(),
+            }
+        })
+        .collect();
+    format_ident!("{name}")
+}
+
+pub fn generate_instruction_enum(
+    storage: usize,
+    spec: &spec::Spec,
+    options: &CodegenOptions,
+) -> TokenStream {
+    let storage = format_ident!("u{storage}");
+    let names: Vec<_> = spec
+        .instructions
+        .iter()
+        .map(|i| format_ident!("{}", i.name))
+        .collect();
+    let allow = allow_deprecated(&spec.instructions);
+    let (from_machine, to_machine) = match spec.endianness {
+        Endianness::Little => (quote! { from_le }, quote! { to_le }),
+        Endianness::Big => (quote! { from_be }, quote! { to_be }),
+    };
+    let mut emits = Vec::new();
+    let mut attempts = TokenStream::default();
+    for (instr, name) in spec.instructions.iter().zip(&names) {
+        let instr_storage =
+            format_ident!("u{}", instruction_storage(spec, instr));
+        emits.push(widen_machine(
+            quote! { isf::MachineInstruction::emit_machine(i) },
+            &instr_storage,
+            &storage,
+            spec.endianness,
+        ));
+        let attempt = |value: TokenStream, raw: TokenStream| {
+            quote! {
+                if #value & #name::OPCODE_MASK == #name::OPCODE_MATCH {
+                    if let Ok(i) =
+                        <#name as isf::MachineInstruction<#instr_storage>>::parse_machine(#raw)
+                    {
+                        return Some(Instruction::#name(i));
+                    }
+                }
+            }
+        };
+        if instr_storage == storage {
+            attempts.extend(attempt(quote! { value }, quote! { raw }));
+        } else {
+            let attempt =
+                attempt(quote! { narrow }, quote! { narrow.#to_machine() });
+            attempts.extend(quote! {
+                if let Ok(narrow) = #instr_storage::try_from(value) {
+                    #attempt
+                }
+            });
+        }
+    }
+    let emit_assembly = (!options.no_std).then(|| {
+        quote! {
+            /// Emit the instruction in assembly form.
+            pub fn emit_assembly(&self) -> String {
+                match self {
+                    #(Self::#names(i) => isf::AssemblyInstruction::emit_assembly(i)),*
+                }
+            }
+        }
+    });
+
+    let ord = options.ord.then(|| quote! { #[derive(PartialOrd, Ord)] });
+
+    quote! {
+        /// Any instruction of this spec.
+        #allow
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #ord
+        pub enum Instruction {
+            #(#names(#names)),*
+        }
+
+        #allow
+        impl Instruction {
+            /// Emit the instruction in machine form.
+            pub fn emit_machine(&self) -> #storage {
+                match self {
+                    #(Self::#names(i) => #emits),*
+                }
+            }
+
+            #emit_assembly
+        }
+
+        /// Decode a machine word as the first instruction whose constant
+        /// fields match, in spec order.
+        #allow
+        pub fn decode(raw: #storage) -> Option<Instruction> {
+            let value = #storage::#from_machine(raw);
+            #attempts
+            None
+        }
+    }
+}
+
+pub fn generate_disassembler(storage: usize, spec: &spec::Spec) -> TokenStream {
+    let storage = format_ident!("u{storage}");
+    let size = spec.instruction_width.div_ceil(8);
+    let (bytes, to_memory) = match spec.endianness {
+        Endianness::Little => (quote! { chunk.iter().rev() }, quote! { to_le }),
+        Endianness::Big => (quote! { chunk.iter() }, quote! { to_be }),
+    };
+
+    // With mixed widths each instruction is tried against its own number of
+    // bytes, and an undecodable word is skipped at the default width.
+    let mixed = spec
+        .instructions
+        .iter()
+        .any(|i| i.width != spec.instruction_width);
+    let next = if mixed {
+        let allow = allow_deprecated(&spec.instructions);
+        let mut attempts = TokenStream::default();
+        for instr in &spec.instructions {
+            let name = format_ident!("{}", instr.name);
+            let instr_storage =
+                format_ident!("u{}", instruction_storage(spec, instr));
+            let instr_size = instr.width.div_ceil(8);
+            attempts.extend(quote! {
+                if let Some(chunk) = rest.get(..#instr_size) {
+                    let raw = #bytes.fold(0u128, |v, b| v << 8 | u128::from(*b));
+                    let value = (raw as #instr_storage).#to_memory();
+                    if let Ok(i) =
+                        <#name as isf::MachineInstruction<#instr_storage>>::parse_machine(value)
+                    {
+                        self.offset += #instr_size;
+                        return Some(Ok((offset, Instruction::#name(i))));
+                    }
+                }
+            });
+        }
+        quote! {
+            #allow
+            fn next(&mut self) -> Option<Self::Item> {
+                let offset = self.offset;
+                let rest = self.bytes.get(offset..)?;
+                #attempts
+                let chunk = rest.get(..#size)?;
+                self.offset += #size;
+                let raw = #bytes.fold(0u128, |v, b| v << 8 | u128::from(*b));
+                Some(Err(isf::DecodeError { offset, raw }))
+            }
+        }
+    } else {
+        quote! {
+            fn next(&mut self) -> Option<Self::Item> {
+                let offset = self.offset;
+                let chunk = self.bytes.get(offset..offset + #size)?;
+                self.offset += #size;
+                let raw = #bytes.fold(0u128, |v, b| v << 8 | u128::from(*b));
+                let value = (raw as #storage).#to_memory();
+                Some(
+                    decode(value)
+                        .map(|i| (offset, i))
+                        .ok_or(isf::DecodeError { offset, raw }),
+                )
+            }
+        }
+    };
+
+    quote! {
+        /// Iterator over the instructions in a byte buffer, created by
+        /// [`disassemble`].
+        #[derive(Debug, Clone)]
+        pub struct DisassembleIter<'a> {
+            bytes: &'a [u8],
+            offset: usize,
+        }
+
+        impl Iterator for DisassembleIter<'_> {
+            type Item = Result<(usize, Instruction), isf::DecodeError>;
+
+            #next
+        }
+
+        /// Decode the instructions in a byte buffer in order, yielding each
+        /// with its byte offset. A trailing partial instruction is ignored.
+        pub fn disassemble(bytes: &[u8]) -> DisassembleIter<'_> {
+            DisassembleIter { bytes, offset: 0 }
+        }
+    }
+}
+
+pub fn generate_assembler(storage: usize, spec: &spec::Spec) -> TokenStream {
+    let storage = format_ident!("u{storage}");
+    let names = spec
+        .instructions
+        .iter()
+        .map(|i| format_ident!("{}", i.name));
+    let emits = spec.instructions.iter().map(|instr| {
+        let instr_storage =
+            format_ident!("u{}", instruction_storage(spec, instr));
+        widen_machine(
+            quote! { isf::MachineInstruction::emit_machine(&i) },
+            &instr_storage,
+            &storage,
+            spec.endianness,
+        )
+    });
+    let allow = allow_deprecated(&spec.instructions);
+
+    quote! {
+        /// Assemble a program with one instruction per line into machine
+        /// words. Blank lines, `//` comments and trailing semicolons are
+        /// ignored.
+        #allow
+        pub fn assemble(source: &str) -> Result<Vec<#storage>, isf::AssembleError> {
+            let mut words = Vec::new();
+            for (n, line) in source.lines().enumerate() {
+                let text = line.split("//").next().unwrap_or_default().trim();
+                let text = text.strip_suffix(';').unwrap_or(text).trim_end();
+                if text.is_empty() {
+                    continue;
+                }
+                #(
+                    if let Ok(i) = <#names as isf::AssemblyInstruction>::parse_assembly(text) {
+                        words.push(#emits);
+                        continue;
+                    }
+                )*
+                return Err(isf::AssembleError {
+                    line: n + 1,
+                    text: text.to_owned(),
+                });
+            }
+            Ok(words)
+        }
+    }
+}
+
+pub fn generate_decoder(storage: usize, spec: &spec::Spec) -> TokenStream {
+    let storage = format_ident!("u{storage}");
+    let mut attempts = TokenStream::default();
+
+    for instr in &spec.instructions {
+        let name = format_ident!("{}", instr.name);
+        let instr_storage =
+            format_ident!("u{}", instruction_storage(spec, instr));
+        let attempt = quote! {
+            if let Ok(i) =
+                <#name as isf::MachineInstruction<#instr_storage>>::parse_machine(data)
+            {
+                return Some(Box::new(i));
+            }
+        };
+        if instr_storage == storage {
+            attempts.extend(attempt);
+        } else {
+            attempts.extend(quote! {
+                if let Ok(data) = #instr_storage::try_from(word) {
+                    #attempt
+                }
+            });
+        }
+    }
+
+    let allow = allow_deprecated(&spec.instructions);
+
+    quote! {
+        /// Decoder for the instructions of this spec.
+        #[derive(Debug, Default)]
+        pub struct Decoder;
+
+        #allow
+        impl isf::DecoderPlugin for Decoder {
+            fn try_decode(
+                &self,
+                word: u128,
+            ) -> Option<Box<dyn isf::DecodedInstruction>> {
+                let data = #storage::try_from(word).ok()?;
+                #attempts
+                None
+            }
+        }
+    }
+}
+
 pub fn generate_instruction(
     storage: usize,
+    instruction_width: usize,
+    endianness: Endianness,
+    options: &CodegenOptions,
     instr: &spec::Instruction,
 ) -> TokenStream {
     let name = format_ident!("{}", instr.name);
+    let name_s = instr.name.as_str();
+    let storage_size = storage;
     let storage = format_ident!("u{storage}");
 
     let default_impl = generate_default_impl(instr);
-    let field_methods = generate_field_methods(instr, &storage);
-    let assembly_parser = generate_assembly_parser(instr);
-    let assembly_emitter = generate_assembly_emitter(instr);
-    let machine_parser = generate_machine_parser(instr);
+    let field_methods = generate_field_methods(instr, &storage, options);
+    let assembly_parser = generate_assembly_parser(instr, options, false);
+    let assembly_emitter = generate_assembly_emitter(instr, options);
+    let machine_parser = generate_machine_parser(&storage, endianness, instr);
+    let view = generate_view(instr);
+    let timing = generate_timing(instr.timing);
+    let opcode_consts = generate_opcode_consts(instr, &storage);
+    let all_encodings = generate_all_encodings(instr, &storage);
+    let strict_parser =
+        generate_strict_machine_parser(&storage, endianness, instr);
+    let constant_consts = generate_constant_consts(instr);
+    let field_consts = generate_field_consts(instr, &storage);
+    let field_info = generate_field_info(instr);
+    let width = proc_macro2::Literal::usize_unsuffixed(instruction_width);
+    let (from_machine, to_machine) = match endianness {
+        Endianness::Little => (quote! { from_le }, quote! { to_le }),
+        Endianness::Big => (quote! { from_be }, quote! { to_be }),
+    };
+    // The bytes of the instruction are the low order bytes of the storage
+    // type, which come first in little endian and last in big endian.
+    let size = instruction_width.div_ceil(8);
+    let storage_bytes = storage_size / 8;
+    let start = proc_macro2::Literal::usize_unsuffixed(storage_bytes - size);
+    let size = proc_macro2::Literal::usize_unsuffixed(size);
+    let storage_bytes = proc_macro2::Literal::usize_unsuffixed(storage_bytes);
+    let (to_bytes, from_bytes, byte_range) = match endianness {
+        Endianness::Little => (
+            quote! { to_le_bytes },
+            quote! { from_le_bytes },
+            quote! { ..#size },
+        ),
+        Endianness::Big => (
+            quote! { to_be_bytes },
+            quote! { from_be_bytes },
+            quote! { #start.. },
+        ),
+    };
+    // Bits of the storage type above the instruction width never leave the
+    // instruction.
+    let value = if instruction_width < storage_size {
+        let mask =
+            proc_macro2::Literal::u128_unsuffixed((1 << instruction_width) - 1);
+        quote! { (self.0 & #mask) }
+    } else {
+        quote! { self.0 }
+    };
+
+    let doc = format!(" {}", instr.doc);
+    let deprecated = instr
+        .deprecated
+        .as_ref()
+        .map(|note| quote! { #[deprecated(note = #note)] });
+    let allow = allow_deprecated(std::slice::from_ref(instr));
+    let serde = options.serde.then(|| {
+        quote! {
+            #[derive(serde::Serialize, serde::Deserialize)]
+            #[serde(transparent)]
+        }
+    });
+    // Ordering compares the backing integers, so it follows the machine
+    // encoding rather than field values in declaration order.
+    let ord = options.ord.then(|| quote! { #[derive(PartialOrd, Ord)] });
+
+    // Timing, views and assembly need the standard library.
+    let std = !options.no_std;
+    let timing = std.then(|| {
+        quote! {
+            /// Timing of this instruction.
+            pub const TIMING: isf::Timing = #timing;
+        }
+    });
+    let (debug_names, debug_values): (Vec<_>, Vec<_>) =
+        layout_values(instr, field_value).into_iter().unzip();
+    let pattern_methods = options.wildcards.then(|| {
+        let pattern_parser = generate_assembly_parser(instr, options, true);
+        quote! {
+            /// Parse an assembly instruction from text as `parse_assembly`
+            /// does, also accepting `?` in place of any field operand.
+            /// Wildcarded fields are left at their defaults and their names
+            /// are returned along with the instruction, in text order.
+            pub fn parse_assembly_pattern(
+                mut text: &str,
+            ) -> Result<
+                (Self, Vec<&'static str>),
+                winnow::error::ParseError<&str, winnow::error::ContextError>,
+            > {
+                use winnow::Parser;
+                Self::parse_assembly_pattern_impl.parse(&mut text)
+            }
+            fn parse_assembly_pattern_impl(
+                text: &mut &str,
+            ) -> winnow::PResult<(Self, Vec<&'static str>)> {
+                use winnow::Parser;
+                let input = text;
+                #pattern_parser
+            }
+        }
+    });
+    let std_methods = std.then(|| {
+        quote! {
+            /// The name and value of each field and named constant, in
+            /// machine layout order. Signed fields are given as the two's
+            /// complement bits of their width, plus the sign bit for
+            /// sign-magnitude fields.
+            pub fn debug_fields(&self) -> Vec<(String, u128)> {
+                vec![#((#debug_names.to_owned(), #debug_values)),*]
+            }
+            /// Type-erased view of this instruction's operands and timing.
+            pub fn view(&self) -> isf::spec::DecodedView {
+                #view
+            }
+            fn parse_assembly_impl(text: &mut &str) -> winnow::PResult<Self> {
+                use winnow::Parser;
+                let input = text;
+                #assembly_parser
+            }
+            /// Parse an assembly instruction from the front of text, returning
+            /// it with the text that follows it. Unlike `parse_assembly`, the
+            /// instruction need not span the whole text.
+            pub fn parse_assembly_partial(
+                text: &str,
+            ) -> Result<
+                (Self, &str),
+                winnow::error::ParseError<&str, winnow::error::ContextError>,
+            > {
+                use winnow::Parser;
+                (Self::parse_assembly_impl, winnow::combinator::rest).parse(text)
+            }
+            #pattern_methods
+        }
+    });
+    let assembly_impls = std.then(|| {
+        quote! {
+            #allow
+            impl isf::AssemblyInstruction for #name {
+                fn parse_assembly(
+                    mut text: &str,
+                ) -> Result<
+                    Self,
+                    winnow::error::ParseError<&str, winnow::error::ContextError>,
+                > {
+                    use winnow::Parser;
+                    let result = Self::parse_assembly_impl.parse(&mut text)?;
+                    Ok(result)
+                }
+                fn emit_assembly(&self) -> String {
+                    #assembly_emitter
+                }
+            }
+
+            #allow
+            impl std::fmt::Display for #name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(&isf::AssemblyInstruction::emit_assembly(self))
+                }
+            }
+        }
+    });
+    let decoded_impl = std.then(|| {
+        quote! {
+            #allow
+            impl isf::DecodedInstruction for #name {
+                fn name(&self) -> &'static str {
+                    #name_s
+                }
+                fn assembly(&self) -> String {
+                    isf::AssemblyInstruction::emit_assembly(self)
+                }
+                fn machine(&self) -> u128 {
+                    u128::from(#value)
+                }
+                fn view(&self) -> isf::spec::DecodedView {
+                    #name::view(self)
+                }
+            }
+        }
+    });
 
-    let doc = format!(" {}", instr.doc);
+    let (derive_debug, field_debug) = if options.field_debug {
+        (None, Some(generate_field_debug(instr)))
+    } else {
+        (Some(quote! { Debug, }), None)
+    };
 
-    let generated = quote! {
+    // The struct is laid out exactly as its backing integer, so slices of
+    // instructions may be reinterpreted as slices of machine words.
+    quote! {
         #[doc = #doc]
-        #[derive(Debug, PartialEq, Eq)]
+        #deprecated
+        #[derive(#derive_debug Clone, Copy, PartialEq, Eq, Hash)]
+        #ord
+        #serde
+        #[repr(transparent)]
         pub struct #name(#storage);
 
+        #field_debug
+
+        #allow
         impl Default for #name {
             fn default() -> Self {
                 #default_impl
             }
         }
 
+        #allow
         impl #name {
+            /// Width of this instruction in bits.
+            pub const WIDTH: usize = #width;
+            #timing
+            #opcode_consts
+            #constant_consts
+            #field_consts
             #field_methods
-            fn parse_assembly_impl(text: &mut &str) -> winnow::PResult<Self> {
-                use winnow::Parser;
-                let input = text;
-                #assembly_parser
+            /// The backing integer of this instruction, in host byte order
+            /// and without masking to the instruction width.
+            pub fn raw(&self) -> #storage {
+                self.0
             }
-        }
-
-        impl isf::AssemblyInstruction for #name {
-            fn parse_assembly(
-                mut text: &str,
-            ) -> Result<
-                Self,
-                winnow::error::ParseError<&str, winnow::error::ContextError>,
-            > {
-                use winnow::Parser;
-                let result = Self::parse_assembly_impl.parse(&mut text)?;
-                Ok(result)
+            /// Construct an instruction from its backing integer, in host
+            /// byte order, without checking its constant fields. Use
+            /// [`isf::MachineInstruction::parse_machine`] to validate.
+            pub fn from_raw_unchecked(raw: #storage) -> Self {
+                Self(raw)
+            }
+            /// Width of this instruction in bits, [`Self::WIDTH`].
+            pub fn width_bits(&self) -> usize {
+                Self::WIDTH
+            }
+            /// Placement of this instruction's fields in the machine layout.
+            pub fn fields() -> &'static [isf::FieldInfo] {
+                &[#field_info]
             }
-            fn emit_assembly(&self) -> String {
-                #assembly_emitter
+            /// Whether the constant fields of a machine word hold this
+            /// instruction's values. Cheaper than
+            /// [`isf::MachineInstruction::parse_machine`], which also checks
+            /// field values.
+            pub const fn matches(data: #storage) -> bool {
+                #storage::#from_machine(data) & Self::OPCODE_MASK == Self::OPCODE_MATCH
             }
+            /// The machine encoding of this instruction as exactly as many
+            /// bytes as its width needs, in the byte order of the spec.
+            pub fn emit_machine_bytes(&self) -> [u8; #size] {
+                let bytes = #value.#to_bytes();
+                let mut result = [0; #size];
+                result.copy_from_slice(&bytes[#byte_range]);
+                result
+            }
+            /// Parse an instruction from bytes laid out as
+            /// [`Self::emit_machine_bytes`] produces them. `None` if `data`
+            /// is of another length or does not hold this instruction.
+            pub fn parse_machine_bytes(data: &[u8]) -> Option<Self> {
+                if data.len() != #size {
+                    return None;
+                }
+                let mut bytes = [0; #storage_bytes];
+                bytes[#byte_range].copy_from_slice(data);
+                let value = #storage::#from_bytes(bytes).#to_machine();
+                isf::MachineInstruction::parse_machine(value).ok()
+            }
+            #strict_parser
+            #all_encodings
+            #std_methods
         }
 
+        #assembly_impls
+
+        #allow
         impl isf::MachineInstruction<#storage> for #name {
             fn parse_machine(data: #storage) -> Result<Self, isf::FieldMismatchError> {
                 #machine_parser
             }
             fn emit_machine(&self) -> #storage {
-                self.0
+                #value.#to_machine()
+            }
+        }
+
+        #allow
+        impl TryFrom<#storage> for #name {
+            type Error = isf::FieldMismatchError;
+            fn try_from(data: #storage) -> Result<Self, Self::Error> {
+                isf::MachineInstruction::parse_machine(data)
             }
         }
+
+        #allow
+        impl From<#name> for #storage {
+            fn from(value: #name) -> Self {
+                isf::MachineInstruction::emit_machine(&value)
+            }
+        }
+
+        #decoded_impl
+    }
+}
+
+/// The bits held by constant layout elements and their values, as a mask and
+/// the value of the masked bits.
+pub fn generate_opcode_consts(
+    instr: &spec::Instruction,
+    storage: &Ident,
+) -> TokenStream {
+    let ranges = instr
+        .layout_ranges()
+        .unwrap_or_else(|e| panic!("machine layout: {e}"));
+    let mut mask = 0u128;
+    let mut matches = 0u128;
+    for (me, (_, offset, width)) in instr.machine.layout.iter().zip(ranges) {
+        let MachineElement::Constant {
+            value: Some(value), ..
+        } = me
+        else {
+            continue;
+        };
+        if width == 0 {
+            continue;
+        }
+        mask |= (u128::MAX >> (128 - width)) << offset;
+        matches |= u128::from(*value) << offset;
+    }
+    let mask: proc_macro2::Literal = format!("{mask:#x}").parse().unwrap();
+    let matches: proc_macro2::Literal =
+        format!("{matches:#x}").parse().unwrap();
+    quote! {
+        /// Bits of the machine word held by constant fields.
+        pub const OPCODE_MASK: #storage = #mask;
+        /// Value of the bits in [`Self::OPCODE_MASK`] for this instruction.
+        pub const OPCODE_MATCH: #storage = #matches;
+    }
+}
+
+/// An `all_encodings` method iterating over every value of the bits held by
+/// fields, with constant layout elements fixed.
+pub fn generate_all_encodings(
+    instr: &spec::Instruction,
+    storage: &Ident,
+) -> TokenStream {
+    let ranges = instr
+        .layout_ranges()
+        .unwrap_or_else(|e| panic!("machine layout: {e}"));
+    let mut mask = 0u128;
+    for (me, (_, offset, width)) in instr.machine.layout.iter().zip(ranges) {
+        if width == 0 || matches!(me, MachineElement::Constant { .. }) {
+            continue;
+        }
+        mask |= (u128::MAX >> (128 - width)) << offset;
+    }
+    let mask: proc_macro2::Literal = format!("{mask:#x}").parse().unwrap();
+    // Only fields with allowed values can make a combination illegal.
+    let filter = instr.fields.iter().any(|f| f.allowed.is_some()).then(|| {
+        quote! {
+            .filter(|i| {
+                <Self as isf::MachineInstruction<#storage>>::parse_machine(
+                    isf::MachineInstruction::emit_machine(i),
+                )
+                .is_ok()
+            })
+        }
+    });
+    quote! {
+        /// Every legal encoding of this instruction, in increasing order of
+        /// the backing integer. Fields take each of their values while
+        /// constant fields hold theirs and reserved bits are zero. There are
+        /// two to the power of the total field width of them, so this is
+        /// only tractable for instructions with few field bits.
+        pub fn all_encodings() -> impl Iterator<Item = Self> {
+            const FIELD_MASK: #storage = #mask;
+            core::iter::successors(Some::<#storage>(0), |bits| {
+                let next = bits.wrapping_sub(FIELD_MASK) & FIELD_MASK;
+                (next != 0).then_some(next)
+            })
+            .map(|bits| Self(Self::OPCODE_MATCH | bits))
+            #filter
+        }
+    }
+}
+
+/// A `parse_machine_strict` method that also requires the reserved `_`
+/// regions of the layout to be zero.
+pub fn generate_strict_machine_parser(
+    storage: &Ident,
+    endianness: Endianness,
+    instr: &spec::Instruction,
+) -> TokenStream {
+    let ranges = instr
+        .layout_ranges()
+        .unwrap_or_else(|e| panic!("machine layout: {e}"));
+    let from_machine = match endianness {
+        Endianness::Little => quote! { from_le },
+        Endianness::Big => quote! { from_be },
+    };
+    let mut checks = TokenStream::default();
+    for (me, (_, offset, width)) in instr.machine.layout.iter().zip(ranges) {
+        let MachineElement::Constant {
+            name, value: None, ..
+        } = me
+        else {
+            continue;
+        };
+        if name != "_" || width == 0 {
+            continue;
+        }
+        let mask: proc_macro2::Literal =
+            format!("{:#x}", u128::MAX >> (128 - width))
+                .parse()
+                .unwrap();
+        checks.extend(quote! {
+            let found = (value >> #offset) & #mask;
+            if found != 0 {
+                return Err(isf::FieldMismatchError {
+                    field: "_",
+                    expected: 0,
+                    allowed: &[],
+                    found: found as u64,
+                });
+            }
+        });
+    }
+    let value = if checks.is_empty() {
+        None
+    } else {
+        Some(quote! { let value = #storage::#from_machine(data); })
     };
+    quote! {
+        /// Parse a machine instruction as
+        /// [`isf::MachineInstruction::parse_machine`] does, additionally
+        /// requiring reserved bits to be zero.
+        pub fn parse_machine_strict(
+            data: #storage,
+        ) -> Result<Self, isf::FieldMismatchError> {
+            let result =
+                <Self as isf::MachineInstruction<#storage>>::parse_machine(data)?;
+            #value
+            #checks
+            Ok(result)
+        }
+    }
+}
+
+/// An associated const holding the value of each named constant layout
+/// element, typed as the element's getter is.
+pub fn generate_constant_consts(instr: &spec::Instruction) -> TokenStream {
+    let mut tks = TokenStream::default();
+    for me in &instr.machine.layout {
+        let MachineElement::Constant {
+            name,
+            width,
+            value: Some(value),
+        } = me
+        else {
+            continue;
+        };
+        if name == "_" {
+            continue;
+        }
+        let doc = format!(" Value of the constant `{name}` field.");
+        let konst = format_ident!("{}", name.to_uppercase());
+        if *width == 1 {
+            let value = *value != 0;
+            tks.extend(quote! {
+                #[doc = #doc]
+                pub const #konst: bool = #value;
+            });
+        } else {
+            let ty = format_ident!("u{}", uint_size(*width));
+            let value = proc_macro2::Literal::u64_unsuffixed(*value);
+            tks.extend(quote! {
+                #[doc = #doc]
+                pub const #konst: #ty = #value;
+            });
+        }
+    }
+    tks
+}
+
+/// A mask const for the bits of the machine word holding each field, and a
+/// shift const for fields held by a single layout element.
+pub fn generate_field_consts(
+    instr: &spec::Instruction,
+    storage: &Ident,
+) -> TokenStream {
+    let ranges = instr
+        .layout_ranges()
+        .unwrap_or_else(|e| panic!("machine layout: {e}"));
+    let mut tks = TokenStream::default();
+    for f in &instr.fields {
+        let mut mask = 0u128;
+        let mut offsets = Vec::new();
+        for (me, (name, offset, width)) in
+            instr.machine.layout.iter().zip(&ranges)
+        {
+            let (MachineElement::Field { .. }
+            | MachineElement::FieldNegate { .. }
+            | MachineElement::FieldSlice { .. }) = me
+            else {
+                continue;
+            };
+            if *name != f.name || *width == 0 {
+                continue;
+            }
+            mask |= (u128::MAX >> (128 - width)) << offset;
+            offsets.push(*offset);
+        }
+        if mask == 0 {
+            continue;
+        }
+        let upper = f.name.to_uppercase();
+        let mask_const = format_ident!("{upper}_MASK");
+        let mask: proc_macro2::Literal = format!("{mask:#x}").parse().unwrap();
+        let doc = format!(
+            " Bits of the machine word holding the `{}` field.",
+            f.name
+        );
+        tks.extend(quote! {
+            #[doc = #doc]
+            pub const #mask_const: #storage = #mask;
+        });
+        // A field split into slices has no single shift.
+        if let [offset] = offsets[..] {
+            let shift_const = format_ident!("{upper}_SHIFT");
+            let offset = proc_macro2::Literal::usize_unsuffixed(offset);
+            let doc = format!(
+                " Offset of the lowest bit of the `{}` field in the machine \
+                word.",
+                f.name
+            );
+            tks.extend(quote! {
+                #[doc = #doc]
+                pub const #shift_const: usize = #offset;
+            });
+        }
+    }
+    tks
+}
+
+pub fn generate_field_info(instr: &spec::Instruction) -> TokenStream {
+    let ranges = instr
+        .layout_ranges()
+        .unwrap_or_else(|e| panic!("machine layout: {e}"));
+    let info = instr.machine.layout.iter().zip(ranges).filter_map(
+        |(me, (name, offset, width))| {
+            let (MachineElement::Field { .. }
+            | MachineElement::FieldNegate { .. }
+            | MachineElement::FieldSlice { .. }) = me
+            else {
+                return None;
+            };
+            let flag = instr.get_field(&name).map(|f| f.width) == Some(1);
+            Some(quote! {
+                isf::FieldInfo {
+                    name: #name,
+                    offset: #offset,
+                    width: #width,
+                    flag: #flag,
+                }
+            })
+        },
+    );
+    quote! { #(#info),* }
+}
+
+/// An expression constructing the given timing.
+fn generate_timing(timing: Timing) -> TokenStream {
+    match timing {
+        Timing::Cycle(n) => quote! { isf::Timing::Cycle(#n) },
+        Timing::Nanos(n) => quote! { isf::Timing::Nanos(#n) },
+        Timing::Stages(n) => quote! { isf::Timing::Stages(#n) },
+        Timing::Async => quote! { isf::Timing::Async },
+        Timing::Multi => quote! { isf::Timing::Multi },
+        Timing::MultiUpTo(n) => quote! { isf::Timing::MultiUpTo(#n) },
+        Timing::MultiRange(min, max) => {
+            quote! { isf::Timing::MultiRange(#min, #max) }
+        }
+    }
+}
+
+pub fn generate_view(instr: &spec::Instruction) -> TokenStream {
+    let name = instr.name.as_str();
+    let operands = accessor_fields(instr).into_iter().map(|f| {
+        let field = f.name.as_str();
+        let value = field_value(instr, f);
+        quote! { (#field.to_owned(), #value) }
+    });
+    quote! {
+        isf::spec::DecodedView {
+            name: #name.to_owned(),
+            operands: vec![#(#operands),*],
+            timing: Self::TIMING,
+        }
+    }
+}
+
+/// The value of a field as a `u128`. Signed fields give the two's
+/// complement bits of their value in the field width, plus the sign bit for
+/// sign-magnitude fields, rather than sign extending to 128 bits.
+fn field_value(instr: &spec::Instruction, f: &spec::Field) -> TokenStream {
+    let getter = format_ident!("get_{}", f.name);
+    let sign_magnitude = instr.is_sign_magnitude(&f.name);
+    if f.class.is_some() {
+        let raw = format_ident!("u{}", uint_size(f.width));
+        quote! { u128::from(#raw::from(self.#getter())) }
+    } else if f.is_signed || sign_magnitude {
+        let width = f.width + usize::from(sign_magnitude);
+        let mask: proc_macro2::Literal =
+            format!("{:#x}", u128::MAX >> (128 - width))
+                .parse()
+                .unwrap();
+        quote! { (self.#getter() as u128) & #mask }
+    } else {
+        quote! { self.#getter() as u128 }
+    }
+}
+
+/// The value of a field for a `Debug` impl, which shows signed fields with
+/// their sign.
+fn field_debug_value(
+    instr: &spec::Instruction,
+    f: &spec::Field,
+) -> TokenStream {
+    if f.class.is_none() && (f.is_signed || instr.is_sign_magnitude(&f.name)) {
+        let getter = format_ident!("get_{}", f.name);
+        quote! { self.#getter() }
+    } else {
+        field_value(instr, f)
+    }
+}
+
+/// The name and value of each field and named constant, in layout order,
+/// with fields valued by `value`. Constants are read from the instruction
+/// rather than the spec so that mismatched bits show.
+fn layout_values(
+    instr: &spec::Instruction,
+    value: fn(&spec::Instruction, &spec::Field) -> TokenStream,
+) -> Vec<(String, TokenStream)> {
+    let ranges = instr
+        .layout_ranges()
+        .unwrap_or_else(|e| panic!("machine layout: {e}"));
+    let mut values = Vec::new();
+    let mut seen = HashSet::new();
+    for (me, (_, offset, width)) in instr.machine.layout.iter().zip(ranges) {
+        match me {
+            MachineElement::Field { name }
+            | MachineElement::FieldNegate { name }
+            | MachineElement::FieldSlice { name, .. } => {
+                if !seen.insert(name) {
+                    continue;
+                }
+                let f = instr.get_field(name).unwrap_or_else(|| {
+                    panic!("{}: field {name} not found", instr.name)
+                });
+                values.push((name.clone(), value(instr, f)));
+            }
+            MachineElement::Constant { name, .. } if name != "_" => {
+                let mask: proc_macro2::Literal =
+                    format!("{:#x}", u128::MAX >> (128 - width))
+                        .parse()
+                        .unwrap();
+                values.push((
+                    name.clone(),
+                    quote! { u128::from((self.0 >> #offset) & #mask) },
+                ));
+            }
+            _ => {}
+        }
+    }
+    values
+}
 
-    generated
+/// A `Debug` impl listing the layout fields with their values.
+pub fn generate_field_debug(instr: &spec::Instruction) -> TokenStream {
+    let name = format_ident!("{}", instr.name);
+    let name_s = instr.name.as_str();
+    let allow = allow_deprecated(std::slice::from_ref(instr));
+    let (names, values): (Vec<_>, Vec<_>) =
+        layout_values(instr, field_debug_value).into_iter().unzip();
+    quote! {
+        #allow
+        impl core::fmt::Debug for #name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_struct(#name_s)
+                    #(.field(#names, &(#values)))*
+                    .finish()
+            }
+        }
+    }
 }
 
 pub fn generate_default_impl(instr: &spec::Instruction) -> TokenStream {
@@ -135,7 +1627,13 @@ pub fn generate_default_impl(instr: &spec::Instruction) -> TokenStream {
         })
         .count();
 
-    if num_constants == 0 && num_field_absent_tests == 0 {
+    let defaults = accessor_fields(instr)
+        .into_iter()
+        .filter_map(|f| f.value.map(|v| (f, v)))
+        .collect::<Vec<_>>();
+
+    if num_constants == 0 && num_field_absent_tests == 0 && defaults.is_empty()
+    {
         tks.extend(quote! { Self(0) });
         return tks;
     }
@@ -170,14 +1668,40 @@ pub fn generate_default_impl(instr: &spec::Instruction) -> TokenStream {
         }
     }
 
+    for (f, value) in defaults {
+        let setter = format_ident!("set_{}", f.name);
+        if f.class.is_some() {
+            let try_setter = format_ident!("try_{setter}");
+            tks.extend(quote! {
+                def.#try_setter(#value.try_into().unwrap()).unwrap();
+            });
+        } else if f.width == 1 {
+            tks.extend(quote! {
+                def.#setter(#value != 0);
+            });
+        } else {
+            tks.extend(quote! {
+                def.#setter(#value.try_into().unwrap());
+            });
+        }
+    }
+
     tks.extend(quote! { def });
     tks
 }
-pub fn generate_machine_parser(instr: &spec::Instruction) -> TokenStream {
+pub fn generate_machine_parser(
+    storage: &Ident,
+    endianness: Endianness,
+    instr: &spec::Instruction,
+) -> TokenStream {
     let mut tks = TokenStream::default();
 
+    let from_machine = match endianness {
+        Endianness::Little => quote! { from_le },
+        Endianness::Big => quote! { from_be },
+    };
     tks.extend(quote! {
-        let perhaps = Self(data);
+        let perhaps = Self(#storage::#from_machine(data));
     });
 
     for me in &instr.machine.layout {
@@ -194,8 +1718,9 @@ pub fn generate_machine_parser(instr: &spec::Instruction) -> TokenStream {
                     let expected = #value;
                     if found != expected {
                         return Err(isf::FieldMismatchError{
-                            field: #name.to_owned(),
+                            field: #name,
                             expected,
+                            allowed: &[],
                             found,
                         });
                     }
@@ -203,18 +1728,62 @@ pub fn generate_machine_parser(instr: &spec::Instruction) -> TokenStream {
             }
         }
     }
+
+    for f in &instr.fields {
+        let Some(allowed) = &f.allowed else {
+            continue;
+        };
+        let name = f.name.as_str();
+        let getter = format_ident!("get_{name}");
+        let first = proc_macro2::Literal::u64_unsuffixed(allowed[0]);
+        let allowed = allowed_values(allowed);
+        tks.extend(quote! {
+            let found = perhaps.#getter() as u64;
+            if !#allowed.contains(&found) {
+                return Err(isf::FieldMismatchError {
+                    field: #name,
+                    expected: #first,
+                    allowed: #allowed,
+                    found,
+                });
+            }
+        });
+    }
+
     tks.extend(quote! { Ok(perhaps) });
     tks
 }
 
-pub fn generate_assembly_emitter(instr: &spec::Instruction) -> TokenStream {
+pub fn generate_assembly_emitter(
+    instr: &spec::Instruction,
+    options: &CodegenOptions,
+) -> TokenStream {
     let mut tks = TokenStream::default();
 
     tks.extend(quote! {
         let mut s = String::default();
     });
+    tks.extend(generate_assembly_elements_emitter(
+        instr,
+        &instr.assembly.syntax,
+        options,
+    ));
+
+    tks.extend(quote! {
+        s.truncate(s.trim_end().len());
+        s
+    });
+    tks
+}
 
-    for ae in &instr.assembly.syntax {
+/// Emit a sequence of assembly elements, appending to `s`.
+fn generate_assembly_elements_emitter(
+    instr: &spec::Instruction,
+    syntax: &[AssemblyElement],
+    options: &CodegenOptions,
+) -> TokenStream {
+    let mut tks = TokenStream::default();
+    for ae in syntax {
         match ae {
             AssemblyElement::StringLiteral { value } => {
                 if !value.is_empty() {
@@ -232,48 +1801,145 @@ pub fn generate_assembly_emitter(instr: &spec::Instruction) -> TokenStream {
                     }
                 });
             }
-            AssemblyElement::OptionalField { name, with_dot } => {
-                let getter = format_ident!("get_{name}");
-                if *with_dot {
-                    tks.extend(quote! {
-                        if self.#getter() != 0 {
-                            s += ".";
-                            s += #name;
-                        }
-                    });
-                } else {
-                    tks.extend(quote! {
-                        if self.#getter() != 0 {
-                            s += #name;
-                        }
-                    });
-                }
+            AssemblyElement::Optional { elements } => {
+                let present = optional_present(instr, elements);
+                let inner = generate_assembly_elements_emitter(
+                    instr, elements, options,
+                );
+                tks.extend(quote! {
+                    if #present {
+                        #inner
+                    }
+                });
+            }
+            AssemblyElement::Conditional { field, elements } => {
+                let getter = format_ident!("get_{field}");
+                let inner = generate_assembly_elements_emitter(
+                    instr, elements, options,
+                );
+                tks.extend(quote! {
+                    if self.#getter() {
+                        #inner
+                    }
+                });
             }
             AssemblyElement::Dot => {
                 tks.extend(quote! { s += "."; });
             }
             AssemblyElement::Comma => {
-                tks.extend(quote! { s += ","; });
+                tks.extend(quote! { s += ", "; });
             }
             AssemblyElement::Space => {
-                tks.extend(quote! { s += " "; });
+                tks.extend(quote! {
+                    if !s.is_empty() && !s.ends_with(' ') {
+                        s += " ";
+                    }
+                });
             }
             AssemblyElement::Field { name } => {
                 let getter = format_ident!("get_{name}");
-                tks.extend(quote! {
-                    s += &format!("{}", self.#getter());
-                })
+                let field = instr
+                    .get_field(name)
+                    .unwrap_or_else(|| panic!("field {name} undefined"));
+                let width = field.width;
+                let format = operand_format(instr, name);
+                if options.class_names && field.class.is_some() {
+                    tks.extend(quote! {
+                        match self.#getter().name() {
+                            Some(name) => s += name,
+                            None => s += &format!(#format, self.#getter()),
+                        }
+                    })
+                } else if width == 1 {
+                    tks.extend(quote! {
+                        s += &format!(#format, u8::from(self.#getter()));
+                    })
+                } else if field.scale != 1 {
+                    let scale =
+                        proc_macro2::Literal::u64_unsuffixed(field.scale);
+                    tks.extend(quote! {
+                        s += &format!(#format, u128::from(self.#getter()) * #scale);
+                    })
+                } else {
+                    tks.extend(quote! {
+                        s += &format!(#format, self.#getter());
+                    })
+                }
             }
         }
     }
-
-    tks.extend(quote! { s });
     tks
 }
 
+/// Whether an optional group of assembly elements sets any field.
+fn optional_fields(elements: &[AssemblyElement]) -> bool {
+    elements.iter().any(|ae| match ae {
+        AssemblyElement::Field { .. }
+        | AssemblyElement::OptionalFlag { .. } => true,
+        AssemblyElement::Optional { elements }
+        | AssemblyElement::Conditional { elements, .. } => {
+            optional_fields(elements)
+        }
+        _ => false,
+    })
+}
+
+/// The condition under which an optional group of assembly elements is
+/// emitted: any field or flag within it is set. A group without fields
+/// carries no information and is never emitted.
+fn optional_present(
+    instr: &spec::Instruction,
+    elements: &[AssemblyElement],
+) -> TokenStream {
+    let mut conditions = Vec::new();
+    for ae in elements {
+        match ae {
+            AssemblyElement::Field { name } => {
+                let f = instr
+                    .get_field(name)
+                    .unwrap_or_else(|| panic!("field {name} undefined"));
+                let value = field_value(instr, f);
+                conditions.push(quote! { #value != 0 });
+            }
+            AssemblyElement::OptionalFlag { field, .. }
+            | AssemblyElement::Conditional { field, .. } => {
+                let getter = format_ident!("get_{field}");
+                conditions.push(quote! { self.#getter() });
+            }
+            AssemblyElement::Optional { elements } => {
+                conditions.push(optional_present(instr, elements));
+            }
+            _ => {}
+        }
+    }
+    if conditions.is_empty() {
+        quote! { false }
+    } else {
+        quote! { #((#conditions))||* }
+    }
+}
+
+/// The format string for emitting a field operand in its declared radix,
+/// zero padded to its declared number of digits. Rust counts the `0x` or
+/// `0b` prefix toward the width, so the width allows for it.
+fn operand_format(instr: &spec::Instruction, name: &str) -> String {
+    let field = instr
+        .get_field(name)
+        .unwrap_or_else(|| panic!("field {name} undefined"));
+    match (field.radix, field.pad) {
+        (Radix::Decimal, 0) => "{}".to_owned(),
+        (Radix::Hex, 0) => "{:#x}".to_owned(),
+        (Radix::Binary, 0) => "{:#b}".to_owned(),
+        (Radix::Decimal, pad) => format!("{{:0{pad}}}"),
+        (Radix::Hex, pad) => format!("{{:#0{}x}}", pad + 2),
+        (Radix::Binary, pad) => format!("{{:#0{}b}}", pad + 2),
+    }
+}
+
 pub fn generate_field_methods(
     instr: &spec::Instruction,
     storage: &Ident,
+    options: &CodegenOptions,
 ) -> TokenStream {
     let mut tks = TokenStream::default();
     let mut offset = 0usize;
@@ -282,6 +1948,7 @@ pub fn generate_field_methods(
     let mut getters = BTreeMap::<String, (Ident, TokenStream, bool)>::default();
     let mut set_indicators = BTreeMap::<String, TokenStream>::default();
     let mut mark_unset = BTreeMap::<String, TokenStream>::default();
+    let mut sign_bits = BTreeMap::<String, usize>::default();
 
     for me in &instr.machine.layout {
         let (
@@ -317,6 +1984,11 @@ pub fn generate_field_methods(
                     .width;
                 (name.as_str(), width, false, None, width, true, false, false)
             }
+            spec::MachineElement::FieldSign { name } => {
+                sign_bits.insert(name.clone(), offset);
+                offset += 1;
+                continue;
+            }
             spec::MachineElement::OptionalFieldPresentTest { name } => {
                 let width = instr
                     .get_field(name.as_str())
@@ -331,7 +2003,12 @@ pub fn generate_field_methods(
                     .width;
                 (name.as_str(), width, false, None, 1, false, false, true)
             }
-            spec::MachineElement::FieldSlice { name, begin, end } => {
+            spec::MachineElement::FieldSlice {
+                name,
+                begin,
+                end,
+                negate,
+            } => {
                 let element_width = (end - begin) + 1;
                 let width = instr
                     .get_field(name.as_str())
@@ -343,7 +2020,7 @@ pub fn generate_field_methods(
                     false,
                     Some((begin, end)),
                     element_width,
-                    false,
+                    *negate,
                     false,
                     false,
                 )
@@ -370,26 +2047,29 @@ pub fn generate_field_methods(
         let set_indicator_s = format!("{name}_is_set");
         let mark_unset_s = format!("{name}_mark_unset");
         let byte_size = uint_size(width);
-        let (byte_type, get_fn, set_fn) = if width == 1 {
-            (
-                format_ident!("bool"),
-                format_ident!("get_bit_{storage}"),
-                format_ident!("set_bit_{storage}"),
-            )
+        let byte_type = if width == 1 {
+            format_ident!("bool")
         } else if byte_size <= 128 {
-            (
-                format_ident!("u{byte_size}"),
-                format_ident!("get_u{element_width}_{storage}"),
-                format_ident!("set_u{element_width}_{storage}"),
-            )
+            format_ident!("u{byte_size}")
         } else {
             panic!("invalid field width for {name}: width");
         };
+        let read = read_bits(storage, width, element_width, offset);
 
         let negate = if negate {
             quote! { ! }
         } else {
-            quote! {}
+            quote! {}
+        };
+        // The complement of a multi-bit element read is masked to the
+        // element, so bits beyond it do not leak into the field value.
+        let read = if negate.is_empty() || width == 1 {
+            quote! { #negate #read }
+        } else {
+            let mask = proc_macro2::Literal::u128_unsuffixed(
+                u128::MAX >> (128 - element_width),
+            );
+            quote! { (#read ^ #mask) }
         };
 
         // This is last getter wins semantics, should be ok for multiple
@@ -407,8 +2087,7 @@ pub fn generate_field_methods(
                     let body = quote! { self.0 = isf::bits::#mark_unset_fn(self.0, #offset, true); };
                     mark_unset.insert(mark_unset_s, body);
                 } else {
-                    let body =
-                        quote! { #negate isf::bits::#get_fn(self.0, #offset) };
+                    let body = quote! { #read };
                     getters.insert(getter_s, (byte_type.clone(), body, false));
                 }
             }
@@ -419,14 +2098,14 @@ pub fn generate_field_methods(
                     Some(entry) => {
                         let body = quote! {
                             result |=
-                                (#negate isf::bits::#get_fn(self.0, #offset) as #typ)
+                                (#read as #typ)
                                 << #lower;
                         };
                         entry.1.extend(body);
                     }
                     None => {
                         let body = quote! {
-                            let mut result = #negate isf::bits::#get_fn(self.0, #offset) as #typ;
+                            let mut result = #read as #typ;
                         };
                         getters
                             .insert(getter_s, (byte_type.clone(), body, true));
@@ -437,29 +2116,33 @@ pub fn generate_field_methods(
 
         let body = match slice_bounds {
             None => {
-                if ptest {
-                    quote! {
-                        self.0 = isf::bits::#set_fn(self.0, #offset, 1);
-                    }
-                } else if atest {
+                if ptest | atest {
+                    let set_bit = format_ident!("set_bit_{storage}");
                     quote! {
-                        self.0 = isf::bits::#set_fn(self.0, #offset, 0);
+                        self.0 = isf::bits::#set_bit(self.0, #offset, #ptest);
                     }
                 } else {
-                    quote! {
-                        self.0 = isf::bits::#set_fn(self.0, #offset, #negate value);
-                    }
+                    let write = write_bits(
+                        storage,
+                        width,
+                        element_width,
+                        offset,
+                        quote! { #negate value },
+                    );
+                    quote! { self.0 = #write; }
                 }
             }
             Some((lower, _upper)) => {
                 let w = uint_size(element_width);
                 let typ = format_ident!("u{w}");
-                quote! {
-                    self.0 = isf::bits::#set_fn(
-                        self.0, #offset, (#negate value >> #lower)
-                        as #typ
-                    );
-                }
+                let write = write_bits(
+                    storage,
+                    width,
+                    element_width,
+                    offset,
+                    quote! { (#negate value >> #lower) as #typ },
+                );
+                quote! { self.0 = #write; }
             }
         };
         setters
@@ -470,8 +2153,192 @@ pub fn generate_field_methods(
         offset += element_width;
     }
 
+    // Fold separately placed sign bits into the magnitude accessors of
+    // sign-magnitude fields.
+    for (name, sign_offset) in &sign_bits {
+        let width = instr
+            .get_field(name.as_str())
+            .unwrap_or_else(|| panic!("undefined field: {name}"))
+            .width;
+        let signed = format_ident!("i{}", uint_size(width + 1));
+        let get_bit = format_ident!("get_bit_{storage}");
+        let set_bit = format_ident!("set_bit_{storage}");
+
+        if let Some((byte_type, tokens, slice_based)) =
+            getters.get_mut(&format!("get_{name}"))
+        {
+            let magnitude = if *slice_based {
+                quote! { { #tokens result } }
+            } else {
+                quote! { { #tokens } }
+            };
+            *tokens = quote! {
+                let magnitude = #magnitude as #signed;
+                if isf::bits::#get_bit(self.0, #sign_offset) {
+                    -magnitude
+                } else {
+                    magnitude
+                }
+            };
+            *byte_type = signed.clone();
+            *slice_based = false;
+        }
+
+        if let Some((_, byte_type, tokens)) =
+            setters.get_mut(&format!("set_{name}"))
+        {
+            let magnitude = if byte_type == "bool" {
+                quote! { value != 0 }
+            } else {
+                quote! { value.unsigned_abs() as #byte_type }
+            };
+            *tokens = quote! {
+                self.0 = isf::bits::#set_bit(self.0, #sign_offset, value < 0);
+                let value = #magnitude;
+                #tokens
+            };
+            *byte_type = signed;
+        }
+    }
+
+    // Sign-extend two's complement signed fields on read and reinterpret
+    // them as unsigned on write, leaving masking to the width-specific
+    // setters.
+    for f in instr.fields.iter().filter(|f| f.is_signed) {
+        let size = uint_size(f.width);
+        let signed = format_ident!("i{size}");
+        let unsigned = format_ident!("u{size}");
+        let shift = size - f.width;
+
+        if let Some((byte_type, tokens, slice_based)) =
+            getters.get_mut(&format!("get_{}", f.name))
+        {
+            let raw = if *slice_based {
+                quote! { { #tokens result } }
+            } else {
+                quote! { { #tokens } }
+            };
+            *tokens = quote! {
+                ((#raw as #signed) << #shift) >> #shift
+            };
+            *byte_type = signed.clone();
+            *slice_based = false;
+        }
+
+        if let Some((_, byte_type, tokens)) =
+            setters.get_mut(&format!("set_{}", f.name))
+        {
+            *tokens = quote! {
+                let value = value as #unsigned;
+                #tokens
+            };
+            *byte_type = signed;
+        }
+    }
+
+    // Present class fields through their class types.
+    for f in &instr.fields {
+        let Some(class) = &f.class else {
+            continue;
+        };
+        let class = format_ident!("{class}");
+        let raw = format_ident!("u{}", uint_size(f.width));
+
+        if let Some((byte_type, tokens, slice_based)) =
+            getters.get_mut(&format!("get_{}", f.name))
+        {
+            let value = if *slice_based {
+                quote! { { #tokens result } }
+            } else {
+                quote! { { #tokens } }
+            };
+            *tokens = quote! {
+                #class::try_from(#value).expect("class covers field values")
+            };
+            *byte_type = class.clone();
+            *slice_based = false;
+        }
+
+        if let Some((_, byte_type, tokens)) =
+            setters.get_mut(&format!("set_{}", f.name))
+        {
+            *tokens = quote! {
+                let value = #raw::from(value);
+                #tokens
+            };
+            *byte_type = class;
+        }
+    }
+
+    // Widen unsigned accessors to the storage type, keeping narrow ones
+    // under their own names.
+    if options.storage_accessors {
+        let unsigned =
+            |t: &Ident| t != "bool" && t.to_string().starts_with('u');
+        for (fn_name, (byte_type, tokens, slice_based)) in &mut getters {
+            if !unsigned(byte_type) {
+                continue;
+            }
+            let getter = format_ident!("{fn_name}");
+            let narrow = format_ident!("{fn_name}_narrow");
+            if byte_type == storage {
+                tks.extend(quote! {
+                    pub fn #narrow(&self) -> #byte_type {
+                        self.#getter()
+                    }
+                });
+                continue;
+            }
+            tks.extend(quote! {
+                pub fn #narrow(&self) -> #byte_type {
+                    self.#getter() as #byte_type
+                }
+            });
+            let value = if *slice_based {
+                quote! { { #tokens result } }
+            } else {
+                quote! { { #tokens } }
+            };
+            *tokens = quote! { #storage::from(#value) };
+            *byte_type = storage.clone();
+            *slice_based = false;
+        }
+        for (fn_name, (private, byte_type, tokens)) in &mut setters {
+            if !unsigned(byte_type) {
+                continue;
+            }
+            let setter = format_ident!("{fn_name}");
+            let narrow = format_ident!("{fn_name}_narrow");
+            let value = if byte_type == storage {
+                quote! { value }
+            } else {
+                *tokens = quote! {
+                    let value = value as #byte_type;
+                    #tokens
+                };
+                quote! { #storage::from(value) }
+            };
+            if !*private {
+                tks.extend(quote! {
+                    pub fn #narrow(&mut self, value: #byte_type) {
+                        self.#setter(#value)
+                    }
+                });
+            }
+            *byte_type = storage.clone();
+        }
+    }
+
     for (fn_name, (byte_type, tokens, slice_based)) in &getters {
         let getter = format_ident!("{fn_name}");
+        for alias in field_aliases(instr, fn_name, "get_") {
+            let alias = format_ident!("get_{alias}");
+            tks.extend(quote! {
+                pub fn #alias(&self) -> #byte_type {
+                    self.#getter()
+                }
+            });
+        }
         if *slice_based {
             tks.extend(quote! {
                 pub fn #getter(&self) -> #byte_type {
@@ -502,6 +2369,31 @@ pub fn generate_field_methods(
                     #tokens
                 }
             });
+            if byte_type != "bool" {
+                tks.extend(generate_checked_setter(instr, fn_name, byte_type));
+            }
+            for alias in field_aliases(instr, fn_name, "set_") {
+                let alias_setter = format_ident!("set_{alias}");
+                tks.extend(quote! {
+                    pub fn #alias_setter(&mut self, value: #byte_type) {
+                        self.#setter(value)
+                    }
+                });
+                if byte_type != "bool" {
+                    let try_setter = format_ident!("try_{fn_name}");
+                    let alias_try_setter = format_ident!("try_set_{alias}");
+                    let value_type =
+                        checked_setter_type(instr, fn_name, byte_type);
+                    tks.extend(quote! {
+                        pub fn #alias_try_setter(
+                            &mut self,
+                            value: #value_type,
+                        ) -> Result<(), isf::FieldOverflowError> {
+                            self.#try_setter(value)
+                        }
+                    });
+                }
+            }
         }
     }
 
@@ -523,10 +2415,251 @@ pub fn generate_field_methods(
         })
     }
 
+    tks.extend(generate_constructor(instr, &setters));
+
+    for f in accessor_fields(instr) {
+        let getter = format_ident!("get_{}", f.name);
+        let setter = format_ident!("set_{}", f.name);
+        for (bit, name) in f.bits.iter().enumerate() {
+            let get_bit = format_ident!("get_{name}");
+            let set_bit = format_ident!("set_{name}");
+            let get_doc = format!("Bit {bit} of the `{}` field.", f.name);
+            let set_doc = format!("Set bit {bit} of the `{}` field.", f.name);
+            tks.extend(quote! {
+                #[doc = #get_doc]
+                pub fn #get_bit(&self) -> bool {
+                    (self.#getter() >> #bit) & 1 != 0
+                }
+                #[doc = #set_doc]
+                pub fn #set_bit(&mut self, value: bool) {
+                    let field = self.#getter();
+                    if value {
+                        self.#setter(field | (1 << #bit));
+                    } else {
+                        self.#setter(field & !(1 << #bit));
+                    }
+                }
+            });
+        }
+    }
+
     tks
 }
 
-pub fn generate_assembly_parser(instr: &spec::Instruction) -> TokenStream {
+/// A `new` constructor taking a value for each field, in declaration order.
+/// Values are set with the checked setters, so values that do not fit their
+/// fields are errors.
+fn generate_constructor(
+    instr: &spec::Instruction,
+    setters: &BTreeMap<String, (bool, Ident, TokenStream)>,
+) -> TokenStream {
+    let mut args = Vec::new();
+    let mut sets = TokenStream::default();
+    for f in accessor_fields(instr) {
+        let Some((_, byte_type, _)) = setters.get(&format!("set_{}", f.name))
+        else {
+            continue;
+        };
+        let field = format_ident!("{}", f.name);
+        args.push(quote! { #field: #byte_type });
+        // Flags and class values always fit their fields.
+        if byte_type == "bool" || f.class.is_some() {
+            let setter = format_ident!("set_{}", f.name);
+            sets.extend(quote! { result.#setter(#field); });
+        } else {
+            let try_setter = format_ident!("try_set_{}", f.name);
+            sets.extend(quote! { result.#try_setter(#field)?; });
+        }
+    }
+    let body = if sets.is_empty() {
+        quote! { Ok(Self::default()) }
+    } else {
+        quote! {
+            let mut result = Self::default();
+            #sets
+            Ok(result)
+        }
+    };
+    quote! {
+        /// An instruction with the given field values and its constant
+        /// fields set, or an error for the first value that does not fit
+        /// its field.
+        #[allow(clippy::too_many_arguments)]
+        pub fn new(#(#args),*) -> Result<Self, isf::FieldOverflowError> {
+            #body
+        }
+    }
+}
+
+/// Aliases of the field an accessor named `<prefix><field>` belongs to.
+fn field_aliases<'a>(
+    instr: &'a spec::Instruction,
+    accessor: &str,
+    prefix: &str,
+) -> &'a [String] {
+    accessor
+        .strip_prefix(prefix)
+        .and_then(|name| instr.get_field(name))
+        .map(|f| f.aliases.as_slice())
+        .unwrap_or_default()
+}
+
+/// The value type taken by the `try_set_<field>` counterpart of a setter.
+/// Class fields are checked from their raw representation.
+fn checked_setter_type(
+    instr: &spec::Instruction,
+    setter: &str,
+    byte_type: &Ident,
+) -> Ident {
+    let name = setter.strip_prefix("set_").unwrap();
+    match instr.get_field(name) {
+        Some(spec::Field {
+            class: Some(_),
+            width,
+            ..
+        }) => format_ident!("u{}", uint_size(*width)),
+        _ => byte_type.clone(),
+    }
+}
+
+/// Generate a `try_set_<field>` setter that returns an error instead of
+/// silently truncating values too wide for the field.
+fn generate_checked_setter(
+    instr: &spec::Instruction,
+    setter: &str,
+    byte_type: &Ident,
+) -> TokenStream {
+    let name = setter.strip_prefix("set_").unwrap();
+    let field = instr
+        .get_field(name)
+        .unwrap_or_else(|| panic!("undefined field: {name}"));
+    let width = field.width;
+    let try_setter = format_ident!("try_{setter}");
+    let setter = format_ident!("{setter}");
+    if let Some(class) = &field.class {
+        let class = format_ident!("{class}");
+        let raw = format_ident!("u{}", uint_size(width));
+        return quote! {
+            pub fn #try_setter(
+                &mut self,
+                value: #raw,
+            ) -> Result<(), isf::FieldOverflowError> {
+                let value = #class::try_from(value).map_err(|e| {
+                    isf::FieldOverflowError {
+                        field: #name,
+                        width: #width,
+                        value: e.value,
+                        allowed: &[],
+                    }
+                })?;
+                self.#setter(value);
+                Ok(())
+            }
+        };
+    }
+    let (magnitude, overflow) = if field.is_signed {
+        let bound = 1i128 << (width - 1);
+        (
+            quote! { value.unsigned_abs() as u128 },
+            quote! { !(-#bound..#bound).contains(&(value as i128)) },
+        )
+    } else {
+        let shift = width as u32;
+        let magnitude = if instr.is_sign_magnitude(name) {
+            quote! { value.unsigned_abs() as u128 }
+        } else {
+            quote! { value as u128 }
+        };
+        (
+            magnitude,
+            quote! { magnitude.checked_shr(#shift).unwrap_or(0) != 0 },
+        )
+    };
+    // Allowed values all fit in the field, so membership also checks width.
+    let (overflow, allowed) = match &field.allowed {
+        Some(values) => {
+            let values = allowed_values(values);
+            (
+                quote! {
+                    u64::try_from(magnitude)
+                        .map_or(true, |v| !#values.contains(&v))
+                },
+                values,
+            )
+        }
+        None => (overflow, quote! { &[] }),
+    };
+    quote! {
+        pub fn #try_setter(
+            &mut self,
+            value: #byte_type,
+        ) -> Result<(), isf::FieldOverflowError> {
+            let magnitude = #magnitude;
+            if #overflow {
+                return Err(isf::FieldOverflowError {
+                    field: #name,
+                    width: #width,
+                    value: magnitude,
+                    allowed: #allowed,
+                });
+            }
+            self.#setter(value);
+            Ok(())
+        }
+    }
+}
+
+/// A `&[u64]` slice expression of the values a field allows.
+fn allowed_values(values: &[u64]) -> TokenStream {
+    let values = values
+        .iter()
+        .map(|v| proc_macro2::Literal::u64_unsuffixed(*v));
+    quote! { &[#(#values),*] }
+}
+
+/// Set a field from a parsed assembly operand, failing the parse if the
+/// operand does not fit in the field. The failure points back at `start`,
+/// the checkpoint taken before the operand was parsed.
+fn checked_assembly_set(field: &Ident, width: usize) -> TokenStream {
+    let try_setter = format_ident!("try_set_{field}");
+    let name = field.to_string();
+    let fits = format!("{width}-bit value");
+    let label = quote! { winnow::error::StrContext::Label(#name) };
+    let expected = expected(quote! { Description(#fits) });
+    quote! {
+        #field
+            .try_into()
+            .ok()
+            .and_then(|v| result.#try_setter(v).ok())
+            .ok_or_else(|| {
+                use winnow::error::AddContext;
+                winnow::stream::Stream::reset(input, &start);
+                winnow::error::ErrMode::Backtrack(
+                    winnow::error::ContextError::new()
+                        .add_context(input, &start, #label)
+                        .add_context(input, &start, #expected),
+                )
+            })?;
+    }
+}
+
+/// Context naming what an assembly parser expected, for error messages.
+fn expected(value: TokenStream) -> TokenStream {
+    quote! {
+        winnow::error::StrContext::Expected(
+            winnow::error::StrContextValue::#value
+        )
+    }
+}
+
+/// Generate the body of an assembly parser. A pattern parser also accepts
+/// `?` in place of field operands, leaving those fields at their defaults,
+/// and returns the names of the wildcarded fields with the instruction.
+pub fn generate_assembly_parser(
+    instr: &spec::Instruction,
+    options: &CodegenOptions,
+    pattern: bool,
+) -> TokenStream {
     let mut tks = TokenStream::default();
 
     if instr.fields.is_empty() {
@@ -539,24 +2672,113 @@ pub fn generate_assembly_parser(instr: &spec::Instruction) -> TokenStream {
         });
     }
 
-    for x in &instr.assembly.syntax {
+    let (output, ok) = if pattern {
+        tks.extend(quote! {
+            let wildcards: &mut Vec<&'static str> = &mut Vec::new();
+        });
+        (
+            quote! { (Self, Vec<&'static str>) },
+            quote! { Ok((result, std::mem::take(wildcards))) },
+        )
+    } else {
+        (quote! { Self }, quote! { Ok(result) })
+    };
+
+    let form_parser = |syntax: &[spec::AssemblyElement]| {
+        let mnemonic_end = syntax
+            .iter()
+            .position(|x| matches!(x, spec::AssemblyElement::Space))
+            .unwrap_or(syntax.len());
+        generate_assembly_elements_parser(
+            instr,
+            syntax,
+            options,
+            mnemonic_end,
+            pattern,
+        )
+    };
+
+    if instr.assembly.alternatives.is_empty() {
+        tks.extend(form_parser(&instr.assembly.syntax));
+        tks.extend(ok);
+        return tks;
+    }
+
+    // Each form is tried in turn from the start of the text. When none
+    // parses, the error of the canonical form is reported.
+    let forms: Vec<TokenStream> = instr
+        .assembly
+        .forms()
+        .map(|form| {
+            let parser = form_parser(form);
+            quote! {
+                |input: &mut &str| -> winnow::PResult<#output> {
+                    #tks
+                    #parser
+                    #ok
+                }
+            }
+        })
+        .collect();
+    let (canonical, alternatives) = forms.split_first().unwrap();
+    quote! {
+        let start = winnow::stream::Stream::checkpoint(input);
+        let error = match (#canonical)(input) {
+            Ok(result) => return Ok(result),
+            Err(e) => e,
+        };
+        let failed = winnow::stream::Stream::checkpoint(input);
+        #(
+            winnow::stream::Stream::reset(input, &start);
+            if let Ok(result) = (#alternatives)(input) {
+                return Ok(result);
+            }
+        )*
+        winnow::stream::Stream::reset(input, &failed);
+        Err(error)
+    }
+}
+
+/// Parse a sequence of assembly elements into `result`. String literals
+/// before `mnemonic_end` are part of the mnemonic. Pattern parsers record
+/// wildcarded fields in `wildcards`.
+fn generate_assembly_elements_parser(
+    instr: &spec::Instruction,
+    syntax: &[spec::AssemblyElement],
+    options: &CodegenOptions,
+    mnemonic_end: usize,
+    pattern: bool,
+) -> TokenStream {
+    let mut tks = TokenStream::default();
+    let literal = |i: usize, value: &str| {
+        if options.caseless_mnemonics && i < mnemonic_end {
+            quote! { winnow::ascii::Caseless(#value) }
+        } else {
+            quote! { #value }
+        }
+    };
+    for (i, x) in syntax.iter().enumerate() {
         match x {
             spec::AssemblyElement::StringLiteral { value } => {
                 if !value.is_empty() {
+                    let context = expected(quote! { StringLiteral(#value) });
+                    let value = literal(i, value);
                     tks.extend(quote! {
-                        let _ = #value.parse_next(input)?;
+                        let _ = #value.context(#context).parse_next(input)?;
                     });
                 }
             }
             spec::AssemblyElement::NumberLiteral { value } => {
                 let value = value.to_string();
+                let context = expected(quote! { StringLiteral(#value) });
                 tks.extend(quote! {
-                    let _ = #value.parse_next(input)?;
+                    let _ = #value.context(#context).parse_next(input)?;
                 });
             }
             spec::AssemblyElement::OptionalFlag { name, field } => {
                 let field = format_ident!("{field}");
                 let setter = format_ident!("set_{field}");
+                let name = literal(i, name);
                 tks.extend(quote! {
                     let #field : Result<
                         &str,
@@ -565,45 +2787,97 @@ pub fn generate_assembly_parser(instr: &spec::Instruction) -> TokenStream {
                     result.#setter(#field.is_ok());
                 });
             }
-            spec::AssemblyElement::OptionalField { name, with_dot } => {
-                let field = format_ident!("{name}");
-                let setter = format_ident!("set_{name}");
-                let body = quote! {
-                    let #field : Result<
-                        u64,
-                        winnow::error::ErrMode<winnow::error::ContextError>,
-                    > =  isf::parse::number_parser.parse_next(input);
-                    if let Ok(#field) = #field {
-                        result.#setter(#field.try_into().unwrap());
-                    }
+            spec::AssemblyElement::Optional { elements } => {
+                // The group parses into a copy of the result, which is kept
+                // only when the whole group matches.
+                let group_end =
+                    if i < mnemonic_end { elements.len() } else { 0 };
+                let inner = generate_assembly_elements_parser(
+                    instr, elements, options, group_end, pattern,
+                );
+                let (keep, mutability) = if optional_fields(elements) {
+                    (quote! { Ok(group) => result = group }, quote! { mut })
+                } else {
+                    (quote! { Ok(_) => {} }, quote! {})
                 };
-                if *with_dot {
+                if pattern {
+                    // Wildcards recorded by a group that fails to match are
+                    // dropped along with its fields.
                     tks.extend(quote! {
-                        let dot_ok = isf::parse::s(".").parse_next(input).is_ok();
-                        if dot_ok {
-                            #body
+                        let group_start = winnow::stream::Stream::checkpoint(input);
+                        let group_wildcards = wildcards.len();
+                        let group = |input: &mut &str,
+                                     #mutability result: Self,
+                                     wildcards: &mut Vec<&'static str>|
+                         -> winnow::PResult<Self> {
+                            #inner
+                            Ok(result)
+                        };
+                        match group(input, result, &mut *wildcards) {
+                            #keep,
+                            Err(_) => {
+                                winnow::stream::Stream::reset(input, &group_start);
+                                wildcards.truncate(group_wildcards);
+                            }
                         }
                     });
-                } else {
-                    tks.extend(quote! {
-                        #body
-                    })
+                    continue;
                 }
+                tks.extend(quote! {
+                    let group_start = winnow::stream::Stream::checkpoint(input);
+                    let group = |input: &mut &str,
+                                 #mutability result: Self|
+                     -> winnow::PResult<Self> {
+                        #inner
+                        Ok(result)
+                    };
+                    match group(input, result) {
+                        #keep,
+                        Err(_) => winnow::stream::Stream::reset(input, &group_start),
+                    }
+                });
+            }
+            spec::AssemblyElement::Conditional { field, elements } => {
+                // Unlike an optional group, the group is required when its
+                // flag is set by the elements before it.
+                let getter = format_ident!("get_{field}");
+                let group_end =
+                    if i < mnemonic_end { elements.len() } else { 0 };
+                let inner = generate_assembly_elements_parser(
+                    instr, elements, options, group_end, pattern,
+                );
+                tks.extend(quote! {
+                    if result.#getter() {
+                        #inner
+                    }
+                });
             }
             spec::AssemblyElement::Dot => {
+                let context = expected(quote! { CharLiteral('.') });
                 tks.extend(quote! {
-                    let _ = '.'.parse_next(input)?;
+                    let _ = '.'.context(#context).parse_next(input)?;
                 });
             }
             spec::AssemblyElement::Comma => {
+                let context = expected(quote! { CharLiteral(',') });
                 tks.extend(quote! {
-                    let _ = ','.parse_next(input)?;
+                    let _ = isf::parse::s(',').context(#context).parse_next(input)?;
                 });
             }
             spec::AssemblyElement::Space => {
-                tks.extend(quote! {
-                    let _ = winnow::ascii::multispace0.parse_next(input)?;
-                });
+                if space_required(syntax, i) {
+                    let context =
+                        expected(quote! { Description("whitespace") });
+                    tks.extend(quote! {
+                        let _ = winnow::ascii::multispace1
+                            .context(#context)
+                            .parse_next(input)?;
+                    });
+                } else {
+                    tks.extend(quote! {
+                        let _ = winnow::ascii::multispace0.parse_next(input)?;
+                    });
+                }
             }
             spec::AssemblyElement::Field { name } => {
                 let field = format_ident!("{name}");
@@ -611,27 +2885,208 @@ pub fn generate_assembly_parser(instr: &spec::Instruction) -> TokenStream {
                 let field_info = instr
                     .get_field(name)
                     .unwrap_or_else(|| panic!("field {name} undefined"));
-                if field_info.width == 1 {
-                    tks.extend(quote! {
-                        let #field: u64 = isf::parse::number_parser.parse_next(input)?;
+                let label = quote! { winnow::error::StrContext::Label(#name) };
+                let number = expected(quote! { Description("number") });
+                let parse = if let (true, Some(class)) =
+                    (options.class_names, &field_info.class)
+                {
+                    let class = format_ident!("{class}");
+                    let raw = format_ident!("u{}", uint_size(field_info.width));
+                    let set = checked_assembly_set(&field, field_info.width);
+                    let name_or_number =
+                        expected(quote! { Description("name or number") });
+                    quote! {
+                        let start = winnow::stream::Stream::checkpoint(input);
+                        let #field: u64 = winnow::combinator::alt((
+                            isf::parse::identifier_parser_nospace.verify_map(
+                                |name: String| {
+                                    #class::from_name(&name)
+                                        .map(|v| u64::from(#raw::from(v)))
+                                },
+                            ),
+                            isf::parse::number_parser,
+                        ))
+                        .context(#label)
+                        .context(#name_or_number)
+                        .parse_next(input)?;
+                        #set
+                    }
+                } else if instr.is_sign_magnitude(name) || field_info.is_signed
+                {
+                    let set = checked_assembly_set(&field, field_info.width);
+                    quote! {
+                        let start = winnow::stream::Stream::checkpoint(input);
+                        let #field: i64 = isf::parse::signed_number_parser
+                            .context(#label)
+                            .context(#number)
+                            .parse_next(input)?;
+                        #set
+                    }
+                } else if field_info.width == 1 {
+                    let bit = expected(quote! { Description("1-bit value") });
+                    quote! {
+                        let #field: u64 = isf::parse::number_parser
+                            .verify(|v: &u64| *v <= 1)
+                            .context(#label)
+                            .context(#bit)
+                            .parse_next(input)?;
                         result.#setter(#field != 0);
-                    });
+                    }
+                } else if field_info.scale != 1 {
+                    // Operands that are not a multiple of the scale have no
+                    // encoding.
+                    let scale =
+                        proc_macro2::Literal::u64_unsuffixed(field_info.scale);
+                    let set = checked_assembly_set(&field, field_info.width);
+                    let multiple = format!("multiple of {}", field_info.scale);
+                    let multiple = expected(quote! { Description(#multiple) });
+                    quote! {
+                        let start = winnow::stream::Stream::checkpoint(input);
+                        let #field: u64 = isf::parse::number_parser
+                            .verify_map(|v: u64| {
+                                (v % #scale == 0).then_some(v / #scale)
+                            })
+                            .context(#label)
+                            .context(#multiple)
+                            .parse_next(input)?;
+                        #set
+                    }
                 } else {
+                    let set = checked_assembly_set(&field, field_info.width);
+                    quote! {
+                        let start = winnow::stream::Stream::checkpoint(input);
+                        let #field: u64 = isf::parse::number_parser
+                            .context(#label)
+                            .context(#number)
+                            .parse_next(input)?;
+                        #set
+                    }
+                };
+                if pattern {
                     tks.extend(quote! {
-                        let #field: u64 = isf::parse::number_parser.parse_next(input)?;
-                        result.#setter(#field.try_into().unwrap());
+                        if let Some(rest) = input.strip_prefix('?') {
+                            *input = rest;
+                            wildcards.push(#name);
+                        } else {
+                            #parse
+                        }
                     });
+                } else {
+                    tks.extend(parse);
                 }
             }
         }
     }
-    tks.extend(quote! {
-        Ok(result)
-    });
-
     tks
 }
 
+/// A space in assembly syntax is required when it separates mandatory
+/// elements, such as a mnemonic and its first operand, possibly with optional
+/// elements in between. A space that follows another space across only
+/// optional elements may be empty, as the earlier space may have consumed the
+/// whitespace when the optional elements are absent. Spaces at either end of
+/// the syntax or next to punctuation may also be empty.
+fn space_required(syntax: &[spec::AssemblyElement], i: usize) -> bool {
+    use spec::AssemblyElement as AE;
+    let optional = |e: &&AE| {
+        matches!(
+            e,
+            AE::OptionalFlag { .. }
+                | AE::Optional { .. }
+                | AE::Conditional { .. }
+        )
+    };
+    let prev = syntax[..i].iter().rev().find(|e| !optional(e));
+    let next = syntax[i + 1..].iter().find(|e| !optional(e));
+    matches!(
+        prev,
+        Some(
+            AE::StringLiteral { .. }
+                | AE::NumberLiteral { .. }
+                | AE::Field { .. }
+        )
+    ) && matches!(
+        next,
+        Some(
+            AE::StringLiteral { .. }
+                | AE::NumberLiteral { .. }
+                | AE::Field { .. }
+                | AE::Space
+        )
+    )
+}
+
+/// Storage type width of an instruction, which may be narrower than that of
+/// the spec when instructions override the instruction width.
+fn instruction_storage(spec: &spec::Spec, instr: &spec::Instruction) -> usize {
+    spec.force_storage.unwrap_or_else(|| uint_size(instr.width))
+}
+
+/// Convert a machine form word of an instruction to the wider storage type of
+/// the spec.
+fn widen_machine(
+    value: TokenStream,
+    from: &Ident,
+    to: &Ident,
+    endianness: Endianness,
+) -> TokenStream {
+    if from == to {
+        return value;
+    }
+    let (from_machine, to_machine) = match endianness {
+        Endianness::Little => (quote! { from_le }, quote! { to_le }),
+        Endianness::Big => (quote! { from_be }, quote! { to_be }),
+    };
+    quote! { #to::from(#from::#from_machine(#value)).#to_machine() }
+}
+
+/// Expression reading `element_width` bits at `offset` of a field `width`
+/// bits wide. Single bit fields read as `bool`, wider ones as the smallest
+/// unsigned integer holding `element_width` bits.
+fn read_bits(
+    storage: &Ident,
+    width: usize,
+    element_width: usize,
+    offset: usize,
+) -> TokenStream {
+    if width == 1 {
+        let get_bit = format_ident!("get_bit_{storage}");
+        return quote! { isf::bits::#get_bit(self.0, #offset) };
+    }
+    let get_bits = format_ident!("get_bits_{storage}");
+    let typ = format_ident!("u{}", uint_size(element_width));
+    if typ == *storage {
+        quote! { isf::bits::#get_bits(self.0, #offset, #element_width) }
+    } else {
+        quote! {
+            (isf::bits::#get_bits(self.0, #offset, #element_width) as #typ)
+        }
+    }
+}
+
+/// Expression writing `value` into `element_width` bits at `offset` of a
+/// field `width` bits wide, the inverse of [`read_bits`].
+fn write_bits(
+    storage: &Ident,
+    width: usize,
+    element_width: usize,
+    offset: usize,
+    value: TokenStream,
+) -> TokenStream {
+    if width == 1 {
+        let set_bit = format_ident!("set_bit_{storage}");
+        return quote! { isf::bits::#set_bit(self.0, #offset, #value) };
+    }
+    let set_bits = format_ident!("set_bits_{storage}");
+    let typ = format_ident!("u{}", uint_size(element_width));
+    let value = if typ == *storage {
+        value
+    } else {
+        quote! { #storage::from(#value) }
+    };
+    quote! { isf::bits::#set_bits(self.0, #offset, #element_width, #value) }
+}
+
 fn uint_size(bits: usize) -> usize {
     match bits {
         x if x <= 8 => 8,
@@ -661,6 +3116,76 @@ mod test {
         expectorate::assert_contents("testcase/slice_add.rs", code.as_str());
     }
 
+    #[test]
+    fn cg_word_size() {
+        let text = read_to_string("testcase/word-size.isf").unwrap();
+        for (word_size, storage) in [(32, "u32"), (64, "u64")] {
+            let text = text
+                .replace("word_size = 32", &format!("word_size = {word_size}"))
+                .replace("_: 24", &format!("_: {}", word_size - 8));
+            let ast = crate::parse::parse.parse(text.as_str()).unwrap();
+            let spec = spec::form_spec(&ast).unwrap();
+            assert_eq!(spec.instruction_width, word_size);
+            let code = generate(&spec).to_string();
+            assert!(code.contains(&format!("pub struct Nop ({storage})")));
+        }
+    }
+
+    #[test]
+    fn cg_base_only() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        let text = &text[..text.find("/// Add values").unwrap()];
+        let ast = crate::parse::parse.parse(text).unwrap();
+        let spec = spec::form_spec(&ast).unwrap();
+        assert!(spec.instructions.is_empty());
+        let code = generate(&spec).to_string();
+        for item in ["enum Instruction", "fn decode", "fn assemble", "Decoder"]
+        {
+            assert!(!code.contains(item), "{item} in {code}");
+        }
+    }
+
+    #[test]
+    fn cg_to_file() {
+        let out = std::env::temp_dir().join("isf-cg-to-file.rs");
+        generate_to_file("testcase/add.isf", &out).unwrap();
+        let code = read_to_string(&out).unwrap();
+        assert!(code.contains("pub struct Add(u32);"));
+        assert!(!code.contains("#![doc"));
+
+        let err = generate_to_file("testcase/nonexistent.isf", &out)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "read testcase/nonexistent.isf");
+
+        let bad = std::env::temp_dir().join("isf-cg-to-file.isf");
+        std::fs::write(
+            &bad,
+            "instruction_width = 32;
+instruction {",
+        )
+        .unwrap();
+        let err = generate_to_file(&bad, &out).unwrap_err().to_string();
+        assert!(err.starts_with(&format!("parse {}:", bad.display())));
+    }
+
+    #[test]
+    fn cg_deprecated() {
+        let code = generate_code("testcase/deprecated.isf").unwrap();
+        assert!(code.contains(
+            "#[deprecated(note = \"use addx instead\")]\n\
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n\
+            #[repr(transparent)]\n\
+            pub struct Add(u32);"
+        ));
+        assert!(!code.contains(
+            "#[deprecated(note = \"use addx instead\")]\n\
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n\
+            #[repr(transparent)]\n\
+            pub struct AddX(u32);"
+        ));
+    }
+
     #[test]
     fn cg_add_field_opt() {
         let mut code = generate_code("testcase/add-field-opt.isf").unwrap();
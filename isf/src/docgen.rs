@@ -12,11 +12,15 @@ use winnow::Parser;
 struct Instruction {
     pub doc: String,
     pub name: String,
+    pub deprecated: Option<String>,
     pub timing: String,
     pub fields: Vec<Field>,
     pub assembly: String,
     pub examples: Vec<Example>,
-    pub machine: Vec<(usize, usize, String)>,
+    /// Offset, width, label and documentation of each layout element.
+    pub machine: Vec<(usize, usize, String, String)>,
+    /// SVG bit-field diagram of the machine layout.
+    pub svg: String,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize)]
@@ -30,9 +34,14 @@ impl From<spec::Instruction> for Instruction {
         Instruction {
             doc: markdown_to_html(&value.doc, &Options::default()),
             name: value.name.clone(),
+            deprecated: value.deprecated.clone(),
             timing: format!("{}", value.timing),
             fields: value.fields.clone().into_iter().map(Into::into).collect(),
-            assembly: assembly_string(&value.assembly),
+            assembly: assembly_string(
+                &value.assembly,
+                &|value| format!("<span class=\"constant\">'{value}'</span>"),
+                &|name| format!("<span class=\"field\">{name}</span>"),
+            ),
             examples: value
                 .assembly
                 .example
@@ -42,7 +51,16 @@ impl From<spec::Instruction> for Instruction {
                     code: x.example.clone(),
                 })
                 .collect(),
-            machine: machine_element_table(&value),
+            machine: machine_element_table(&value, |name| {
+                format!("<span class=\"field\">{name}</span>")
+            })
+            .into_iter()
+            .zip(&value.machine.docs)
+            .map(|((offset, width, label), doc)| {
+                (offset, width, label, doc.clone())
+            })
+            .collect(),
+            svg: bitfield_svg(&value),
         }
     }
 }
@@ -51,8 +69,13 @@ impl From<spec::Instruction> for Instruction {
 pub struct Field {
     pub doc: String,
     pub name: String,
+    pub aliases: Vec<String>,
+    pub bits: Vec<String>,
     pub width: usize,
     pub class: Option<String>,
+    pub is_signed: bool,
+    pub allowed: Option<Vec<u64>>,
+    pub value: Option<u64>,
 }
 
 impl From<spec::Field> for Field {
@@ -60,60 +83,62 @@ impl From<spec::Field> for Field {
         Field {
             doc: value.doc,
             name: value.name,
+            aliases: value.aliases,
+            bits: value.bits,
             width: value.width,
             class: value.class.clone(),
+            is_signed: value.is_signed,
+            allowed: value.allowed,
+            value: value.value,
         }
     }
 }
 
-fn machine_element_table(i: &spec::Instruction) -> Vec<(usize, usize, String)> {
+/// Offset, width and label of each machine layout element, with field names
+/// rendered by `field`.
+fn machine_element_table(
+    i: &spec::Instruction,
+    field: impl Fn(&str) -> String,
+) -> Vec<(usize, usize, String)> {
     let mut result = Vec::default();
     let mut idx = 0;
     for e in &i.machine.layout {
         match e {
             spec::MachineElement::Field { name } => {
                 let f = i.fields.iter().find(|x| &x.name == name).unwrap();
-                result.push((
-                    idx,
-                    f.width,
-                    format!("<span class=\"field\">{name}</span>"),
-                ));
+                result.push((idx, f.width, field(name)));
                 idx += f.width;
             }
-            spec::MachineElement::FieldSlice { name, begin, end } => {
+            spec::MachineElement::FieldSlice {
+                name,
+                begin,
+                end,
+                negate,
+            } => {
                 let w = (end - begin) + 1;
+                let negate = if *negate { "!" } else { "" };
                 result.push((
                     idx,
                     w,
-                    format!(
-                        "<span class=\"field\">{name}</span>[{begin}:{end}]"
-                    ),
+                    format!("{}[{begin}:{end}]{negate}", field(name)),
                 ));
                 idx += w;
             }
             spec::MachineElement::FieldNegate { name } => {
                 let f = i.fields.iter().find(|x| &x.name == name).unwrap();
-                result.push((
-                    idx,
-                    f.width,
-                    format!("<span class=\"field\">{name}</span>!"),
-                ));
+                result.push((idx, f.width, format!("{}!", field(name))));
                 idx += f.width;
             }
+            spec::MachineElement::FieldSign { name } => {
+                result.push((idx, 1, format!("{}.sign", field(name))));
+                idx += 1;
+            }
             spec::MachineElement::OptionalFieldPresentTest { name } => {
-                result.push((
-                    idx,
-                    1,
-                    format!("<span class=\"field\">{name}</span>?"),
-                ));
+                result.push((idx, 1, format!("{}?", field(name))));
                 idx += 1;
             }
             spec::MachineElement::OptionalFieldAbsentTest { name } => {
-                result.push((
-                    idx,
-                    1,
-                    format!("<span class=\"field\">{name}</span>?!"),
-                ));
+                result.push((idx, 1, format!("{}?!", field(name))));
                 idx += 1;
             }
             spec::MachineElement::Constant { name, width, value } => {
@@ -131,27 +156,48 @@ fn machine_element_table(i: &spec::Instruction) -> Vec<(usize, usize, String)> {
     result
 }
 
-fn assembly_string(a: &Assembly) -> String {
+/// Assembly syntax of an instruction, with string literals rendered by
+/// `literal` and field names by `field`.
+fn assembly_string(
+    a: &Assembly,
+    literal: &dyn Fn(&str) -> String,
+    field: &dyn Fn(&str) -> String,
+) -> String {
+    a.forms()
+        .map(|form| assembly_elements_string(form, literal, field))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn assembly_elements_string(
+    syntax: &[spec::AssemblyElement],
+    literal: &dyn Fn(&str) -> String,
+    field: &dyn Fn(&str) -> String,
+) -> String {
     let mut s = String::default();
-    for x in &a.syntax {
+    for x in syntax {
         match x {
             spec::AssemblyElement::StringLiteral { value } => {
-                s += &format!("<span class=\"constant\">'{value}'</span>");
+                s += &literal(value);
             }
             spec::AssemblyElement::NumberLiteral { value } => {
                 s += &value.to_string();
             }
-            spec::AssemblyElement::OptionalFlag { name, field } => {
+            spec::AssemblyElement::OptionalFlag { name, field: f } => {
+                s += &format!("[{} = {}]", literal(name), field(f));
+            }
+            spec::AssemblyElement::Optional { elements } => {
                 s += &format!(
-                    "[<span class=\"constant\">'{name}'</span> = <span class=\"field\">{field}</span>]",
+                    "[{}]",
+                    assembly_elements_string(elements, literal, field)
                 );
             }
-            spec::AssemblyElement::OptionalField { name, with_dot } => {
-                if *with_dot {
-                    s += &format!("[.<span class=\"field\">{name}</span>]");
-                } else {
-                    s += &format!("[<span class=\"field\">{name}</span>]");
-                }
+            spec::AssemblyElement::Conditional { field: f, elements } => {
+                s += &format!(
+                    "[{} => {}]",
+                    field(f),
+                    assembly_elements_string(elements, literal, field)
+                );
             }
             spec::AssemblyElement::Dot => {
                 s += ".";
@@ -163,7 +209,7 @@ fn assembly_string(a: &Assembly) -> String {
                 s += " ";
             }
             spec::AssemblyElement::Field { name } => {
-                s += &format!("<span class=\"field\">{name}</span>");
+                s += &field(name);
             }
         }
     }
@@ -175,8 +221,21 @@ fn assembly_string(a: &Assembly) -> String {
 
 /// Generate HTML documentation for an ISF file at the given path.
 pub fn generate_docs(path: &str) -> anyhow::Result<String> {
-    let src = include_str!("../../template/template.liquid");
+    generate_docs_with_template(
+        path,
+        include_str!("../../template/template.liquid"),
+    )
+}
 
+/// Generate documentation for an ISF file at the given path with a caller
+/// supplied liquid template in place of the bundled one. The template is
+/// rendered with an `instrs` array holding each instruction's `name`,
+/// `doc`, `deprecated` note, `timing`, `fields`, `assembly`, `examples`
+/// and `machine` layout rows.
+pub fn generate_docs_with_template(
+    path: &str,
+    src: &str,
+) -> anyhow::Result<String> {
     let text = read_to_string(path)?;
     let s: &str = text.as_str();
     let ast = crate::parse::parse
@@ -188,16 +247,352 @@ pub fn generate_docs(path: &str) -> anyhow::Result<String> {
         spec.instructions.iter().cloned().map(Into::into).collect();
 
     let template = liquid::ParserBuilder::with_stdlib()
-        .build()
-        .unwrap()
+        .build()?
         .parse(src)
-        .unwrap();
+        .map_err(|e| anyhow::anyhow!("docs template: {e}"))?;
 
     let globals = liquid::object!({
         "instrs": instructions,
     });
 
-    let output = template.render(&globals).unwrap();
+    let output = template
+        .render(&globals)
+        .map_err(|e| anyhow::anyhow!("docs template: {e}"))?;
 
     Ok(output)
 }
+
+/// Generate Markdown documentation for an ISF file at the given path, for
+/// embedding in repository docs. Each instruction gets a heading, its
+/// assembly syntax and examples in code fences, and tables of its fields
+/// and machine layout.
+pub fn generate_docs_markdown(path: &str) -> anyhow::Result<String> {
+    let text = read_to_string(path)?;
+    let s: &str = text.as_str();
+    let ast = crate::parse::parse
+        .parse(s)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let spec = spec::form_spec(&ast)?;
+
+    let mut md = String::default();
+    if !spec.doc.is_empty() {
+        md += &format!("{}\n\n", spec.doc);
+    }
+    md += &spec
+        .instructions
+        .iter()
+        .map(instruction_markdown)
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(md)
+}
+
+fn instruction_markdown(i: &spec::Instruction) -> String {
+    let mut md = format!("## {}\n\n", i.name);
+    if let Some(note) = &i.deprecated {
+        md += &format!("**Deprecated:** {note}\n\n");
+    }
+    md += &format!("{}\n\n**Timing:** {}\n\n", i.doc, i.timing);
+
+    let assembly =
+        assembly_string(&i.assembly, &|value| format!("'{value}'"), &|name| {
+            name.to_owned()
+        });
+    md += &format!("### Assembly\n\n```\n{assembly}\n```\n\n");
+
+    if !i.assembly.example.is_empty() {
+        md += "### Examples\n\n";
+        for e in &i.assembly.example {
+            md += &format!("{}\n\n```\n{}\n```\n\n", e.doc, e.example);
+        }
+    }
+
+    if !i.fields.is_empty() {
+        md += "### Fields\n\n";
+        md += "| Name | Width | Reset | Description |\n";
+        md += "| --- | --- | --- | --- |\n";
+        for f in &i.fields {
+            let names = std::iter::once(&f.name)
+                .chain(&f.aliases)
+                .map(|n| format!("`{n}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let mut width = f.width.to_string();
+            if f.is_signed {
+                width += " (signed)";
+            }
+            if let Some(allowed) = &f.allowed {
+                let allowed: Vec<String> =
+                    allowed.iter().map(u64::to_string).collect();
+                width += &format!(" (one of {})", allowed.join(", "));
+            }
+            let reset = f.value.map(|v| v.to_string()).unwrap_or_default();
+            md += &format!(
+                "| {names} | {width} | {reset} | {} |\n",
+                table_cell(&f.doc)
+            );
+        }
+        md += "\n";
+    }
+
+    md += "### Layout\n\n";
+    md += "| Index | Width | Value | Description |\n";
+    md += "| --- | --- | --- | --- |\n";
+    for ((offset, width, label), doc) in machine_element_table(i, str::to_owned)
+        .into_iter()
+        .zip(&i.machine.docs)
+    {
+        md += &format!(
+            "| {offset} | {width} | `{label}` | {} |\n",
+            table_cell(doc)
+        );
+    }
+    md
+}
+
+/// Fit text into a Markdown table cell, which cannot span lines or hold an
+/// unescaped `|`.
+fn table_cell(text: &str) -> String {
+    text.lines()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+/// Render the machine layout of an instruction as a text bit diagram, most
+/// significant bit first, with a header row of bit indices.
+fn layout_diagram(i: &spec::Instruction) -> String {
+    let mut header = String::from(" ");
+    let mut border = String::from("+");
+    let mut labels = String::from("|");
+    for (offset, width, label) in
+        machine_element_table(i, str::to_owned).into_iter().rev()
+    {
+        let hi = (offset + width - 1).to_string();
+        if width == 1 {
+            let w = (label.len() + 2).max(hi.len() + 2);
+            header += &format!("{hi:^w$} ");
+            border += &format!("{}+", "-".repeat(w));
+            labels += &format!("{label:^w$}|");
+        } else {
+            let lo = offset.to_string();
+            let w = (label.len() + 2).max(hi.len() + lo.len() + 3);
+            header += &format!("{hi}{lo:>0$} ", w - hi.len());
+            border += &format!("{}+", "-".repeat(w));
+            labels += &format!("{label:^w$}|");
+        }
+    }
+
+    format!(
+        "{}\n{}\n{border}\n{labels}\n{border}\n",
+        i.name,
+        header.trim_end()
+    )
+}
+
+/// Number of bits drawn per row by [`bitfield_svg`].
+pub const SVG_ROW_BITS: usize = 32;
+
+const SVG_BIT_WIDTH: usize = 28;
+const SVG_BOX_HEIGHT: usize = 32;
+const SVG_TICK_HEIGHT: usize = 16;
+const SVG_ROW_GAP: usize = 8;
+const SVG_MARGIN: usize = 4;
+
+/// Render the machine layout of an instruction as an SVG bit-field diagram,
+/// most significant bit first, wrapping every [`SVG_ROW_BITS`] bits.
+pub fn bitfield_svg(i: &spec::Instruction) -> String {
+    bitfield_svg_wrapped(i, SVG_ROW_BITS)
+}
+
+/// Render the machine layout of an instruction as an SVG bit-field diagram,
+/// with `row_bits` bits per row. The row holding the most significant bits
+/// is on top, and elements crossing a row boundary are split across rows.
+pub fn bitfield_svg_wrapped(i: &spec::Instruction, row_bits: usize) -> String {
+    let elements = machine_element_table(i, str::to_owned);
+    let total: usize = elements.iter().map(|(_, width, _)| width).sum();
+    let row_bits = row_bits.max(1).min(total.max(1));
+    let rows = total.div_ceil(row_bits);
+
+    let row_height = SVG_TICK_HEIGHT + SVG_BOX_HEIGHT;
+    let width = 2 * SVG_MARGIN + row_bits * SVG_BIT_WIDTH;
+    let height = 2 * SVG_MARGIN
+        + rows * row_height
+        + rows.saturating_sub(1) * SVG_ROW_GAP;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"bitfield\" \
+        width=\"{width}\" height=\"{height}\" \
+        viewBox=\"0 0 {width} {height}\" font-family=\"monospace\" \
+        font-size=\"12\">\n"
+    );
+
+    for row in 0..rows {
+        let lo = (rows - 1 - row) * row_bits;
+        let hi = (lo + row_bits).min(total) - 1;
+        let y = SVG_MARGIN + row * (row_height + SVG_ROW_GAP);
+        let top = y + SVG_TICK_HEIGHT;
+        // Rows are right aligned, so bit `lo` of every row is in the same
+        // column.
+        let x = |bit: usize| {
+            SVG_MARGIN + (row_bits - 1 - (bit - lo)) * SVG_BIT_WIDTH
+        };
+
+        for (offset, width, label) in &elements {
+            let begin = (*offset).max(lo);
+            let end = (offset + width - 1).min(hi);
+            if begin > end {
+                continue;
+            }
+            let left = x(end);
+            let span = (end - begin + 1) * SVG_BIT_WIDTH;
+            let label = xml_escape(label);
+            svg += &format!(
+                "<g><title>{label}</title>\
+                <rect x=\"{left}\" y=\"{top}\" width=\"{span}\" \
+                height=\"{SVG_BOX_HEIGHT}\" fill=\"none\" \
+                stroke=\"black\"/>\
+                <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" \
+                dominant-baseline=\"middle\">{label}</text></g>\n",
+                left + span / 2,
+                top + SVG_BOX_HEIGHT / 2,
+            );
+            for bit in (begin + 1)..=end {
+                let tick = x(bit) + SVG_BIT_WIDTH;
+                svg += &format!(
+                    "<line x1=\"{tick}\" y1=\"{top}\" x2=\"{tick}\" \
+                    y2=\"{}\" stroke=\"black\"/>\n",
+                    top + SVG_BOX_HEIGHT / 8,
+                );
+            }
+            let mut indices = vec![end];
+            if begin != end {
+                indices.push(begin);
+            }
+            for bit in indices {
+                svg += &format!(
+                    "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">\
+                    {bit}</text>\n",
+                    x(bit) + SVG_BIT_WIDTH / 2,
+                    top - 4,
+                );
+            }
+        }
+    }
+
+    svg += "</svg>";
+    svg
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Generate text bit diagrams of the machine layout of each instruction in
+/// an ISF file at the given path.
+pub fn generate_layout(path: &str) -> anyhow::Result<String> {
+    let text = read_to_string(path)?;
+    let s: &str = text.as_str();
+    let ast = crate::parse::parse
+        .parse(s)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let spec = spec::form_spec(&ast)?;
+
+    Ok(spec
+        .instructions
+        .iter()
+        .map(layout_diagram)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn docgen_layout() {
+        let text = generate_layout("testcase/add-field-opt.isf").unwrap();
+        expectorate::assert_contents("testcase/add_field_opt.layout", &text);
+    }
+
+    #[test]
+    fn docgen_svg() {
+        let text = read_to_string("testcase/add-field-opt.isf").unwrap();
+        let ast = crate::parse::parse.parse(text.as_str()).unwrap();
+        let spec = spec::form_spec(&ast).unwrap();
+        let i = &spec.instructions[0];
+
+        let svg = bitfield_svg(i);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>"));
+        assert_eq!(svg.matches("<rect ").count(), 10);
+        assert!(svg.contains(">opcode = 2</text>"));
+        assert!(svg.contains(">31</text>"));
+        assert!(svg.contains(">0</text>"));
+
+        // Wrapping at 16 bits splits the src1 field at bits 16..=20 from
+        // the ~ constant at bits 13..=15, and no element crosses the
+        // boundary, so the rect count is unchanged but the height doubles.
+        let wrapped = bitfield_svg_wrapped(i, 16);
+        assert_eq!(wrapped.matches("<rect ").count(), 10);
+        assert!(wrapped.contains("height=\"112\""));
+
+        // Wrapping at 10 bits splits dst (8..=12) and src1 (16..=20).
+        let wrapped = bitfield_svg_wrapped(i, 10);
+        assert_eq!(wrapped.matches(">dst</text>").count(), 2);
+        assert_eq!(wrapped.matches(">src1</text>").count(), 2);
+    }
+
+    #[test]
+    fn docgen_markdown() {
+        let md = generate_docs_markdown("testcase/add-field-opt.isf").unwrap();
+        expectorate::assert_contents("testcase/add_field_opt.md", &md);
+    }
+
+    #[test]
+    fn docgen_template() {
+        let text = generate_docs_with_template(
+            "testcase/binop.isf",
+            "{% for i in instrs %}{{i.name}}: {{i.timing}}\n{% endfor %}",
+        )
+        .unwrap();
+        assert_eq!(text, "Add: async\nSub: 47 cycles\n");
+        assert!(generate_docs_with_template(
+            "testcase/binop.isf",
+            "{% for i in instrs %}"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn docgen_deprecated() {
+        let html = generate_docs("testcase/deprecated.isf").unwrap();
+        assert!(html.contains(
+            "<p class=\"deprecated\"><b>Deprecated:</b> use addx instead</p>"
+        ));
+        assert_eq!(html.matches("class=\"deprecated\"").count(), 1);
+    }
+
+    #[test]
+    fn docgen_field_reset() {
+        let html = generate_docs("testcase/reset.isf").unwrap();
+        assert!(html.contains(
+            "<td><span class=\"constant\">1</span></td><td>Operating mode</td>"
+        ));
+        assert!(html
+            .contains("<td></td><td>Register holding the mode argument</td>"));
+    }
+
+    #[test]
+    fn docgen_machine_doc() {
+        let html = generate_docs("testcase/slice-add.isf").unwrap();
+        assert!(html.contains(
+            "<tr title=\"Low bits of the sources, kept apart from the high \
+            bits to match\nthe register file ports.\"><td>16</td>"
+        ));
+    }
+}
@@ -0,0 +1,480 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Export of a resolved spec for tools outside of Rust. Bit offsets of
+//! machine layout elements and of field bits are computed here so consumers
+//! need not reimplement layout resolution.
+
+use crate::ast::{self, Endianness, Radix};
+use crate::spec;
+use serde::Serialize;
+use std::fs::read_to_string;
+use winnow::Parser;
+
+#[derive(Debug, Serialize)]
+struct Spec {
+    doc: String,
+    instruction_width: usize,
+    force_storage: Option<usize>,
+    endianness: &'static str,
+    name: Option<String>,
+    version: Option<String>,
+    classes: Vec<Class>,
+    instructions: Vec<Instruction>,
+}
+
+#[derive(Debug, Serialize)]
+struct Class {
+    name: String,
+    doc: String,
+    width: usize,
+    values: Vec<ClassValue>,
+}
+
+#[derive(Debug, Serialize)]
+struct ClassValue {
+    name: String,
+    doc: String,
+    value: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct Instruction {
+    name: String,
+    doc: String,
+    deprecated: Option<String>,
+    width: usize,
+    timing: Timing,
+    fields: Vec<Field>,
+    assembly: Vec<AssemblyElement>,
+    /// Further forms the assembly may be written in.
+    alternatives: Vec<Vec<AssemblyElement>>,
+    examples: Vec<Example>,
+    machine: Vec<MachineElement>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Timing {
+    Cycle {
+        cycles: usize,
+    },
+    Nanos {
+        ns: usize,
+    },
+    Stages {
+        stages: usize,
+    },
+    Async,
+    /// Multiple cycles, with optional bounds on the count.
+    Multi {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct Field {
+    name: String,
+    aliases: Vec<String>,
+    /// Names of the bits of the field, least significant first.
+    bits: Vec<String>,
+    doc: String,
+    width: usize,
+    class: Option<String>,
+    signed: bool,
+    sign_magnitude: bool,
+    radix: &'static str,
+    /// Minimum number of digits in assembly, zero for no padding.
+    pad: usize,
+    /// Factor the field is multiplied by in assembly, one for no scaling.
+    scale: u64,
+    allowed: Option<Vec<u64>>,
+    value: Option<u64>,
+    /// Where the bits of the field are placed in the machine word.
+    placement: Vec<Placement>,
+}
+
+/// A run of `width` bits of a field starting at field bit `begin`, placed
+/// at machine bit `offset`.
+#[derive(Debug, Serialize)]
+struct Placement {
+    offset: usize,
+    begin: usize,
+    width: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum AssemblyElement {
+    String {
+        value: String,
+    },
+    Number {
+        value: u64,
+    },
+    OptionalFlag {
+        name: String,
+        field: String,
+    },
+    Optional {
+        elements: Vec<AssemblyElement>,
+    },
+    Conditional {
+        field: String,
+        elements: Vec<AssemblyElement>,
+    },
+    Dot,
+    Comma,
+    Space,
+    Field {
+        field: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct Example {
+    doc: String,
+    code: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum MachineElement {
+    Field {
+        name: String,
+        offset: usize,
+        width: usize,
+        negate: bool,
+    },
+    FieldSlice {
+        name: String,
+        offset: usize,
+        width: usize,
+        begin: usize,
+        end: usize,
+        negate: bool,
+    },
+    FieldSign {
+        name: String,
+        offset: usize,
+        width: usize,
+    },
+    OptionalFieldTest {
+        name: String,
+        offset: usize,
+        width: usize,
+        present: bool,
+    },
+    Constant {
+        name: String,
+        offset: usize,
+        width: usize,
+        value: Option<u64>,
+    },
+}
+
+impl From<ast::Timing> for Timing {
+    fn from(value: ast::Timing) -> Self {
+        match value {
+            ast::Timing::Cycle(cycles) => Timing::Cycle { cycles },
+            ast::Timing::Nanos(ns) => Timing::Nanos { ns },
+            ast::Timing::Stages(stages) => Timing::Stages { stages },
+            ast::Timing::Async => Timing::Async,
+            ast::Timing::Multi => Timing::Multi {
+                min: None,
+                max: None,
+            },
+            ast::Timing::MultiUpTo(max) => Timing::Multi {
+                min: None,
+                max: Some(max),
+            },
+            ast::Timing::MultiRange(min, max) => Timing::Multi {
+                min: Some(min),
+                max: Some(max),
+            },
+        }
+    }
+}
+
+impl From<&spec::AssemblyElement> for AssemblyElement {
+    fn from(value: &spec::AssemblyElement) -> Self {
+        match value.clone() {
+            spec::AssemblyElement::StringLiteral { value } => {
+                AssemblyElement::String { value }
+            }
+            spec::AssemblyElement::NumberLiteral { value } => {
+                AssemblyElement::Number { value }
+            }
+            spec::AssemblyElement::OptionalFlag { name, field } => {
+                AssemblyElement::OptionalFlag { name, field }
+            }
+            spec::AssemblyElement::Optional { elements } => {
+                AssemblyElement::Optional {
+                    elements: elements.iter().map(Into::into).collect(),
+                }
+            }
+            spec::AssemblyElement::Conditional { field, elements } => {
+                AssemblyElement::Conditional {
+                    field,
+                    elements: elements.iter().map(Into::into).collect(),
+                }
+            }
+            spec::AssemblyElement::Dot => AssemblyElement::Dot,
+            spec::AssemblyElement::Comma => AssemblyElement::Comma,
+            spec::AssemblyElement::Space => AssemblyElement::Space,
+            spec::AssemblyElement::Field { name } => {
+                AssemblyElement::Field { field: name }
+            }
+        }
+    }
+}
+
+impl Instruction {
+    fn new(value: &spec::Instruction) -> anyhow::Result<Self> {
+        let ranges = value.layout_ranges()?;
+        let machine: Vec<MachineElement> = value
+            .machine
+            .layout
+            .iter()
+            .zip(&ranges)
+            .map(|(me, (_, offset, width))| {
+                let (offset, width) = (*offset, *width);
+                match me.clone() {
+                    spec::MachineElement::Field { name } => {
+                        MachineElement::Field {
+                            name,
+                            offset,
+                            width,
+                            negate: false,
+                        }
+                    }
+                    spec::MachineElement::FieldNegate { name } => {
+                        MachineElement::Field {
+                            name,
+                            offset,
+                            width,
+                            negate: true,
+                        }
+                    }
+                    spec::MachineElement::FieldSlice {
+                        name,
+                        begin,
+                        end,
+                        negate,
+                    } => MachineElement::FieldSlice {
+                        name,
+                        offset,
+                        width,
+                        begin,
+                        end,
+                        negate,
+                    },
+                    spec::MachineElement::FieldSign { name } => {
+                        MachineElement::FieldSign {
+                            name,
+                            offset,
+                            width,
+                        }
+                    }
+                    spec::MachineElement::OptionalFieldPresentTest { name } => {
+                        MachineElement::OptionalFieldTest {
+                            name,
+                            offset,
+                            width,
+                            present: true,
+                        }
+                    }
+                    spec::MachineElement::OptionalFieldAbsentTest { name } => {
+                        MachineElement::OptionalFieldTest {
+                            name,
+                            offset,
+                            width,
+                            present: false,
+                        }
+                    }
+                    spec::MachineElement::Constant { name, width, value } => {
+                        MachineElement::Constant {
+                            name,
+                            offset,
+                            width,
+                            value,
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        let fields = value
+            .fields
+            .iter()
+            .map(|f| Field {
+                name: f.name.clone(),
+                aliases: f.aliases.clone(),
+                bits: f.bits.clone(),
+                doc: f.doc.clone(),
+                width: f.width,
+                class: f.class.clone(),
+                signed: f.is_signed,
+                sign_magnitude: value.is_sign_magnitude(&f.name),
+                radix: match f.radix {
+                    Radix::Decimal => "decimal",
+                    Radix::Hex => "hex",
+                    Radix::Binary => "binary",
+                },
+                pad: f.pad,
+                scale: f.scale,
+                allowed: f.allowed.clone(),
+                value: f.value,
+                placement: placement(&machine, &f.name),
+            })
+            .collect();
+
+        Ok(Instruction {
+            name: value.name.clone(),
+            doc: value.doc.clone(),
+            deprecated: value.deprecated.clone(),
+            width: value.width,
+            timing: value.timing.into(),
+            fields,
+            assembly: value.assembly.syntax.iter().map(Into::into).collect(),
+            alternatives: value
+                .assembly
+                .alternatives
+                .iter()
+                .map(|form| form.iter().map(Into::into).collect())
+                .collect(),
+            examples: value
+                .assembly
+                .example
+                .iter()
+                .map(|x| Example {
+                    doc: x.doc.clone(),
+                    code: x.example.clone(),
+                })
+                .collect(),
+            machine,
+        })
+    }
+}
+
+/// Placement of the bits of a field, from whole and sliced occurrences in
+/// the machine layout.
+fn placement(machine: &[MachineElement], field: &str) -> Vec<Placement> {
+    machine
+        .iter()
+        .filter_map(|me| match me {
+            MachineElement::Field {
+                name,
+                offset,
+                width,
+                ..
+            } if name == field => Some(Placement {
+                offset: *offset,
+                begin: 0,
+                width: *width,
+            }),
+            MachineElement::FieldSlice {
+                name,
+                offset,
+                width,
+                begin,
+                ..
+            } if name == field => Some(Placement {
+                offset: *offset,
+                begin: *begin,
+                width: *width,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Export a resolved spec as pretty printed JSON.
+pub fn export_spec_json(spec: &spec::Spec) -> anyhow::Result<String> {
+    let mut classes: Vec<Class> = spec
+        .classes
+        .values()
+        .map(|c| Class {
+            name: c.name.clone(),
+            doc: c.doc.clone(),
+            width: c.width,
+            values: c
+                .values
+                .iter()
+                .map(|v| ClassValue {
+                    name: v.name.clone(),
+                    doc: v.doc.clone(),
+                    value: v.value,
+                })
+                .collect(),
+        })
+        .collect();
+    classes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let export = Spec {
+        doc: spec.doc.clone(),
+        instruction_width: spec.instruction_width,
+        force_storage: spec.force_storage,
+        endianness: match spec.endianness {
+            Endianness::Little => "little",
+            Endianness::Big => "big",
+        },
+        name: spec.name.clone(),
+        version: spec.version.clone(),
+        classes,
+        instructions: spec
+            .instructions
+            .iter()
+            .map(Instruction::new)
+            .collect::<anyhow::Result<_>>()?,
+    };
+    let mut json = serde_json::to_string_pretty(&export)?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// Export the ISF file at the given path as JSON.
+pub fn export_json(path: &str) -> anyhow::Result<String> {
+    let text = read_to_string(path)?;
+    let s: &str = text.as_str();
+    let ast = crate::parse::parse
+        .parse(s)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let spec = spec::form_spec(&ast)?;
+    export_spec_json(&spec)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn export_slice_add() {
+        let json = export_json("testcase/slice-add.isf").unwrap();
+        let v: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let instr = &v["instructions"][0];
+        assert_eq!(instr["name"], "SliceAdd");
+        assert_eq!(instr["timing"]["kind"], "cycle");
+        assert_eq!(instr["timing"]["cycles"], 1);
+
+        let src = &instr["fields"][1];
+        assert_eq!(src["name"], "src");
+        assert_eq!(
+            src["placement"],
+            serde_json::json!([
+                { "offset": 16, "begin": 0, "width": 7 },
+                { "offset": 25, "begin": 7, "width": 7 },
+            ])
+        );
+
+        let opcode = &instr["machine"][0];
+        assert_eq!(opcode["kind"], "constant");
+        assert_eq!(opcode["offset"], 0);
+        assert_eq!(opcode["width"], 7);
+        assert_eq!(opcode["value"], 2);
+        assert_eq!(instr["assembly"][0]["kind"], "string");
+        assert_eq!(instr["assembly"][0]["value"], "add");
+    }
+}
@@ -0,0 +1,632 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! This module contains a formatter for ISF. The [`format()`] function emits
+//! canonically formatted ISF text from an ISF [`ast::Ast`]. Comments
+//! captured by the parser are emitted again around the declaration they
+//! belong to.
+
+use crate::ast::{
+    self, AssemblyElement, BaseParameter, Characteristic, CharacteristicValue,
+    Comments, Endianness, MachineElement, MachineElementValue, Timing,
+};
+use std::fmt::Write;
+use std::fs::read_to_string;
+use winnow::Parser;
+
+const INDENT: &str = "  ";
+
+/// Format the ISF file at the given path. Files with comments the AST does
+/// not keep, such as those between the items of a class body, are refused
+/// rather than formatted without them.
+pub fn format_spec(path: &str) -> anyhow::Result<String> {
+    let text = read_to_string(path)?;
+    let s: &str = text.as_str();
+    let ast = crate::parse::parse
+        .parse(s)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let formatted = format(&ast);
+
+    let kept = source_comments(&formatted);
+    for (n, (line, comment)) in source_comments(&text).into_iter().enumerate() {
+        if kept.get(n).map(|(_, c)| *c) != Some(comment) {
+            return Err(anyhow::anyhow!(
+                "{path}:{line}: comment cannot be kept by fmt: {comment}"
+            ));
+        }
+    }
+    Ok(formatted)
+}
+
+/// Emit formatted ISF text for an ISF AST.
+pub fn format(ast: &ast::Ast) -> String {
+    let mut s = String::default();
+
+    for line in ast.doc.lines() {
+        if line.is_empty() {
+            s += "//!\n";
+        } else {
+            writeln!(s, "//! {line}").unwrap();
+        }
+    }
+    if !ast.doc.is_empty() {
+        s += "\n";
+    }
+
+    // Characteristics and constants keep their declaration order, since a
+    // constant can only be referred to after it is declared.
+    let mut header: Vec<(usize, String, &Comments)> = ast
+        .constants
+        .iter()
+        .map(|c| {
+            (
+                c.position,
+                format!("{} = {};", c.name, c.value),
+                &c.comments,
+            )
+        })
+        .collect();
+    let none = Comments::default();
+    let comments = ast
+        .characteristic_comments
+        .iter()
+        .chain(std::iter::repeat(&none));
+    let positions = ast
+        .characteristic_positions
+        .iter()
+        .copied()
+        .chain(std::iter::repeat(usize::MAX));
+    for ((c, comments), position) in
+        ast.characteristics.iter().zip(comments).zip(positions)
+    {
+        let line = match c {
+            Characteristic::InstructionWidth(w) => {
+                let w = match w {
+                    CharacteristicValue::NumericConstant(w) => w.to_string(),
+                    CharacteristicValue::NamedConstant(name) => name.clone(),
+                };
+                format!("instruction_width = {w};")
+            }
+            Characteristic::ForceStorage(w) => format!("force_storage = u{w};"),
+            Characteristic::Endianness(e) => {
+                let e = match e {
+                    Endianness::Little => "little",
+                    Endianness::Big => "big",
+                };
+                format!("endianness = {e};")
+            }
+            Characteristic::Name(n) => format!("name = '{}';", escape(n)),
+            Characteristic::Version(v) => {
+                format!("version = '{}';", escape(v))
+            }
+        };
+        header.push((position, line, comments));
+    }
+    header.sort_by_key(|(position, _, _)| *position);
+    for (_, line, comments) in header {
+        format_line(&mut s, "", comments, "", &line);
+    }
+
+    for c in &ast.classes {
+        s += "\n";
+        format_comments(&mut s, "", &c.comments.leading);
+        format_doc(&mut s, "", &c.doc);
+        format_comments(&mut s, "", &c.comments.inner);
+        writeln!(s, "class {} {{", c.name).unwrap();
+        writeln!(s, "{INDENT}width: {}", c.width).unwrap();
+        if !c.values.is_empty() {
+            writeln!(s, "{INDENT}values:").unwrap();
+            let indent = INDENT.repeat(2);
+            for v in &c.values {
+                let line = format!("{} = {},", v.name, v.value);
+                format_line(&mut s, &indent, &v.comments, &v.doc, &line);
+            }
+        }
+        s += "}";
+        format_trailing(&mut s, &c.comments);
+        format_comments(&mut s, "", &c.comments.after);
+    }
+
+    for i in &ast.instructions {
+        s += "\n";
+        format_instruction(&mut s, i);
+    }
+
+    if !ast.comments.is_empty() {
+        s += "\n";
+        format_comments(&mut s, "", &ast.comments);
+    }
+
+    s
+}
+
+fn format_instruction(s: &mut String, i: &ast::Instruction) {
+    format_comments(s, "", &i.comments.leading);
+    format_doc(s, "", &i.doc);
+    format_comments(s, "", &i.comments.inner);
+    if let Some(note) = &i.deprecated {
+        writeln!(s, "@deprecated(\"{note}\")").unwrap();
+    }
+    *s += "instruction ";
+    *s += &i.name;
+    if !i.parameters.is_empty() {
+        let params = i
+            .parameters
+            .iter()
+            .map(|p| match &p.default {
+                None => p.name.clone(),
+                Some(d) => format!("{} = {}", p.name, format_base_parameter(d)),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(s, "<{params}>").unwrap();
+    }
+    if let Some(width) = i.width {
+        write!(s, ": width {width}").unwrap();
+    }
+    if let Some(base) = &i.base {
+        let params = base
+            .parameters
+            .iter()
+            .map(format_base_parameter)
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(s, ": {}<{params}>", base.name).unwrap();
+    }
+    *s += " {";
+    format_trailing(s, &i.comments);
+
+    if let Some(timing) = &i.timing {
+        let timing = match timing {
+            Timing::Cycle(_) | Timing::Nanos(_) | Timing::Stages(_) => {
+                timing.to_string()
+            }
+            Timing::Async => "async".to_owned(),
+            Timing::Multi => "multi".to_owned(),
+            Timing::MultiUpTo(n) => format!("multi {n} cycles"),
+            Timing::MultiRange(min, max) => {
+                format!("multi {min}..{max} cycles")
+            }
+        };
+        let line = format!("timing: {timing}");
+        format_line(s, INDENT, &i.timing_comments, "", &line);
+    }
+
+    if !i.fields.is_empty() || !i.fields_comments.is_empty() {
+        format_line(s, INDENT, &i.fields_comments, "", "fields:");
+        let indent = INDENT.repeat(2);
+        for f in &i.fields {
+            let ty = match &f.ty {
+                ast::FieldType::FixedWidth(w) => w.to_string(),
+                ast::FieldType::Class(c) => c.clone(),
+            };
+            let signed = if f.is_signed { " signed" } else { "" };
+            let radix = match f.radix {
+                ast::Radix::Decimal => "",
+                ast::Radix::Hex => " hex",
+                ast::Radix::Binary => " bin",
+            };
+            let pad = if f.pad == 0 {
+                String::default()
+            } else {
+                format!(" pad {}", f.pad)
+            };
+            let scale = if f.scale == 1 {
+                String::default()
+            } else {
+                format!(" scale {}", f.scale)
+            };
+            let aliases = if f.aliases.is_empty() {
+                String::default()
+            } else {
+                format!(" (alias {})", f.aliases.join(", "))
+            };
+            let bits = if f.bits.is_empty() {
+                String::default()
+            } else {
+                format!(" {{{}}}", f.bits.join(", "))
+            };
+            let allowed = match &f.allowed {
+                None => String::default(),
+                Some(values) => {
+                    let values: Vec<String> =
+                        values.iter().map(u64::to_string).collect();
+                    format!(" in {{{}}}", values.join(", "))
+                }
+            };
+            let value = match &f.value {
+                None => String::default(),
+                Some(ast::FieldValue::NumericConstant(v)) => format!(" = {v}"),
+                Some(ast::FieldValue::GenericParameter(p)) => {
+                    format!(" = ${p}")
+                }
+                Some(ast::FieldValue::OptionalFieldValue(_)) => {
+                    panic!("optional field values have no syntax")
+                }
+            };
+            let line = format!(
+                "{}: {ty}{signed}{radix}{pad}{scale}{aliases}{bits}{allowed}{value},",
+                f.name
+            );
+            format_line(s, &indent, &f.comments, &f.doc, &line);
+        }
+    }
+
+    let assembly = &i.assembly;
+    let examples =
+        !assembly.example.is_empty() || !assembly.examples_comments.is_empty();
+    if !assembly.syntax.is_empty() || examples || !assembly.comments.is_empty()
+    {
+        if !i.fields.is_empty() {
+            *s += "\n";
+        }
+        format_line(s, INDENT, &assembly.comments, "", "assembly:");
+        let indent = INDENT.repeat(2);
+        if !assembly.syntax.is_empty() {
+            let forms: Vec<String> = std::iter::once(&assembly.syntax)
+                .chain(&assembly.alternatives)
+                .map(|form| assembly_string(form))
+                .collect();
+            let line = format!("{};", forms.join(" | "));
+            format_line(s, &indent, &assembly.syntax_comments, "", &line);
+        }
+        if examples {
+            if !assembly.syntax.is_empty() {
+                *s += "\n";
+            }
+            let comments = &assembly.examples_comments;
+            format_line(s, &indent, comments, "", "examples:");
+            let indent = INDENT.repeat(3);
+            for (n, e) in assembly.example.iter().enumerate() {
+                if n > 0 {
+                    *s += "\n";
+                }
+                let line = format!("{};", e.example);
+                format_line(s, &indent, &e.comments, &e.doc, &line);
+            }
+        }
+    }
+
+    if !i.machine.layout.is_empty() {
+        *s += "\n";
+        format_line(s, INDENT, &i.machine.comments, "", "machine:");
+        let indent = INDENT.repeat(2);
+        let positions = i.machine.positions.iter().copied();
+        let none = Comments::default();
+        let comments = i.machine.element_comments.iter();
+        for (((e, doc), position), comments) in i
+            .machine
+            .layout
+            .iter()
+            .zip(&i.machine.docs)
+            .zip(positions.chain(std::iter::repeat(None)))
+            .zip(comments.chain(std::iter::repeat(&none)))
+        {
+            let line = format!("{},", machine_element_string(e, position));
+            format_line(s, &indent, comments, doc, &line);
+        }
+    }
+
+    format_comments(s, &INDENT.repeat(2), &i.closing_comments.leading);
+    *s += "}";
+    format_trailing(s, &i.closing_comments);
+    format_comments(s, "", &i.comments.after);
+}
+
+/// Emit a declaration that fits on one line, with its docstring and
+/// comments.
+fn format_line(
+    s: &mut String,
+    indent: &str,
+    comments: &Comments,
+    doc: &str,
+    line: &str,
+) {
+    format_comments(s, indent, &comments.leading);
+    format_doc(s, indent, doc);
+    format_comments(s, indent, &comments.inner);
+    write!(s, "{indent}{line}").unwrap();
+    format_trailing(s, comments);
+    format_comments(s, indent, &comments.after);
+}
+
+/// End a line, with its trailing comment if it has one.
+fn format_trailing(s: &mut String, comments: &Comments) {
+    match &comments.trailing {
+        Some(c) => writeln!(s, " {c}").unwrap(),
+        None => *s += "\n",
+    }
+}
+
+fn format_base_parameter(p: &BaseParameter) -> String {
+    match p {
+        BaseParameter::Number(n) => n.to_string(),
+        BaseParameter::Text(t) => format!("'{}'", escape(t)),
+        BaseParameter::Parameter(p) => format!("${p}"),
+    }
+}
+
+/// Escape the quotes, backslashes and newlines of a string literal.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\'', "\\'")
+        .replace('\n', "\\n")
+}
+
+fn format_comments(s: &mut String, indent: &str, comments: &[String]) {
+    for c in comments {
+        if c.is_empty() {
+            *s += "\n";
+        } else {
+            writeln!(s, "{indent}{c}").unwrap();
+        }
+    }
+}
+
+/// The line and block comments in ISF text, with the line each starts on.
+/// Docstrings and the contents of string literals are skipped.
+fn source_comments(text: &str) -> Vec<(usize, &str)> {
+    let mut comments = Vec::new();
+    let mut line = 1;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let end = if rest.starts_with("//") {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            if !rest.starts_with("///") && !rest.starts_with("//!") {
+                comments.push((line, rest[..end].trim_end()));
+            }
+            end
+        } else if rest.starts_with("/*") {
+            let end = rest.find("*/").map_or(rest.len(), |e| e + 2);
+            comments.push((line, &rest[..end]));
+            end
+        } else if c == '\'' || c == '"' {
+            let mut chars = rest.char_indices().skip(1);
+            let mut end = rest.len();
+            while let Some((i, x)) = chars.next() {
+                if x == '\\' {
+                    chars.next();
+                } else if x == c {
+                    end = i + 1;
+                    break;
+                }
+            }
+            end
+        } else {
+            c.len_utf8()
+        };
+        line += rest[..end].matches('\n').count();
+        rest = &rest[end..];
+    }
+    comments
+}
+
+fn format_doc(s: &mut String, indent: &str, doc: &str) {
+    for line in doc.lines() {
+        if line.is_empty() {
+            writeln!(s, "{indent}///").unwrap();
+        } else {
+            writeln!(s, "{indent}/// {line}").unwrap();
+        }
+    }
+}
+
+fn assembly_string(syntax: &[AssemblyElement]) -> String {
+    let mut s = String::default();
+    for x in syntax {
+        match x {
+            AssemblyElement::Expansion { name } => write!(s, "${name}"),
+            AssemblyElement::StringLiteral { value } => {
+                write!(s, "'{}'", escape(value))
+            }
+            AssemblyElement::NumberLiteral { value } => write!(s, "{value}"),
+            AssemblyElement::OptionalFlag { name, field } => {
+                write!(s, "['{}' = {field}]", escape(name))
+            }
+            AssemblyElement::Optional { elements } => {
+                write!(s, "[{}]", assembly_string(elements))
+            }
+            AssemblyElement::Conditional { field, elements } => {
+                write!(s, "[{field} => {}]", assembly_string(elements))
+            }
+            AssemblyElement::Dot => write!(s, "."),
+            AssemblyElement::Comma => write!(s, ","),
+            AssemblyElement::Space => write!(s, " "),
+            AssemblyElement::Field { name } => write!(s, "{name}"),
+        }
+        .unwrap();
+    }
+    s
+}
+
+/// A machine layout element, with its bit position if it has one. The
+/// position of a constant goes between its name and its width.
+fn machine_element_string(
+    e: &MachineElement,
+    position: Option<usize>,
+) -> String {
+    let element = machine_element_kind_string(e);
+    let Some(position) = position else {
+        return element;
+    };
+    match e {
+        MachineElement::Constant { name, .. }
+        | MachineElement::Fill { name } => {
+            format!("{name} @ {position}{}", &element[name.len()..])
+        }
+        _ => format!("{element} @ {position}"),
+    }
+}
+
+fn machine_element_kind_string(e: &MachineElement) -> String {
+    match e {
+        MachineElement::Field { name } => name.clone(),
+        MachineElement::FieldSlice {
+            name,
+            begin,
+            end,
+            negate,
+        } => {
+            let negate = if *negate { "!" } else { "" };
+            format!("{name}[{begin}:{end}]{negate}")
+        }
+        MachineElement::FieldNegate { name } => format!("{name}!"),
+        MachineElement::FieldSign { name } => format!("{name}.sign"),
+        MachineElement::OptionalFieldPresentTest { name } => {
+            format!("{name}?")
+        }
+        MachineElement::OptionalFieldAbsentTest { name } => {
+            format!("{name}?!")
+        }
+        MachineElement::Constant { name, width, value } => match value {
+            None => format!("{name}: {width}"),
+            Some(MachineElementValue::NumericConstant(v)) => {
+                format!("{name}: {width} = {v}")
+            }
+            Some(MachineElementValue::SignedConstant(v)) => {
+                format!("{name}: {width} = {v}")
+            }
+            Some(MachineElementValue::GenericParameter(p)) => {
+                format!("{name}: {width} = ${p}")
+            }
+        },
+        MachineElement::Fill { name } => format!("{name}: fill"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn fmt_preserves_comments() {
+        let text = read_to_string("testcase/add.isf").unwrap();
+        let formatted = format_spec("testcase/add.isf").unwrap();
+        // The formatter only adds a blank line below the docstring.
+        assert_eq!(
+            formatted,
+            text.replacen("generation.\n", "generation.\n\n", 1)
+        );
+    }
+
+    #[test]
+    fn fmt_keeps_block_comments() {
+        let text = read_to_string("testcase/block-comment.isf").unwrap();
+        let formatted = format_spec("testcase/block-comment.isf").unwrap();
+        assert_eq!(formatted, text);
+    }
+
+    #[test]
+    fn fmt_refuses_dropped_comments() {
+        let text = read_to_string("testcase/class.isf").unwrap().replacen(
+            "width: 2\n",
+            "width: 2 /* bits */\n",
+            1,
+        );
+        let path = std::env::temp_dir().join("isf-fmt-dropped-comment.isf");
+        std::fs::write(&path, text).unwrap();
+        let path = path.to_str().unwrap();
+        let err = format_spec(path).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("{path}:5: comment cannot be kept by fmt: /* bits */")
+        );
+    }
+
+    #[test]
+    fn fmt_escapes_string_literals() {
+        let text = "instruction_width = 32;\n\n\
+            /// Emit a quoted directive\n\
+            instruction Quote {\n  \
+              fields:\n    \
+                /// The value\n    \
+                v: 8,\n\n  \
+              assembly:\n    \
+                '.q \\'\\\\' v;\n\n  \
+              machine:\n    \
+                v,\n    \
+                _: 24,\n\
+            }\n";
+        let mut s = text;
+        let ast = crate::parse::parse(&mut s).unwrap();
+        let formatted = format(&ast);
+        assert!(formatted.contains(r"'.q \'\\' v;"), "{formatted}");
+        let mut s = formatted.as_str();
+        assert_eq!(format(&crate::parse::parse(&mut s).unwrap()), formatted);
+    }
+
+    #[test]
+    fn fmt_timing_units() {
+        let text = read_to_string("testcase/timing-units.isf")
+            .unwrap()
+            .replace("1 cycle", "3 cycles")
+            .replace("2 stages", "1 stage");
+        let ast = parse::parse.parse(text.as_str()).unwrap();
+        assert_eq!(format(&ast), text);
+    }
+
+    const TESTCASES: &[&str] = &[
+        "testcase/add.isf",
+        "testcase/add-reg.isf",
+        "testcase/add-field-opt.isf",
+        "testcase/alias.isf",
+        "testcase/allowed.isf",
+        "testcase/alternatives.isf",
+        "testcase/big-endian.isf",
+        "testcase/binop.isf",
+        "testcase/block-comment.isf",
+        "testcase/class.isf",
+        "testcase/comma.isf",
+        "testcase/conditional.isf",
+        "testcase/deprecated.isf",
+        "testcase/field-bits.isf",
+        "testcase/field-widths.isf",
+        "testcase/mixed-width.isf",
+        "testcase/negate-slice.isf",
+        "testcase/multi-timing.isf",
+        "testcase/nested-base.isf",
+        "testcase/odd-width.isf",
+        "testcase/optional-group.isf",
+        "testcase/pad.isf",
+        "testcase/positions.isf",
+        "testcase/radix.isf",
+        "testcase/reset.isf",
+        "testcase/scale.isf",
+        "testcase/sign-magnitude.isf",
+        "testcase/signed.isf",
+        "testcase/slice-add.isf",
+        "testcase/slice-add-msb.isf",
+        "testcase/timing-units.isf",
+        "testcase/word-size.isf",
+    ];
+
+    #[test]
+    fn fmt_idempotent() {
+        for path in TESTCASES {
+            let first = format_spec(path).unwrap();
+            let ast = parse::parse.parse(first.as_str()).unwrap();
+            let second = format(&ast);
+            assert_eq!(first, second, "{path}");
+        }
+    }
+
+    #[test]
+    fn fmt_keeps_comments_and_ast() {
+        for path in TESTCASES {
+            let text = read_to_string(path).unwrap();
+            let formatted = format_spec(path).unwrap();
+            let comments = |s| {
+                source_comments(s)
+                    .into_iter()
+                    .map(|(_, c)| c)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(comments(&text), comments(&formatted), "{path}");
+            let before = parse::parse.parse(text.as_str()).unwrap();
+            let after = parse::parse.parse(formatted.as_str()).unwrap();
+            assert_eq!(format!("{before:?}"), format!("{after:?}"), "{path}");
+        }
+    }
+}
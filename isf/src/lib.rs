@@ -2,14 +2,33 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+//! Without the default `std` feature only the parts of this crate used by
+//! code generated in `no_std` mode are available: the [`bits`] helpers, the
+//! [`MachineInstruction`] trait and the error types.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
 pub mod ast;
 pub mod bits;
+#[cfg(feature = "std")]
 pub mod codegen;
+#[cfg(feature = "std")]
 pub mod docgen;
+#[cfg(feature = "std")]
+pub mod export;
+#[cfg(feature = "std")]
+pub mod fmt;
+#[cfg(feature = "std")]
 pub mod parse;
+#[cfg(feature = "std")]
 pub mod spec;
 
+#[cfg(feature = "std")]
+pub use ast::Timing;
+
 /// Functions for interacting with instructions in assembly format.
+#[cfg(feature = "std")]
 pub trait AssemblyInstruction: Sized {
     /// Parse an assembly instruction from text.
     fn parse_assembly(
@@ -18,21 +37,242 @@ pub trait AssemblyInstruction: Sized {
         Self,
         winnow::error::ParseError<&str, winnow::error::ContextError>,
     >;
-    /// Emit assembly instruction in text form.
+    /// Parse an assembly instruction from text as [`parse_assembly`] does,
+    /// reporting failure as an [`AssemblyError`] that locates it.
+    ///
+    /// [`parse_assembly`]: AssemblyInstruction::parse_assembly
+    fn parse_assembly_detailed(text: &str) -> Result<Self, AssemblyError> {
+        Self::parse_assembly(text).map_err(AssemblyError::from)
+    }
+    /// Emit assembly instruction in canonical text form: elements are
+    /// separated by a single space or, at a comma, by a comma and a space,
+    /// with no leading or trailing whitespace.
     fn emit_assembly(&self) -> String;
 }
 
+/// An assembly instruction does not parse, with the location of the
+/// failure for presenting to users.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyError {
+    /// Byte offset of the failure in the text.
+    pub offset: usize,
+    /// One-based character column of the failure.
+    pub column: usize,
+    /// The text from the failure onward.
+    pub remaining: String,
+    /// What the parser expected at the failure.
+    pub message: String,
+}
+
+#[cfg(feature = "std")]
+impl From<winnow::error::ParseError<&str, winnow::error::ContextError>>
+    for AssemblyError
+{
+    fn from(
+        e: winnow::error::ParseError<&str, winnow::error::ContextError>,
+    ) -> Self {
+        use winnow::error::StrContext;
+        let text = *e.input();
+        let offset = e.offset();
+        let remaining = &text[offset..];
+        let label = e.inner().context().find_map(|c| match c {
+            StrContext::Label(l) => Some(l),
+            _ => None,
+        });
+        let expected = e
+            .inner()
+            .context()
+            .filter_map(|c| match c {
+                StrContext::Expected(x) => Some(x.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message = match (expected.is_empty(), label) {
+            (false, Some(label)) => format!("expected {expected} for {label}"),
+            (false, None) => format!("expected {expected}"),
+            (true, Some(label)) => format!("invalid {label}"),
+            (true, None) if remaining.is_empty() => {
+                "unexpected end of input".to_owned()
+            }
+            (true, None) => "unexpected input".to_owned(),
+        };
+        AssemblyError {
+            offset,
+            column: text[..offset].chars().count() + 1,
+            remaining: remaining.to_owned(),
+            message,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at column {}", self.message, self.column)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AssemblyError {}
+
 /// Functions for interacting with instructions in machine format.
 pub trait MachineInstruction<T>: Sized {
-    /// Parse an assembly instruction from text.
+    /// Parse an instruction from a machine word.
     fn parse_machine(data: T) -> Result<Self, FieldMismatchError>;
-    /// Emit assembly instruction in text form.
+    /// Emit the instruction as a machine word.
     fn emit_machine(&self) -> T;
 }
 
+/// Object-safe view of a decoded instruction.
+#[cfg(feature = "std")]
+pub trait DecodedInstruction: std::fmt::Debug {
+    /// Name of the instruction.
+    fn name(&self) -> &'static str;
+    /// Same as [`AssemblyInstruction::emit_assembly`].
+    fn assembly(&self) -> String;
+    /// Emit machine instruction widened to 128 bits.
+    fn machine(&self) -> u128;
+    /// Type-erased view of the instruction's operands and timing.
+    fn view(&self) -> spec::DecodedView;
+}
+
+/// Object-safe decoder for the instructions of a spec. Decoders from
+/// multiple specs may be combined as `Vec<Box<dyn DecoderPlugin>>`.
+#[cfg(feature = "std")]
+pub trait DecoderPlugin {
+    /// Decode a machine word, returning `None` if no instruction matches.
+    fn try_decode(&self, word: u128) -> Option<Box<dyn DecodedInstruction>>;
+}
+
+/// Placement of an instruction field within the machine layout. A field
+/// split into slices has one entry per slice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    /// Bit offset of the placement from the least significant bit.
+    pub offset: usize,
+    /// Number of bits in the placement.
+    pub width: usize,
+    /// True for single-bit fields.
+    pub flag: bool,
+}
+
+/// A machine word holds a value a field does not allow.
 #[derive(Debug)]
 pub struct FieldMismatchError {
-    pub field: String,
+    pub field: &'static str,
+    /// The value of a constant field, or the first allowed value of a
+    /// constrained field.
     pub expected: u64,
+    /// The values a constrained field allows, empty for constant fields.
+    pub allowed: &'static [u64],
     pub found: u64,
 }
+
+impl core::fmt::Display for FieldMismatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.allowed.is_empty() {
+            write!(
+                f,
+                "field {} holds {}, expected {}",
+                self.field, self.found, self.expected
+            )
+        } else {
+            write!(
+                f,
+                "field {} holds {}, expected one of {:?}",
+                self.field, self.found, self.allowed
+            )
+        }
+    }
+}
+
+impl core::error::Error for FieldMismatchError {}
+
+/// A value passed to a checked field setter does not fit in the field, or
+/// is not one of the values the field allows.
+#[derive(Debug)]
+pub struct FieldOverflowError {
+    pub field: &'static str,
+    pub width: usize,
+    /// The rejected value. For signed fields, its magnitude.
+    pub value: u128,
+    /// The values a constrained field allows, empty for other fields.
+    pub allowed: &'static [u64],
+}
+
+impl core::fmt::Display for FieldOverflowError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.allowed.is_empty() {
+            write!(
+                f,
+                "value {} does not fit in {}-bit field {}",
+                self.value, self.width, self.field
+            )
+        } else {
+            write!(
+                f,
+                "value {} is not allowed in field {}, expected one of {:?}",
+                self.value, self.field, self.allowed
+            )
+        }
+    }
+}
+
+impl core::error::Error for FieldOverflowError {}
+
+/// A machine word at the given byte offset does not decode as any
+/// instruction.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    pub offset: usize,
+    pub raw: u128,
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "unrecognized instruction {:#x} at offset {}",
+            self.raw, self.offset
+        )
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// A line of an assembly program does not parse as any instruction.
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq, Eq)]
+pub struct AssembleError {
+    /// One-based line number.
+    pub line: usize,
+    pub text: String,
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: cannot assemble `{}`", self.line, self.text)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AssembleError {}
+
+/// A raw value does not correspond to any value of a class.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ClassValueError {
+    pub class: &'static str,
+    pub value: u128,
+}
+
+impl core::fmt::Display for ClassValueError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} is not a value of class {}", self.value, self.class)
+    }
+}
+
+impl core::error::Error for ClassValueError {}
@@ -9,48 +9,118 @@ use crate::ast;
 use winnow::{
     ascii::{
         alpha1, alphanumeric1, digit1, hex_digit1, line_ending, multispace0,
-        multispace1, till_line_ending,
+        multispace1, space0, till_line_ending,
+    },
+    combinator::{
+        alt, cut_err, fail, not, opt, preceded, repeat, separated, trace,
     },
-    combinator::{alt, cut_err, repeat, separated, trace},
     error::{ContextError, StrContext},
-    token::{none_of, take_until},
+    stream::Range,
+    token::{none_of, take_till, take_until, take_while},
     PResult, Parser,
 };
 
 /// Parse ISF text into an ISF AST.
 pub fn parse(input: &mut &str) -> PResult<ast::Ast> {
-    let spec = ast::Ast {
-        characteristics: parse_characteristics.parse_next(input)?,
-        classes: parse_classes.parse_next(input)?,
-        instructions: parse_instructions.parse_next(input)?,
+    let doc = inner_docstring.parse_next(input)?;
+    let (lines, blank_after) = comment_lines(input, 0)?;
+    let mut pending = comment_block(&lines, blank_after);
+    let mut spec = ast::Ast {
+        doc,
+        ..Default::default()
     };
+    parse_header(input, &mut pending, &mut spec)?;
+    spec.classes = parse_classes(input, &mut pending)?;
+    spec.instructions = parse_instructions(input, &mut pending)?;
+    while pending.last().is_some_and(String::is_empty) {
+        pending.pop();
+    }
+    spec.comments = pending;
     Ok(spec)
 }
 
-fn parse_characteristics(
+enum HeaderItem {
+    Characteristic(ast::Characteristic),
+    Constant(ast::Constant),
+}
+
+/// Parse the characteristics and named constants that lead a spec into
+/// `spec`, along with the comments and position of each declaration.
+fn parse_header(
     input: &mut &str,
-) -> PResult<Vec<ast::Characteristic>> {
-    let result = repeat(0.., characteristic).parse_next(input)?;
-    Ok(result)
+    pending: &mut Vec<String>,
+    spec: &mut ast::Ast,
+) -> PResult<()> {
+    let items: Vec<(HeaderItem, ast::Comments)> =
+        repeat(0.., |input: &mut &str| {
+            commented(
+                input,
+                pending,
+                alt((
+                    characteristic.map(HeaderItem::Characteristic),
+                    constant.map(HeaderItem::Constant),
+                )),
+            )
+        })
+        .parse_next(input)?;
+
+    for (position, (item, comments)) in items.into_iter().enumerate() {
+        match item {
+            HeaderItem::Characteristic(c) => {
+                spec.characteristics.push(c);
+                spec.characteristic_comments.push(comments);
+                spec.characteristic_positions.push(position);
+            }
+            HeaderItem::Constant(c) => spec.constants.push(ast::Constant {
+                position,
+                comments,
+                ..c
+            }),
+        }
+    }
+    Ok(())
 }
 
-fn parse_classes(input: &mut &str) -> PResult<Vec<ast::Class>> {
-    lcp.parse_next(input)?;
-    let result = cut_err(repeat(0.., class)).parse_next(input)?;
-    lcp.parse_next(input)?;
-    Ok(result)
+fn constant(input: &mut &str) -> PResult<ast::Constant> {
+    let name = identifier_parser.parse_next(input)?;
+    let _ = s("=").parse_next(input)?;
+    let value = number_parser.parse_next(input)?;
+    let _ = s(";").parse_next(input)?;
+    Ok(ast::Constant {
+        name,
+        value,
+        position: 0,
+        comments: ast::Comments::default(),
+    })
+}
+
+fn parse_classes(
+    input: &mut &str,
+    pending: &mut Vec<String>,
+) -> PResult<Vec<ast::Class>> {
+    cut_err(repeat(0.., |input: &mut &str| {
+        let (class, comments) = commented(input, pending, class)?;
+        Ok(ast::Class {
+            comments: ast::Comments {
+                inner: class.comments.inner,
+                ..comments
+            },
+            ..class
+        })
+    }))
+    .parse_next(input)
 }
 
 fn class(input: &mut &str) -> PResult<ast::Class> {
-    lcp.parse_next(input)?;
     let doc = docstring.parse_next(input)?;
-    lcp.parse_next(input)?;
+    let inner = inner_comments.parse_next(input)?;
 
     let _ = s("class").parse_next(input)?;
     let mut instr = cut_err(class_body)
         .context(StrContext::Label("class body"))
         .parse_next(input)?;
     instr.doc = doc;
+    instr.comments.inner = inner;
     Ok(instr)
 }
 
@@ -60,24 +130,83 @@ fn class_body(input: &mut &str) -> PResult<ast::Class> {
     lcp.parse_next(input)?;
     let _ = s("width:").parse_next(input)?;
     let width = s(number_parser).parse_next(input)?;
+    lcp.parse_next(input)?;
+    let values = if s("values:").parse_next(input).is_ok() {
+        class_values
+            .context(StrContext::Label("class values"))
+            .parse_next(input)?
+    } else {
+        Vec::default()
+    };
     let _ = s("}").parse_next(input)?;
     Ok(ast::Class {
         doc: String::default(),
+        comments: ast::Comments::default(),
         name,
         width: width.try_into().expect("width as usize"),
+        values,
     })
 }
 
-fn parse_instructions(input: &mut &str) -> PResult<Vec<ast::Instruction>> {
-    lcp.parse_next(input)?;
-    let result = cut_err(repeat(0.., instruction)).parse_next(input)?;
+fn class_values(input: &mut &str) -> PResult<Vec<ast::ClassValue>> {
+    // Comments elsewhere in a class body are not kept, so only the values
+    // carry comments.
+    let (lines, blank_after) = comment_lines(input, 1)?;
+    let mut pending = comment_block(&lines, blank_after);
+    let values = cut_err(|input: &mut &str| {
+        commented_list(input, &mut pending, 1.., class_value)
+    })
+    .parse_next(input)?;
     lcp.parse_next(input)?;
-    Ok(result)
+    Ok(values
+        .into_iter()
+        .map(|(value, comments)| ast::ClassValue {
+            comments: ast::Comments {
+                inner: value.comments.inner,
+                ..comments
+            },
+            ..value
+        })
+        .collect())
+}
+
+fn class_value(input: &mut &str) -> PResult<ast::ClassValue> {
+    let doc = opt(docstring).parse_next(input)?.unwrap_or_default();
+    let inner = inner_comments.parse_next(input)?;
+    let name = s(identifier_parser).parse_next(input)?;
+    let _ = cut_err(s('=')).parse_next(input)?;
+    let value = cut_err(s(number_parser))
+        .context(StrContext::Label("class value"))
+        .parse_next(input)?;
+    Ok(ast::ClassValue {
+        doc,
+        comments: ast::Comments {
+            inner,
+            ..Default::default()
+        },
+        name,
+        value,
+    })
+}
+
+fn parse_instructions(
+    input: &mut &str,
+    pending: &mut Vec<String>,
+) -> PResult<Vec<ast::Instruction>> {
+    cut_err(repeat(0.., |input: &mut &str| {
+        let (mut instr, comments) = commented(input, pending, instruction)?;
+        instr.comments.leading = comments.leading;
+        instr.comments.after = comments.after;
+        instr.closing_comments.trailing = comments.trailing;
+        Ok(instr)
+    }))
+    .parse_next(input)
 }
 
 fn instruction(input: &mut &str) -> PResult<ast::Instruction> {
-    lcp.parse_next(input)?;
     let doc = docstring.parse_next(input)?;
+    let inner = inner_comments.parse_next(input)?;
+    let deprecated = opt(deprecated_attribute).parse_next(input)?;
     lcp.parse_next(input)?;
 
     let _ = s("instruction").parse_next(input)?;
@@ -85,52 +214,104 @@ fn instruction(input: &mut &str) -> PResult<ast::Instruction> {
         .context(StrContext::Label("instruction body"))
         .parse_next(input)?;
     instr.doc = doc;
+    instr.comments.inner = inner;
+    instr.deprecated = deprecated;
     Ok(instr)
 }
 
+fn deprecated_attribute(input: &mut &str) -> PResult<String> {
+    let _ = s("@deprecated").parse_next(input)?;
+    let _ = cut_err(s('(')).parse_next(input)?;
+    let _ = cut_err('"').parse_next(input)?;
+    let note = take_until(0.., '"').parse_next(input)?;
+    let _ = '"'.parse_next(input)?;
+    let _ = cut_err(s(')'))
+        .context(StrContext::Label("deprecated attribute"))
+        .parse_next(input)?;
+    Ok(note.to_owned())
+}
+
 fn instruction_body(input: &mut &str) -> PResult<ast::Instruction> {
     let name = identifier_parser.parse_next(input)?;
     let parameters =
         instruction_parameters.parse_next(input).unwrap_or_default();
+    let width = opt(instruction_width_override).parse_next(input)?;
     let base = instruction_base.parse_next(input).ok();
+    let start = *input;
     let _ = s("{").parse_next(input)?;
-    lcp.parse_next(input)?;
-    let timing = if s("timing:").parse_next(input).is_ok() {
-        Some(
-            timing
-                .context(StrContext::Label("timing"))
-                .parse_next(input)?,
+    let (trailing, lines, blank_after) =
+        following_comments(input, parsed(start, input))?;
+    let mut pending = comment_block(&lines, blank_after);
+
+    let (timing, timing_comments) = match opt(|input: &mut &str| {
+        commented(
+            input,
+            &mut pending,
+            preceded(
+                s("timing:"),
+                cut_err(timing).context(StrContext::Label("timing")),
+            ),
         )
-    } else {
-        None
-    };
-    let fields = if s("fields:").parse_next(input).is_ok() {
-        fields
-            .context(StrContext::Label("fields"))
-            .parse_next(input)?
-    } else {
-        Vec::default()
+    })
+    .parse_next(input)?
+    {
+        Some((timing, comments)) => (Some(timing), comments),
+        None => (None, ast::Comments::default()),
     };
-    let assembly = if s("assembly:").parse_next(input).is_ok() {
-        cut_err(assembly)
-            .context(StrContext::Label("assembly"))
-            .parse_next(input)?
-    } else {
-        ast::Assembly::default()
+    let (fields, fields_comments) =
+        match section(input, &mut pending, "fields:")? {
+            Some(comments) => {
+                let fields = (|input: &mut &str| fields(input, &mut pending))
+                    .context(StrContext::Label("fields"))
+                    .parse_next(input)?;
+                (fields, comments)
+            }
+            None => (Vec::default(), ast::Comments::default()),
+        };
+    let assembly = match section(input, &mut pending, "assembly:")? {
+        Some(comments) => {
+            let assembly =
+                cut_err(|input: &mut &str| assembly(input, &mut pending))
+                    .context(StrContext::Label("assembly"))
+                    .parse_next(input)?;
+            ast::Assembly {
+                comments,
+                ..assembly
+            }
+        }
+        None => ast::Assembly::default(),
     };
 
-    let machine = if s("machine:").parse_next(input).is_ok() {
-        cut_err(machine)
-            .context(StrContext::Label("machine"))
-            .parse_next(input)?
-    } else {
-        ast::Machine::default()
+    let machine = match section(input, &mut pending, "machine:")? {
+        Some(comments) => {
+            let machine =
+                cut_err(|input: &mut &str| machine(input, &mut pending))
+                    .context(StrContext::Label("machine"))
+                    .parse_next(input)?;
+            ast::Machine {
+                comments,
+                ..machine
+            }
+        }
+        None => ast::Machine::default(),
     };
     let _ = s("}").parse_next(input)?;
     Ok(ast::Instruction {
         doc: String::default(),
+        comments: ast::Comments {
+            trailing,
+            ..Default::default()
+        },
+        timing_comments,
+        fields_comments,
+        closing_comments: ast::Comments {
+            leading: pending,
+            ..Default::default()
+        },
+        deprecated: None,
         name,
         parameters,
+        width,
         base,
         timing,
         fields,
@@ -139,13 +320,44 @@ fn instruction_body(input: &mut &str) -> PResult<ast::Instruction> {
     })
 }
 
-fn instruction_parameters(input: &mut &str) -> PResult<Vec<String>> {
+fn instruction_parameters(input: &mut &str) -> PResult<Vec<ast::Parameter>> {
     let _ = s('<').parse_next(input)?;
-    let params = separated(1.., identifier_parser, s(',')).parse_next(input)?;
+    let params =
+        separated(1.., instruction_parameter, s(',')).parse_next(input)?;
     let _ = s('>').parse_next(input)?;
     Ok(params)
 }
 
+fn instruction_parameter(input: &mut &str) -> PResult<ast::Parameter> {
+    let name = s(identifier_parser).parse_next(input)?;
+    let default = if s('=').parse_next(input).is_ok() {
+        Some(
+            cut_err(s(parameter_default))
+                .context(StrContext::Label("parameter default"))
+                .parse_next(input)?,
+        )
+    } else {
+        None
+    };
+    Ok(ast::Parameter { name, default })
+}
+
+fn parameter_default(input: &mut &str) -> PResult<ast::BaseParameter> {
+    if let Ok(number) = number_parser.parse_next(input) {
+        return Ok(ast::BaseParameter::Number(number));
+    };
+    let name = string_literal.parse_next(input)?;
+    Ok(ast::BaseParameter::Text(name))
+}
+
+/// Parse a `: width N` clause giving an instruction its own width.
+fn instruction_width_override(input: &mut &str) -> PResult<usize> {
+    let _ = s(':').parse_next(input)?;
+    let _ = s("width").parse_next(input)?;
+    let width = s(number_parser).parse_next(input)?;
+    Ok(width as usize)
+}
+
 fn instruction_base(input: &mut &str) -> PResult<ast::Base> {
     let _ = s(':').parse_next(input)?;
     let name = identifier_parser.parse_next(input)?;
@@ -156,25 +368,44 @@ fn instruction_base(input: &mut &str) -> PResult<ast::Base> {
     Ok(ast::Base { name, parameters })
 }
 
-fn fields(input: &mut &str) -> PResult<Vec<ast::Field>> {
-    let result = cut_err(separated(0.., field, s(','))).parse_next(input)?;
-    let _ = s(',').parse_next(input);
-    lcp.parse_next(input)?;
-    Ok(result)
+fn fields(
+    input: &mut &str,
+    pending: &mut Vec<String>,
+) -> PResult<Vec<ast::Field>> {
+    let fields =
+        cut_err(|input: &mut &str| commented_list(input, pending, 0.., field))
+            .parse_next(input)?;
+    Ok(fields
+        .into_iter()
+        .map(|(field, comments)| ast::Field {
+            comments: ast::Comments {
+                inner: field.comments.inner,
+                ..comments
+            },
+            ..field
+        })
+        .collect())
 }
 
 fn timing(input: &mut &str) -> PResult<ast::Timing> {
     lcp.parse_next(input)?;
     let result =
         alt((cycle_timing, async_timing, multi_timing)).parse_next(input)?;
-    lcp.parse_next(input)?;
     Ok(result)
 }
 
+/// Parse a count followed by its unit: cycles, nanoseconds or pipeline
+/// stages.
 fn cycle_timing(input: &mut &str) -> PResult<ast::Timing> {
     let n = s(number_parser).parse_next(input)?;
-    let _ = s("cycle").parse_next(input)?;
-    Ok(ast::Timing::Cycle(n.try_into().unwrap()))
+    let n = n.try_into().unwrap();
+    let timing = s(alt((
+        alt(("cycles", "cycle")).value(ast::Timing::Cycle(n)),
+        "ns".value(ast::Timing::Nanos(n)),
+        alt(("stages", "stage")).value(ast::Timing::Stages(n)),
+    )))
+    .parse_next(input)?;
+    Ok(timing)
 }
 
 fn async_timing(input: &mut &str) -> PResult<ast::Timing> {
@@ -184,15 +415,29 @@ fn async_timing(input: &mut &str) -> PResult<ast::Timing> {
 
 fn multi_timing(input: &mut &str) -> PResult<ast::Timing> {
     let _ = s("multi").parse_next(input)?;
-    Ok(ast::Timing::Multi)
+    let Ok(n) = s(number_parser).parse_next(input) else {
+        return Ok(ast::Timing::Multi);
+    };
+    let n = n.try_into().unwrap();
+    let timing = if s("..").parse_next(input).is_ok() {
+        let max = cut_err(s(number_parser))
+            .context(StrContext::Label("multi timing upper bound"))
+            .parse_next(input)?;
+        ast::Timing::MultiRange(n, max.try_into().unwrap())
+    } else {
+        ast::Timing::MultiUpTo(n)
+    };
+    let _ = cut_err(s(alt(("cycles", "cycle"))))
+        .context(StrContext::Label("multi timing cycles"))
+        .parse_next(input)?;
+    Ok(timing)
 }
 
 fn field(input: &mut &str) -> PResult<ast::Field> {
-    lcp.parse_next(input)?;
     let doc = docstring
         .context(StrContext::Label("field docstring"))
         .parse_next(input)?;
-    lcp.parse_next(input)?;
+    let inner = inner_comments.parse_next(input)?;
 
     let name = cut_err(s(identifier_parser))
         .context(StrContext::Label("field identifier"))
@@ -207,17 +452,102 @@ fn field(input: &mut &str) -> PResult<ast::Field> {
         identifier_parser.map(ast::FieldType::Class),
     )))
     .parse_next(input)?;
-
-    lcp.parse_next(input)?;
+    let is_signed = s("signed").parse_next(input).is_ok();
+    let radix = if s("hex").parse_next(input).is_ok() {
+        ast::Radix::Hex
+    } else if s("bin").parse_next(input).is_ok() {
+        ast::Radix::Binary
+    } else {
+        ast::Radix::Decimal
+    };
+    let pad = if s("pad").parse_next(input).is_ok() {
+        cut_err(s(number_parser))
+            .context(StrContext::Label("field padding"))
+            .parse_next(input)?
+            .try_into()
+            .expect("padding as usize")
+    } else {
+        0
+    };
+    let scale = if s("scale").parse_next(input).is_ok() {
+        cut_err(s(number_parser))
+            .context(StrContext::Label("field scale"))
+            .parse_next(input)?
+    } else {
+        1
+    };
+    let aliases = opt(field_aliases).parse_next(input)?.unwrap_or_default();
+    let bits = opt(field_bits).parse_next(input)?.unwrap_or_default();
+    let allowed = opt(field_allowed).parse_next(input)?;
+    let value = if s('=').parse_next(input).is_ok() {
+        Some(
+            cut_err(s(field_value))
+                .context(StrContext::Label("field value"))
+                .parse_next(input)?,
+        )
+    } else {
+        None
+    };
 
     Ok(ast::Field {
         doc,
+        comments: ast::Comments {
+            inner,
+            ..Default::default()
+        },
         name,
         ty,
-        value: None, //TODO
+        value,
+        is_signed,
+        radix,
+        pad,
+        scale,
+        aliases,
+        bits,
+        allowed,
     })
 }
 
+/// Parse the set of values a field may hold, e.g. `in {0, 1, 2}`.
+fn field_allowed(input: &mut &str) -> PResult<Vec<u64>> {
+    let _ = s("in").parse_next(input)?;
+    let _ = cut_err(s('{')).parse_next(input)?;
+    let allowed = cut_err(separated(1.., s(number_parser), s(',')))
+        .context(StrContext::Label("allowed field value"))
+        .parse_next(input)?;
+    let _ = cut_err(s('}')).parse_next(input)?;
+    Ok(allowed)
+}
+
+/// Parse the names of the bits of a field, e.g. `{carry, zero}`.
+fn field_bits(input: &mut &str) -> PResult<Vec<String>> {
+    let _ = s('{').parse_next(input)?;
+    let bits = cut_err(separated(1.., s(identifier_parser), s(',')))
+        .context(StrContext::Label("field bit name"))
+        .parse_next(input)?;
+    let _ = cut_err(s('}')).parse_next(input)?;
+    Ok(bits)
+}
+
+/// Parse the aliases of a field, e.g. `(alias rd, d)`.
+fn field_aliases(input: &mut &str) -> PResult<Vec<String>> {
+    let _ = (s('('), "alias").parse_next(input)?;
+    let aliases = cut_err(separated(1.., identifier_parser, s(',')))
+        .context(StrContext::Label("field alias"))
+        .parse_next(input)?;
+    let _ = cut_err(s(')')).parse_next(input)?;
+    Ok(aliases)
+}
+
+fn field_value(input: &mut &str) -> PResult<ast::FieldValue> {
+    if let Ok(number) = number_parser.parse_next(input) {
+        return Ok(ast::FieldValue::NumericConstant(number));
+    };
+    let _ = '$'.parse_next(input)?;
+    let name = identifier_parser.parse_next(input)?;
+    Ok(ast::FieldValue::GenericParameter(name))
+}
+
 fn docstring(input: &mut &str) -> PResult<String> {
     let lines: Vec<String> = repeat(1.., docstring_line).parse_next(input)?;
     Ok(lines.join("\n"))
@@ -231,24 +561,84 @@ fn docstring_line(input: &mut &str) -> PResult<String> {
     Ok(ds.trim().to_owned())
 }
 
-fn assembly(input: &mut &str) -> PResult<ast::Assembly> {
+fn inner_docstring(input: &mut &str) -> PResult<String> {
+    let lines: Vec<String> =
+        repeat(0.., inner_docstring_line).parse_next(input)?;
+    Ok(lines.join("\n"))
+}
+
+fn inner_docstring_line(input: &mut &str) -> PResult<String> {
+    let _ = multispace0.parse_next(input)?;
+    let _ = "//!".parse_next(input)?;
+    let ds = till_line_ending.parse_next(input)?;
+    let _ = line_ending.parse_next(input)?;
+    Ok(ds.trim().to_owned())
+}
+
+fn assembly(
+    input: &mut &str,
+    pending: &mut Vec<String>,
+) -> PResult<ast::Assembly> {
     lcp.parse_next(input)?;
     let _ = multispace0.parse_next(input)?;
-    let syntax: Vec<ast::AssemblyElement> = if !input.starts_with("examples:") {
-        repeat(0.., assembly_element).parse_next(input)?
-    } else {
-        Vec::default()
-    };
-    if !syntax.is_empty() {
-        let _ = s(';').parse_next(input)?;
+    let ((syntax, alternatives), syntax_comments) =
+        if !input.starts_with("examples:") {
+            opt(|input: &mut &str| commented(input, pending, assembly_syntax))
+                .parse_next(input)?
+                .unwrap_or_default()
+        } else {
+            Default::default()
+        };
+    let (example, examples_comments) =
+        match section(input, pending, "examples:")? {
+            Some(comments) => {
+                let examples = cut_err(|input: &mut &str| {
+                    assembly_examples(input, pending)
+                })
+                .parse_next(input)?;
+                (examples, comments)
+            }
+            None => (Vec::default(), ast::Comments::default()),
+        };
+    Ok(ast::Assembly {
+        syntax,
+        alternatives,
+        example,
+        comments: ast::Comments::default(),
+        syntax_comments,
+        examples_comments,
+    })
+}
+
+/// Parse the syntax of an instruction, its alternatives and the closing
+/// semicolon.
+#[allow(clippy::type_complexity)]
+fn assembly_syntax(
+    input: &mut &str,
+) -> PResult<(Vec<ast::AssemblyElement>, Vec<Vec<ast::AssemblyElement>>)> {
+    let mut syntax: Vec<ast::AssemblyElement> =
+        repeat(1.., assembly_element).parse_next(input)?;
+    let mut alternatives = Vec::new();
+    while s('|').parse_next(input).is_ok() {
+        let alternative: Vec<ast::AssemblyElement> =
+            cut_err(repeat(1.., assembly_element))
+                .context(StrContext::Label("alternative assembly syntax"))
+                .parse_next(input)?;
+        alternatives.push(alternative);
     }
-    lcp.parse_next(input)?;
-    let example = if s("examples:").parse_next(input).is_ok() {
-        cut_err(assembly_examples).parse_next(input)?
-    } else {
-        Vec::default()
-    };
-    Ok(ast::Assembly { syntax, example })
+    // Spaces around a `|` separate the forms rather than belong to them.
+    if !alternatives.is_empty() {
+        for form in std::iter::once(&mut syntax).chain(&mut alternatives) {
+            while form.first() == Some(&ast::AssemblyElement::Space) {
+                form.remove(0);
+            }
+            while form.last() == Some(&ast::AssemblyElement::Space) {
+                form.pop();
+            }
+        }
+    }
+    let _ = s(';').parse_next(input)?;
+    Ok((syntax, alternatives))
 }
 
 fn assembly_element(input: &mut &str) -> PResult<ast::AssemblyElement> {
@@ -256,7 +646,8 @@ fn assembly_element(input: &mut &str) -> PResult<ast::AssemblyElement> {
         assembly_element_expansion,
         assembly_element_string_literal,
         assembly_element_optional_flag,
-        assembly_element_optional_field,
+        assembly_element_conditional,
+        assembly_element_optional,
         assembly_element_identifier,
         assembly_element_dot,
         assembly_element_comma,
@@ -265,11 +656,24 @@ fn assembly_element(input: &mut &str) -> PResult<ast::AssemblyElement> {
     .parse_next(input)
 }
 
+/// Parse a quoted string literal. A backslash escapes a quote (`\'`), a
+/// backslash (`\\`) or a newline (`\n`).
 fn string_literal(input: &mut &str) -> PResult<String> {
     let _ = "'".parse_next(input)?;
-    let content = take_until(0.., "'").parse_next(input)?;
-    let _ = "'".parse_next(input)?;
-    Ok(content.to_owned())
+    let mut content = String::new();
+    loop {
+        let chunk = take_till(0.., ['\'', '\\']).parse_next(input)?;
+        content.push_str(chunk);
+        if opt('\'').parse_next(input)?.is_some() {
+            return Ok(content);
+        }
+        let _ = '\\'.parse_next(input)?;
+        let escaped =
+            cut_err(alt(('\''.value('\''), '\\'.value('\\'), 'n'.value('\n'))))
+                .context(StrContext::Label("string escape"))
+                .parse_next(input)?;
+        content.push(escaped);
+    }
 }
 
 fn assembly_element_expansion(
@@ -303,14 +707,24 @@ fn assembly_element_optional_flag(
     })
 }
 
-fn assembly_element_optional_field(
+fn assembly_element_optional(
     input: &mut &str,
 ) -> PResult<ast::AssemblyElement> {
     let _ = '['.parse_next(input)?;
-    let with_dot = s('.').parse_next(input).is_ok();
-    let name = s(identifier_parser).parse_next(input)?;
+    let elements = repeat(1.., assembly_element).parse_next(input)?;
     let _ = ']'.parse_next(input)?;
-    Ok(ast::AssemblyElement::OptionalField { name, with_dot })
+    Ok(ast::AssemblyElement::Optional { elements })
+}
+
+fn assembly_element_conditional(
+    input: &mut &str,
+) -> PResult<ast::AssemblyElement> {
+    let _ = '['.parse_next(input)?;
+    let field = s(identifier_parser).parse_next(input)?;
+    let _ = s("=>").parse_next(input)?;
+    let elements = repeat(1.., assembly_element).parse_next(input)?;
+    let _ = ']'.parse_next(input)?;
+    Ok(ast::AssemblyElement::Conditional { field, elements })
 }
 
 fn assembly_element_dot(input: &mut &str) -> PResult<ast::AssemblyElement> {
@@ -323,8 +737,10 @@ fn assembly_element_comma(input: &mut &str) -> PResult<ast::AssemblyElement> {
     Ok(ast::AssemblyElement::Comma)
 }
 
+/// Parse a run of whitespace. Line breaks, whether `\n` or `\r\n`, are
+/// whitespace like any other.
 fn assembly_element_space(input: &mut &str) -> PResult<ast::AssemblyElement> {
-    let _ = alt((s("\n"), multispace1)).parse_next(input)?;
+    let _ = multispace1.parse_next(input)?;
     Ok(ast::AssemblyElement::Space)
 }
 
@@ -335,36 +751,91 @@ fn assembly_element_identifier(
     Ok(ast::AssemblyElement::Field { name: value })
 }
 
-fn assembly_examples(input: &mut &str) -> PResult<Vec<ast::AssemblyExample>> {
-    cut_err(repeat(0.., assembly_example)).parse_next(input)
+fn assembly_examples(
+    input: &mut &str,
+    pending: &mut Vec<String>,
+) -> PResult<Vec<ast::AssemblyExample>> {
+    cut_err(repeat(0.., |input: &mut &str| {
+        let (example, comments) = commented(input, pending, assembly_example)?;
+        Ok(ast::AssemblyExample {
+            comments: ast::Comments {
+                inner: example.comments.inner,
+                ..comments
+            },
+            ..example
+        })
+    }))
+    .parse_next(input)
 }
 
 fn assembly_example(input: &mut &str) -> PResult<ast::AssemblyExample> {
-    lcp.parse_next(input)?;
     let doc = docstring.parse_next(input)?;
-    lcp.parse_next(input)?;
+    let inner = inner_comments.parse_next(input)?;
     let example = take_until(1.., ";").parse_next(input)?.trim().to_owned();
     let _ = (";").parse_next(input)?;
-    lcp.parse_next(input)?;
-    Ok(ast::AssemblyExample { doc, example })
+    Ok(ast::AssemblyExample {
+        doc,
+        example,
+        comments: ast::Comments {
+            inner,
+            ..Default::default()
+        },
+    })
 }
 
-fn machine(input: &mut &str) -> PResult<ast::Machine> {
-    let layout = separated(1.., machine_element, s(',')).parse_next(input)?;
-    let _ = s(',').parse_next(input);
-    lcp.parse_next(input)?;
-    Ok(ast::Machine { layout })
+fn machine(
+    input: &mut &str,
+    pending: &mut Vec<String>,
+) -> PResult<ast::Machine> {
+    let elements = commented_list(input, pending, 1.., machine_element)?;
+    let mut machine = ast::Machine::default();
+    for ((doc, inner, (element, position)), comments) in elements {
+        machine.layout.push(element);
+        machine.docs.push(doc);
+        machine.positions.push(position);
+        machine
+            .element_comments
+            .push(ast::Comments { inner, ..comments });
+    }
+    Ok(machine)
 }
 
-fn machine_element(input: &mut &str) -> PResult<ast::MachineElement> {
+/// Parse a machine layout element, its optional docstring and the comments
+/// after it, and its optional bit position.
+#[allow(clippy::type_complexity)]
+fn machine_element(
+    input: &mut &str,
+) -> PResult<(String, Vec<String>, (ast::MachineElement, Option<usize>))> {
     lcp.parse_next(input)?;
+    let doc = opt(docstring).parse_next(input)?.unwrap_or_default();
+    let inner = inner_comments.parse_next(input)?;
     let result = alt((machine_element_constant, machine_element_field))
         .parse_next(input)?;
-    lcp.parse_next(input)?;
-    Ok(result)
+    Ok((doc, inner, result))
+}
+
+/// Parse the bit position of a machine layout element, e.g. `@ 8`.
+fn machine_element_position(input: &mut &str) -> PResult<Option<usize>> {
+    if s('@').parse_next(input).is_err() {
+        return Ok(None);
+    }
+    let position = cut_err(s(number_parser))
+        .context(StrContext::Label("bit position"))
+        .parse_next(input)?;
+    Ok(Some(position.try_into().expect("bit position as usize")))
+}
+
+fn machine_element_field(
+    input: &mut &str,
+) -> PResult<(ast::MachineElement, Option<usize>)> {
+    let element = machine_element_field_kind.parse_next(input)?;
+    let position = machine_element_position.parse_next(input)?;
+    Ok((element, position))
 }
 
-fn machine_element_field(input: &mut &str) -> PResult<ast::MachineElement> {
+fn machine_element_field_kind(
+    input: &mut &str,
+) -> PResult<ast::MachineElement> {
     let name = identifier_parser.parse_next(input)?;
     if tag('[').parse_next(input).is_ok() {
         let begin = number_parser.parse_next(input)?;
@@ -375,9 +846,12 @@ fn machine_element_field(input: &mut &str) -> PResult<ast::MachineElement> {
             name,
             begin: begin.try_into().unwrap(),
             end: end.try_into().unwrap(),
+            negate: tag('!').parse_next(input).is_ok(),
         })
     } else if tag('!').parse_next(input).is_ok() {
         Ok(ast::MachineElement::FieldNegate { name })
+    } else if tag(".sign").parse_next(input).is_ok() {
+        Ok(ast::MachineElement::FieldSign { name })
     } else if tag('?').parse_next(input).is_ok() {
         if tag('!').parse_next(input).is_ok() {
             Ok(ast::MachineElement::OptionalFieldAbsentTest { name })
@@ -389,20 +863,27 @@ fn machine_element_field(input: &mut &str) -> PResult<ast::MachineElement> {
     }
 }
 
-fn machine_element_constant(input: &mut &str) -> PResult<ast::MachineElement> {
+fn machine_element_constant(
+    input: &mut &str,
+) -> PResult<(ast::MachineElement, Option<usize>)> {
     let name = identifier_parser.parse_next(input)?;
+    let position = machine_element_position.parse_next(input)?;
     let _ = s(':').parse_next(input)?;
+    if s("fill").parse_next(input).is_ok() {
+        return Ok((ast::MachineElement::Fill { name }, position));
+    }
     let width = s(number_parser).parse_next(input)?;
     let value = if s('=').parse_next(input).is_ok() {
         Some(s(machine_element_value).parse_next(input)?)
     } else {
         None
     };
-    Ok(ast::MachineElement::Constant {
+    let element = ast::MachineElement::Constant {
         name,
         width: width.try_into().expect("machine element value as usize"),
         value,
-    })
+    };
+    Ok((element, position))
 }
 
 fn machine_element_value(
@@ -412,6 +893,15 @@ fn machine_element_value(
         let v = ast::MachineElementValue::NumericConstant(number);
         return Ok(v);
     };
+    if s('-').parse_next(input).is_ok() {
+        let number = cut_err(number_parser.verify_map(|number| {
+            i64::try_from(number).ok().and_then(i64::checked_neg)
+        }))
+        .context(StrContext::Label("negative constant"))
+        .parse_next(input)?;
+        let v = ast::MachineElementValue::SignedConstant(number);
+        return Ok(v);
+    }
     let _ = s('$').parse_next(input)?;
     let name = identifier_parser.parse_next(input)?;
     let v = ast::MachineElementValue::GenericParameter(name);
@@ -422,6 +912,10 @@ fn base_parameter(input: &mut &str) -> PResult<ast::BaseParameter> {
     if let Ok(number) = number_parser.parse_next(input) {
         return Ok(ast::BaseParameter::Number(number));
     };
+    if s('$').parse_next(input).is_ok() {
+        let name = identifier_parser.parse_next(input)?;
+        return Ok(ast::BaseParameter::Parameter(name));
+    }
     let name = string_literal.parse_next(input)?;
     Ok(ast::BaseParameter::Text(name))
 }
@@ -429,7 +923,14 @@ fn base_parameter(input: &mut &str) -> PResult<ast::BaseParameter> {
 fn characteristic(input: &mut &str) -> PResult<ast::Characteristic> {
     lcp.parse_next(input)?;
     // add others as alternates as they arise
-    let result = instruction_width_characteristic.parse_next(input)?;
+    let result = alt((
+        instruction_width_characteristic,
+        force_storage_characteristic,
+        endianness_characteristic,
+        name_characteristic,
+        version_characteristic,
+    ))
+    .parse_next(input)?;
     Ok(result)
 }
 
@@ -438,11 +939,58 @@ fn instruction_width_characteristic(
 ) -> PResult<ast::Characteristic> {
     let _ = s("instruction_width").parse_next(input)?;
     let _ = s("=").parse_next(input)?;
+    let width = alt((
+        number_parser.map(|width| {
+            ast::CharacteristicValue::NumericConstant(
+                width.try_into().expect("instruction width <= usize"),
+            )
+        }),
+        identifier_parser.map(ast::CharacteristicValue::NamedConstant),
+    ))
+    .parse_next(input)?;
+    let _ = s(";").parse_next(input)?;
+    Ok(ast::Characteristic::InstructionWidth(width))
+}
+
+fn force_storage_characteristic(
+    input: &mut &str,
+) -> PResult<ast::Characteristic> {
+    let _ = s("force_storage").parse_next(input)?;
+    let _ = s("=").parse_next(input)?;
+    let _ = 'u'.parse_next(input)?;
     let width = number_parser.parse_next(input)?;
     let _ = s(";").parse_next(input)?;
-    Ok(ast::Characteristic::InstructionWidth(
-        width.try_into().expect("instruction width <= usize"),
+    Ok(ast::Characteristic::ForceStorage(
+        width.try_into().expect("storage width <= usize"),
+    ))
+}
+
+fn endianness_characteristic(input: &mut &str) -> PResult<ast::Characteristic> {
+    let _ = s("endianness").parse_next(input)?;
+    let _ = s("=").parse_next(input)?;
+    let endianness = alt((
+        "little".value(ast::Endianness::Little),
+        "big".value(ast::Endianness::Big),
     ))
+    .parse_next(input)?;
+    let _ = s(";").parse_next(input)?;
+    Ok(ast::Characteristic::Endianness(endianness))
+}
+
+fn name_characteristic(input: &mut &str) -> PResult<ast::Characteristic> {
+    let _ = s("name").parse_next(input)?;
+    let _ = s("=").parse_next(input)?;
+    let name = s(string_literal).parse_next(input)?;
+    let _ = s(";").parse_next(input)?;
+    Ok(ast::Characteristic::Name(name))
+}
+
+fn version_characteristic(input: &mut &str) -> PResult<ast::Characteristic> {
+    let _ = s("version").parse_next(input)?;
+    let _ = s("=").parse_next(input)?;
+    let version = s(string_literal).parse_next(input)?;
+    let _ = s(";").parse_next(input)?;
+    Ok(ast::Characteristic::Version(version))
 }
 
 /// Parse an identifier.
@@ -499,19 +1047,201 @@ pub fn line_comment_parser(input: &mut &str) -> PResult<(), ContextError> {
     Ok(())
 }
 
+/// Parse a c-style block comment. Nested block comments are not supported.
+pub fn block_comment_parser(input: &mut &str) -> PResult<(), ContextError> {
+    let _ = multispace0.parse_next(input)?;
+    let _ = "/*".parse_next(input)?;
+    block_comment_body.parse_next(input)
+}
+
+/// The rest of a block comment after its `/*`.
+fn block_comment_body(input: &mut &str) -> PResult<(), ContextError> {
+    let _ = cut_err(take_until(0.., "*/"))
+        .context(StrContext::Label("block comment terminator"))
+        .parse_next(input)?;
+    let _ = "*/".parse_next(input)?;
+    Ok(())
+}
+
 pub fn lcp(input: &mut &str) -> PResult<(), ContextError> {
-    repeat(0.., line_comment_parser).parse_next(input)
+    repeat(0.., alt((line_comment_parser, block_comment_parser)))
+        .parse_next(input)
+}
+
+/// Parse a c-style line comment up to the end of its line, or a block
+/// comment, returning its text including the `//` or `/* */`.
+fn comment_text(input: &mut &str) -> PResult<String> {
+    let text = alt((
+        (("//", not('/')), till_line_ending).take(),
+        ("/*", block_comment_body).take(),
+    ))
+    .parse_next(input)?;
+    Ok(text.trim_end().to_owned())
+}
+
+/// Parse comments on lines of their own. `newlines` counts the line breaks
+/// since the last text. Returns each comment with whether a blank line comes
+/// before it, and whether a blank line follows the last one.
+fn comment_lines(
+    input: &mut &str,
+    mut newlines: usize,
+) -> PResult<(Vec<(String, bool)>, bool)> {
+    let mut lines = Vec::new();
+    loop {
+        let space = multispace0.parse_next(input)?;
+        newlines += space.matches('\n').count();
+        if let Some(text) = opt(comment_text).parse_next(input)? {
+            lines.push((text, newlines > 1));
+            newlines = 0;
+        } else {
+            return Ok((lines, newlines > 1));
+        }
+    }
 }
 
+/// Lay out comments from [`comment_lines`] as they are kept in the AST,
+/// with an empty string for each blank line between them.
+fn comment_block(lines: &[(String, bool)], blank_after: bool) -> Vec<String> {
+    let mut block = Vec::default();
+    for (i, (text, blank)) in lines.iter().enumerate() {
+        if *blank && i > 0 {
+            block.push(String::default());
+        }
+        block.push(text.clone());
+    }
+    if blank_after && !block.is_empty() {
+        block.push(String::default());
+    }
+    block
+}
+
+/// Parse comments between a docstring and its declaration.
+fn inner_comments(input: &mut &str) -> PResult<Vec<String>> {
+    let (lines, blank_after) = comment_lines(input, 0)?;
+    Ok(comment_block(&lines, blank_after))
+}
+
+/// The text consumed from `start` to reach `input`.
+fn parsed<'s>(start: &'s str, input: &'s str) -> &'s str {
+    &start[..start.len() - input.len()]
+}
+
+/// Parse the comments following a declaration, given the text the
+/// declaration was `parsed` from: a comment on the same line as its end,
+/// and the comments on lines of their own below it as [`comment_lines`]
+/// returns them.
+#[allow(clippy::type_complexity)]
+fn following_comments(
+    input: &mut &str,
+    parsed: &str,
+) -> PResult<(Option<String>, Vec<(String, bool)>, bool)> {
+    let newlines = parsed[parsed.trim_end().len()..].matches('\n').count();
+    let trailing = if newlines == 0 {
+        opt(preceded(space0, comment_text)).parse_next(input)?
+    } else {
+        None
+    };
+    let (lines, blank_after) = comment_lines(input, newlines)?;
+    Ok((trailing, lines, blank_after))
+}
+
+/// Parse a declaration and its comments. The comments in `pending` lead
+/// the declaration. Comments directly below it belong to it when a blank
+/// line sets them apart from what follows, and the rest are left in
+/// `pending` to lead the next declaration.
+fn commented<'s, O>(
+    input: &mut &'s str,
+    pending: &mut Vec<String>,
+    mut parser: impl Parser<&'s str, O, ContextError>,
+) -> PResult<(O, ast::Comments)> {
+    let start = *input;
+    let result = parser.parse_next(input)?;
+    let (trailing, lines, blank_after) =
+        following_comments(input, parsed(start, input))?;
+    let below = match lines.first() {
+        Some((_, false)) => lines
+            .iter()
+            .position(|(_, blank)| *blank)
+            .unwrap_or(lines.len()),
+        _ => 0,
+    };
+    let below = if below < lines.len() || blank_after {
+        below
+    } else {
+        0
+    };
+    let after = lines[..below]
+        .iter()
+        .map(|(text, _)| text.clone())
+        .collect();
+    let leading =
+        std::mem::replace(pending, comment_block(&lines[below..], blank_after));
+    let comments = ast::Comments {
+        leading,
+        inner: Vec::default(),
+        trailing,
+        after,
+    };
+    Ok((result, comments))
+}
+
+/// Parse a section keyword such as `fields:` with [`commented`].
+fn section(
+    input: &mut &str,
+    pending: &mut Vec<String>,
+    keyword: &'static str,
+) -> PResult<Option<ast::Comments>> {
+    let section =
+        opt(|input: &mut &str| commented(input, pending, s(keyword).void()))
+            .parse_next(input)?;
+    Ok(section.map(|(_, comments)| comments))
+}
+
+/// Parse a comma separated list of declarations with [`commented`],
+/// allowing a trailing comma.
+fn commented_list<'s, O>(
+    input: &mut &'s str,
+    pending: &mut Vec<String>,
+    occurrences: impl Into<Range>,
+    mut parser: impl Parser<&'s str, O, ContextError>,
+) -> PResult<Vec<(O, ast::Comments)>> {
+    let mut done = false;
+    repeat(occurrences, |input: &mut &'s str| {
+        if done {
+            return fail.parse_next(input);
+        }
+        let ((result, comma), comments) =
+            commented(input, pending, (parser.by_ref(), opt(s(','))))?;
+        done = comma.is_none();
+        Ok((result, comments))
+    })
+    .parse_next(input)
+}
+
+/// Parse a number with an optional leading minus sign.
+/// Magnitudes outside the range of `i64` are rejected.
+pub fn signed_number_parser(input: &mut &str) -> PResult<i64> {
+    let negative = s('-').parse_next(input).is_ok();
+    number_parser
+        .verify_map(|n| {
+            let n = i128::from(n);
+            i64::try_from(if negative { -n } else { n }).ok()
+        })
+        .parse_next(input)
+}
+
+/// Literals that do not fit in a `u64` are rejected.
 pub fn number_parser(input: &mut &str) -> PResult<u64> {
     if s("0x").parse_next(input).is_ok() {
-        let s = hex_digit1.parse_next(input)?;
-        let n = u64::from_str_radix(s, 16).unwrap();
-        Ok(n)
+        hex_digit1
+            .try_map(|s| u64::from_str_radix(s, 16))
+            .parse_next(input)
+    } else if s("0b").parse_next(input).is_ok() {
+        take_while(1.., ('0', '1'))
+            .try_map(|s| u64::from_str_radix(s, 2))
+            .parse_next(input)
     } else {
-        let s = digit1.parse_next(input)?;
-        let n: u64 = s.parse().unwrap();
-        Ok(n)
+        digit1.try_map(str::parse).parse_next(input)
     }
 }
 
@@ -533,13 +1263,225 @@ mod test {
             Ok(parsed) => {
                 println!("{parsed:#?}");
                 assert_eq!(
-                    ast::Characteristic::InstructionWidth(47),
+                    ast::Characteristic::InstructionWidth(
+                        ast::CharacteristicValue::NumericConstant(47)
+                    ),
                     parsed.characteristics[0]
                 );
             }
         }
     }
 
+    #[test]
+    fn parse_signed_number() {
+        assert_eq!(signed_number_parser.parse("-42"), Ok(-42));
+        assert_eq!(
+            signed_number_parser.parse("-9223372036854775808"),
+            Ok(i64::MIN)
+        );
+        assert!(signed_number_parser.parse("9223372036854775808").is_err());
+        assert!(signed_number_parser.parse("18446744073709551615").is_err());
+    }
+
+    #[test]
+    fn parse_crlf() {
+        let lf = read_to_string("testcase/add.isf").unwrap();
+        let crlf = read_to_string("testcase/crlf.isf").unwrap();
+        assert!(crlf.contains("\r\n"));
+        let expected = format!("{:?}", parse.parse(lf.as_str()).unwrap());
+        let ast = parse.parse(crlf.as_str()).expect("parse crlf");
+        assert_eq!(format!("{ast:?}"), expected);
+        // Files edited on more than one platform can mix line endings.
+        let mixed = crlf.replacen("\r\n", "\n", 20);
+        let ast = parse.parse(mixed.as_str()).expect("parse mixed endings");
+        assert_eq!(format!("{ast:?}"), expected);
+    }
+
+    #[test]
+    fn parse_block_comments() {
+        let text = read_to_string("testcase/block-comment.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse(&mut s).expect("parse block comments");
+        assert_eq!(ast.instructions.len(), 1);
+        assert_eq!(ast.instructions[0].name, "Add");
+        assert_eq!(ast.instructions[0].fields.len(), 2);
+        assert_eq!(ast.instructions[0].machine.layout.len(), 5);
+
+        let text = format!("{text}/* never closed\n");
+        let err = parse.parse(text.as_str()).unwrap_err();
+        assert!(
+            err.to_string().contains("block comment terminator"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn parse_signed_field() {
+        let text = read_to_string("testcase/signed.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse(&mut s).expect("parse signed");
+        let field = &ast.instructions[0].fields[0];
+        assert_eq!(field.name, "offset");
+        assert_eq!(field.ty, ast::FieldType::FixedWidth(9));
+        assert!(field.is_signed);
+    }
+
+    #[test]
+    fn parse_radix_field() {
+        let text = read_to_string("testcase/radix.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse(&mut s).expect("parse radix");
+        let fields = &ast.instructions[0].fields;
+        assert_eq!(fields[0].radix, ast::Radix::Decimal);
+        assert_eq!(fields[1].radix, ast::Radix::Hex);
+        assert_eq!(fields[2].radix, ast::Radix::Binary);
+        assert_eq!(fields[2].value, Some(ast::FieldValue::NumericConstant(15)));
+        assert_eq!(number_parser.parse("0b101"), Ok(5));
+        assert!(number_parser.parse("18446744073709551616").is_err());
+        assert!(number_parser.parse("0x1ffffffffffffffff").is_err());
+        assert!(number_parser
+            .parse(&format!("0b1{}", "0".repeat(64)))
+            .is_err());
+    }
+
+    #[test]
+    fn parse_negative_constant() {
+        let text = read_to_string("testcase/add.isf").unwrap();
+        let text = text.replacen("_: 3,", "_: 3 = -1,", 1);
+        let ast = parse.parse(text.as_str()).unwrap();
+        assert_eq!(
+            ast.instructions[0].machine.layout[3],
+            ast::MachineElement::Constant {
+                name: "_".to_owned(),
+                width: 3,
+                value: Some(ast::MachineElementValue::SignedConstant(-1)),
+            }
+        );
+        for value in ["-18446744073709551615", "-9223372036854775808"] {
+            let text = text.replace("-1", value);
+            assert!(parse.parse(text.as_str()).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn parse_conditional() {
+        use ast::AssemblyElement::*;
+        let text = read_to_string("testcase/conditional.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse(&mut s).expect("parse conditional");
+        assert_eq!(
+            ast.instructions[0].assembly.syntax.last(),
+            Some(&Conditional {
+                field: "pred".to_owned(),
+                elements: vec![
+                    Comma,
+                    Space,
+                    StringLiteral {
+                        value: "r".to_owned()
+                    },
+                    Field {
+                        name: "src3".to_owned()
+                    },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn parse_optional_group() {
+        use ast::AssemblyElement::*;
+        let text = read_to_string("testcase/optional-group.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse(&mut s).expect("parse optional group");
+        assert_eq!(
+            ast.instructions[0].assembly.syntax,
+            [
+                StringLiteral {
+                    value: "b".to_owned()
+                },
+                Optional {
+                    elements: vec![
+                        StringLiteral {
+                            value: ".cc".to_owned()
+                        },
+                        Space,
+                        Field {
+                            name: "cond".to_owned()
+                        },
+                        Optional {
+                            elements: vec![
+                                StringLiteral {
+                                    value: ".".to_owned()
+                                },
+                                OptionalFlag {
+                                    name: "+".to_owned(),
+                                    field: "likely".to_owned()
+                                },
+                            ]
+                        },
+                    ]
+                },
+                Space,
+                Field {
+                    name: "offset".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_field_bits() {
+        let text = read_to_string("testcase/field-bits.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse(&mut s).expect("parse field bits");
+        let fields = &ast.instructions[0].fields;
+        assert_eq!(fields[0].bits, ["carry", "zero", "neg", "ovf"]);
+    }
+
+    #[test]
+    fn parse_field_aliases() {
+        let text = read_to_string("testcase/alias.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse(&mut s).expect("parse alias");
+        let fields = &ast.instructions[0].fields;
+        assert_eq!(fields[0].aliases, ["rd"]);
+        assert_eq!(fields[1].aliases, ["rs", "rs1"]);
+        assert!(fields[2].aliases.is_empty());
+    }
+
+    #[test]
+    fn parse_string_literal_escapes() {
+        assert_eq!(string_literal.parse("'add'").unwrap(), "add");
+        assert_eq!(string_literal.parse("''").unwrap(), "");
+        assert_eq!(
+            string_literal.parse(r"'.ascii \'x\' \\ \n'").unwrap(),
+            ".ascii 'x' \\ \n"
+        );
+        assert!(string_literal.parse(r"'\t'").is_err());
+        assert!(string_literal.parse("'add").is_err());
+    }
+
+    #[test]
+    fn parse_multiple_characteristics() {
+        for text in [
+            "instruction_width = 16;\nforce_storage = u64;\n",
+            "force_storage = u64;\ninstruction_width = 16;\n",
+        ] {
+            let parsed = match parse.parse(text) {
+                Err(e) => {
+                    panic!("{e}")
+                }
+                Ok(parsed) => parsed,
+            };
+            assert_eq!(parsed.characteristics.len(), 2);
+            assert_eq!(
+                parsed.instruction_width(),
+                Some(&ast::CharacteristicValue::NumericConstant(16))
+            );
+            assert_eq!(parsed.force_storage(), Some(64));
+        }
+    }
+
     #[test]
     fn parse_add() {
         let text = read_to_string("testcase/add.isf").unwrap();
@@ -553,43 +1495,108 @@ mod test {
                 parsed
             }
         };
+        assert_eq!(
+            parsed.doc,
+            "An instruction set with a single add instruction.\n\n\
+            Used to exercise parsing and code generation."
+        );
         assert_eq!(parsed.instructions.len(), 1);
         assert_eq!(parsed.instructions[0].doc, "Add values from two registers");
+        assert_eq!(
+            parsed.characteristic_comments[0],
+            ast::Comments {
+                leading: vec!["// more bits".to_owned()],
+                after: vec![
+                    "// some other comment".to_owned(),
+                    "// about muffins".to_owned(),
+                ],
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            parsed.instructions[0].comments,
+            ast::Comments {
+                leading: vec!["// darmok and jalad".to_owned(), "".to_owned()],
+                inner: vec!["// hello".to_owned()],
+                trailing: Some("// construct additional pylons".to_owned()),
+                after: vec![],
+            }
+        );
         assert_eq!(parsed.instructions[0].fields.len(), 4);
         assert_eq!(
             parsed.instructions[0].fields[0],
             ast::Field {
                 doc: "The destination register".to_owned(),
+                comments: ast::Comments::default(),
                 name: "dst".to_owned(),
                 ty: ast::FieldType::FixedWidth(5),
                 value: None,
+                is_signed: false,
+                radix: ast::Radix::Decimal,
+                pad: 0,
+                scale: 1,
+                aliases: Vec::new(),
+                bits: Vec::new(),
+                allowed: None,
             }
         );
         assert_eq!(
             parsed.instructions[0].fields[1],
             ast::Field {
                 doc: "The first source register".to_owned(),
+                comments: ast::Comments {
+                    leading: vec!["// comments can go here".to_owned()],
+                    inner: vec!["// or here".to_owned()],
+                    trailing: Some("// or here!".to_owned()),
+                    after: vec![],
+                },
                 name: "src1".to_owned(),
                 ty: ast::FieldType::FixedWidth(5),
                 value: None,
+                is_signed: false,
+                radix: ast::Radix::Decimal,
+                pad: 0,
+                scale: 1,
+                aliases: Vec::new(),
+                bits: Vec::new(),
+                allowed: None,
             }
         );
         assert_eq!(
             parsed.instructions[0].fields[2],
             ast::Field {
                 doc: "The second source register".to_owned(),
+                comments: ast::Comments::default(),
                 name: "src2".to_owned(),
                 ty: ast::FieldType::FixedWidth(5),
                 value: None,
+                is_signed: false,
+                radix: ast::Radix::Decimal,
+                pad: 0,
+                scale: 1,
+                aliases: Vec::new(),
+                bits: Vec::new(),
+                allowed: None,
             }
         );
         assert_eq!(
             parsed.instructions[0].fields[3],
             ast::Field {
                 doc: "Set a flag that sign extends the result".to_owned(),
+                comments: ast::Comments {
+                    trailing: Some("// alpha quadrant".to_owned()),
+                    ..Default::default()
+                },
                 name: "sign_extend".to_owned(),
                 ty: ast::FieldType::FixedWidth(1),
                 value: None,
+                is_signed: false,
+                radix: ast::Radix::Decimal,
+                pad: 0,
+                scale: 1,
+                aliases: Vec::new(),
+                bits: Vec::new(),
+                allowed: None,
             }
         );
         assert_eq!(
@@ -632,6 +1639,14 @@ mod test {
                       "register 0."
                     ].join("\n"),
                     example: "add r0 r4 r7".to_owned(),
+                    comments: ast::Comments {
+                        inner: vec!["// comments".to_owned()],
+                        trailing: Some("// 1701".to_owned()),
+                        after: vec![
+                            "// comments comments".to_owned(),
+                        ],
+                        ..Default::default()
+                    },
                 });
         assert_eq!(
             parsed.instructions[0].assembly.example[1],
@@ -642,6 +1657,7 @@ mod test {
                 ]
                 .join("\n"),
                 example: "add.sx r0 r4 r7".to_owned(),
+                comments: ast::Comments::default(),
             }
         );
         assert_eq!(parsed.instructions[0].machine.layout.len(), 8);
@@ -728,36 +1744,76 @@ mod test {
             parsed.instructions[0].fields[0],
             ast::Field {
                 doc: "The destination register".to_owned(),
+                comments: ast::Comments::default(),
                 name: "dst".to_owned(),
                 ty: ast::FieldType::Class("Register".to_owned()),
                 value: None,
+                is_signed: false,
+                radix: ast::Radix::Decimal,
+                pad: 0,
+                scale: 1,
+                aliases: Vec::new(),
+                bits: Vec::new(),
+                allowed: None,
             }
         );
         assert_eq!(
             parsed.instructions[0].fields[1],
             ast::Field {
                 doc: "The first source register".to_owned(),
+                comments: ast::Comments {
+                    leading: vec!["// comments can go here".to_owned()],
+                    inner: vec!["// or here".to_owned()],
+                    trailing: Some("// or here!".to_owned()),
+                    after: vec![],
+                },
                 name: "src1".to_owned(),
                 ty: ast::FieldType::Class("Register".to_owned()),
                 value: None,
+                is_signed: false,
+                radix: ast::Radix::Decimal,
+                pad: 0,
+                scale: 1,
+                aliases: Vec::new(),
+                bits: Vec::new(),
+                allowed: None,
             }
         );
         assert_eq!(
             parsed.instructions[0].fields[2],
             ast::Field {
                 doc: "The second source register".to_owned(),
+                comments: ast::Comments::default(),
                 name: "src2".to_owned(),
                 ty: ast::FieldType::Class("Register".to_owned()),
                 value: None,
+                is_signed: false,
+                radix: ast::Radix::Decimal,
+                pad: 0,
+                scale: 1,
+                aliases: Vec::new(),
+                bits: Vec::new(),
+                allowed: None,
             }
         );
         assert_eq!(
             parsed.instructions[0].fields[3],
             ast::Field {
                 doc: "Set a flag that sign extends the result".to_owned(),
+                comments: ast::Comments {
+                    trailing: Some("// alpha quadrant".to_owned()),
+                    ..Default::default()
+                },
                 name: "sign_extend".to_owned(),
                 ty: ast::FieldType::FixedWidth(1),
                 value: None,
+                is_signed: false,
+                radix: ast::Radix::Decimal,
+                pad: 0,
+                scale: 1,
+                aliases: Vec::new(),
+                bits: Vec::new(),
+                allowed: None,
             }
         );
         assert_eq!(
@@ -800,6 +1856,14 @@ mod test {
                       "register 0."
                     ].join("\n"),
                     example: "add r0 r4 r7".to_owned(),
+                    comments: ast::Comments {
+                        inner: vec!["// comments".to_owned()],
+                        trailing: Some("// 1701".to_owned()),
+                        after: vec![
+                            "// comments comments".to_owned(),
+                        ],
+                        ..Default::default()
+                    },
                 });
         assert_eq!(
             parsed.instructions[0].assembly.example[1],
@@ -810,6 +1874,7 @@ mod test {
                 ]
                 .join("\n"),
                 example: "add.sx r0 r4 r7".to_owned(),
+                comments: ast::Comments::default(),
             }
         );
         assert_eq!(parsed.instructions[0].machine.layout.len(), 8);
@@ -894,42 +1959,74 @@ mod test {
             "A base instruction for binary arithmetic operations"
         );
         assert_eq!(parsed.instructions[0].parameters.len(), 2);
-        assert_eq!(parsed.instructions[0].parameters[0], "name");
-        assert_eq!(parsed.instructions[0].parameters[1], "opcode");
+        assert_eq!(parsed.instructions[0].parameters[0].name, "name");
+        assert_eq!(parsed.instructions[0].parameters[1].name, "opcode");
         assert_eq!(
             parsed.instructions[0].fields[0],
             ast::Field {
                 doc: "The destination register".to_owned(),
+                comments: ast::Comments::default(),
                 name: "dst".to_owned(),
                 ty: ast::FieldType::FixedWidth(5),
                 value: None,
+                is_signed: false,
+                radix: ast::Radix::Decimal,
+                pad: 0,
+                scale: 1,
+                aliases: Vec::new(),
+                bits: Vec::new(),
+                allowed: None,
             }
         );
         assert_eq!(
             parsed.instructions[0].fields[1],
             ast::Field {
                 doc: "The first source register".to_owned(),
+                comments: ast::Comments::default(),
                 name: "src1".to_owned(),
                 ty: ast::FieldType::FixedWidth(5),
                 value: None,
+                is_signed: false,
+                radix: ast::Radix::Decimal,
+                pad: 0,
+                scale: 1,
+                aliases: Vec::new(),
+                bits: Vec::new(),
+                allowed: None,
             }
         );
         assert_eq!(
             parsed.instructions[0].fields[2],
             ast::Field {
                 doc: "The second source register".to_owned(),
+                comments: ast::Comments::default(),
                 name: "src2".to_owned(),
                 ty: ast::FieldType::FixedWidth(5),
                 value: None,
+                is_signed: false,
+                radix: ast::Radix::Decimal,
+                pad: 0,
+                scale: 1,
+                aliases: Vec::new(),
+                bits: Vec::new(),
+                allowed: None,
             }
         );
         assert_eq!(
             parsed.instructions[0].fields[3],
             ast::Field {
                 doc: "Set a flag that sign extends the result".to_owned(),
+                comments: ast::Comments::default(),
                 name: "sign_extend".to_owned(),
                 ty: ast::FieldType::FixedWidth(1),
                 value: None,
+                is_signed: false,
+                radix: ast::Radix::Decimal,
+                pad: 0,
+                scale: 1,
+                aliases: Vec::new(),
+                bits: Vec::new(),
+                allowed: None,
             }
         );
         assert_eq!(
@@ -1044,6 +2141,7 @@ mod test {
                       "register 0."
                     ].join("\n"),
                     example: "add r0 r4 r7".to_owned(),
+                    comments: ast::Comments::default(),
                 });
         assert_eq!(
             parsed.instructions[1].assembly.example[1],
@@ -1054,6 +2152,7 @@ mod test {
                 ]
                 .join("\n"),
                 example: "add.sx r0 r4 r7".to_owned(),
+                comments: ast::Comments::default(),
             }
         );
 
@@ -1082,6 +2181,7 @@ mod test {
                 ]
                 .join("\n"),
                 example: "sub r0 r4 r7".to_owned(),
+                comments: ast::Comments::default(),
             }
         );
         assert_eq!(
@@ -1093,6 +2193,7 @@ mod test {
                 ]
                 .join("\n"),
                 example: "sub.sx r0 r4 r7".to_owned(),
+                comments: ast::Comments::default(),
             }
         );
     }
@@ -1117,7 +2218,8 @@ mod test {
             MachineElement::FieldSlice {
                 name: "src".to_owned(),
                 begin: 0,
-                end: 6
+                end: 6,
+                negate: false,
             }
         );
         assert_eq!(
@@ -1125,9 +2227,18 @@ mod test {
             MachineElement::FieldSlice {
                 name: "src".to_owned(),
                 begin: 7,
-                end: 13
+                end: 13,
+                negate: false,
             }
         );
+        let docs = &parsed.instructions[0].machine.docs;
+        assert_eq!(docs.len(), 7);
+        assert_eq!(
+            docs[4],
+            "Low bits of the sources, kept apart from the high bits to match\n\
+            the register file ports."
+        );
+        assert!(docs[6].is_empty());
     }
 
     #[test]
@@ -1150,7 +2261,8 @@ mod test {
             MachineElement::FieldSlice {
                 name: "src".to_owned(),
                 begin: 0,
-                end: 7
+                end: 7,
+                negate: false,
             }
         );
         assert_eq!(
@@ -1158,7 +2270,39 @@ mod test {
             MachineElement::FieldSlice {
                 name: "src".to_owned(),
                 begin: 8,
-                end: 15
+                end: 15,
+                negate: false,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_negate_slice() {
+        let text = read_to_string("testcase/negate-slice.isf").unwrap();
+        let parsed = parse.parse(text.as_str()).unwrap();
+        let layout = &parsed.instructions[0].machine.layout;
+        assert_eq!(
+            layout[2],
+            MachineElement::FieldSlice {
+                name: "imm".to_owned(),
+                begin: 0,
+                end: 3,
+                negate: true,
+            }
+        );
+        assert_eq!(
+            layout[4],
+            MachineElement::FieldSlice {
+                name: "imm".to_owned(),
+                begin: 4,
+                end: 7,
+                negate: false,
+            }
+        );
+        assert_eq!(
+            layout[5],
+            MachineElement::FieldNegate {
+                name: "mask".to_owned(),
             }
         );
     }
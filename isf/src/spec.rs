@@ -3,26 +3,769 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 //! This module contains the ISF [`Spec`] structure and associated code. The
-//! [`form_spec`] function resolves an ISF [`ast::AST`] into a [`Spec`].
+//! [`form_spec`] function resolves an ISF [`ast::AST`] into a [`Spec`], or
+//! reports why it cannot as a [`SpecError`].
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use crate::ast::{self, Base, BaseParameter, Timing};
-use anyhow::{anyhow, Result};
+use crate::ast::{self, Base, BaseParameter, Endianness, Timing};
+type Result<T, E = SpecError> = std::result::Result<T, E>;
 
 /// Concrete ISF specification resolved from ISF AST.
 #[derive(Debug)]
 pub struct Spec {
+    /// Documentation for the instruction set as a whole.
+    pub doc: String,
     pub instruction_width: usize,
+    /// Storage type width overriding the one derived from
+    /// `instruction_width`.
+    pub force_storage: Option<usize>,
+    /// Byte order of instructions in machine form.
+    pub endianness: Endianness,
+    /// Name identifying the instruction set, if declared.
+    pub name: Option<String>,
+    /// Version of the instruction set, if declared.
+    pub version: Option<String>,
     pub instructions: Vec<Instruction>,
     pub classes: HashMap<String, Class>,
+    /// Problems found in the spec that do not prevent code generation.
+    pub warnings: Vec<String>,
+}
+
+impl Spec {
+    /// Instructions keyed by their primary opcode, the value of the first
+    /// constant layout element with a value. Fails if an instruction has no
+    /// such constant or if two instructions share an opcode.
+    pub fn instructions_by_opcode(
+        &self,
+    ) -> Result<BTreeMap<u64, &Instruction>> {
+        let mut result = BTreeMap::new();
+        for instr in &self.instructions {
+            let opcode = instr
+                .machine
+                .layout
+                .iter()
+                .find_map(|me| match me {
+                    MachineElement::Constant {
+                        value: Some(value), ..
+                    } => Some(*value),
+                    _ => None,
+                })
+                .ok_or_else(|| SpecError::NoOpcode {
+                    instruction: instr.name.clone(),
+                })?;
+            if let Some(other) = result.insert(opcode, instr) {
+                return Err(SpecError::SharedOpcode {
+                    first: other.name.clone(),
+                    second: instr.name.clone(),
+                    opcode,
+                });
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// A problem that prevents forming a [`Spec`] from an ISF AST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecError {
+    /// A characteristic is declared more than once.
+    DuplicateCharacteristic {
+        characteristic: String,
+    },
+    /// The `instruction_width` characteristic is missing.
+    MissingInstructionWidth,
+    /// The `instruction_width` characteristic names an undeclared constant.
+    UndefinedConstant {
+        name: String,
+    },
+    /// The `instruction_width` characteristic names a constant declared
+    /// after it.
+    ConstantUsedBeforeDeclaration {
+        name: String,
+    },
+    /// An instruction, or the instruction set as a whole when `instruction`
+    /// is `None`, is wider than 128 bits.
+    WidthOverflow {
+        instruction: Option<String>,
+    },
+    /// Forced storage is not the width of an unsigned integer type.
+    InvalidStorage {
+        storage: usize,
+    },
+    /// Forced storage is narrower than an instruction, or than the
+    /// instruction set as a whole when `instruction` is `None`.
+    StorageOverflow {
+        storage: usize,
+        instruction: Option<String>,
+        width: usize,
+    },
+    DuplicateInstruction {
+        name: String,
+    },
+    /// An instruction has the name of a type the generated code declares.
+    ReservedInstructionName {
+        name: String,
+    },
+    /// A class is not between 2 and 128 bits wide.
+    ClassWidth {
+        class: String,
+    },
+    DuplicateClassValue {
+        class: String,
+        value: String,
+    },
+    /// A named class value does not fit in the width of its class.
+    ClassValueOverflow {
+        class: String,
+        value: String,
+        number: u64,
+        width: usize,
+    },
+    /// Two named class values have the same number.
+    ClassValueCollision {
+        class: String,
+        first: String,
+        second: String,
+        number: u64,
+    },
+    /// The named values of a class do not cover every value of its width.
+    IncompleteClass {
+        class: String,
+        count: usize,
+        width: usize,
+    },
+    /// Following base instructions from `chain[0]` leads back to an
+    /// instruction already in the chain.
+    BaseCycle {
+        instruction: String,
+        chain: Vec<String>,
+    },
+    UnknownBase {
+        instruction: String,
+        base: String,
+    },
+    /// A base parameter without a default follows one with a default.
+    ParameterDefaultOrder {
+        base: String,
+        parameter: String,
+    },
+    /// A base is given fewer than `min` or more than `max` parameters.
+    ParameterCount {
+        instruction: String,
+        base: String,
+        min: usize,
+        max: usize,
+        given: usize,
+    },
+    /// A parameter passed on to a base is not a parameter of the
+    /// instruction passing it.
+    UnresolvedBaseParameter {
+        instruction: String,
+        base: String,
+        parameter: String,
+    },
+    /// A field, constant or assembly expansion refers to a parameter no
+    /// value was given for.
+    UnresolvedParameter {
+        instruction: String,
+        element: String,
+        parameter: String,
+    },
+    /// A field value parameter was given text.
+    NonNumericField {
+        instruction: String,
+        field: String,
+    },
+    /// A layout constant parameter was given text.
+    NonNumericConstant {
+        instruction: String,
+        parameter: String,
+    },
+    EmptyTimingRange {
+        instruction: String,
+        min: usize,
+        max: usize,
+    },
+    UnknownClass {
+        instruction: String,
+        field: String,
+        class: String,
+    },
+    /// A field combines properties that cannot go together.
+    InvalidField {
+        instruction: String,
+        field: String,
+        problem: FieldProblem,
+    },
+    /// A signed layout constant does not fit in its width.
+    ConstantOverflow {
+        instruction: String,
+        constant: String,
+        value: i64,
+        width: usize,
+    },
+    MultipleFills {
+        instruction: String,
+    },
+    /// A machine layout declares bit positions and has a fill element.
+    PositionedFill {
+        instruction: String,
+    },
+    /// A machine layout declares bit positions for some elements but not
+    /// for `element`.
+    UnpositionedElement {
+        instruction: String,
+        element: String,
+    },
+    /// The layout is `width` bits wide without its fill, more than the
+    /// instruction.
+    FillOverflow {
+        instruction: String,
+        width: usize,
+        instruction_width: usize,
+    },
+    /// Two fields share a name, alias or bit name.
+    DuplicateField {
+        instruction: String,
+        field: String,
+    },
+    UndefinedAssemblyField {
+        instruction: String,
+        field: String,
+    },
+    UndefinedFlagField {
+        instruction: String,
+        flag: String,
+        field: String,
+    },
+    UndefinedLayoutField {
+        instruction: String,
+        field: String,
+    },
+    /// An instruction has fields but no machine layout to place them in.
+    MissingLayout {
+        instruction: String,
+    },
+    /// An optional flag sets a field that is not a 1-bit integer.
+    FlagFieldWidth {
+        instruction: String,
+        flag: String,
+        field: String,
+    },
+    /// A conditional assembly group depends on a field that is not a 1-bit
+    /// integer.
+    ConditionFieldWidth {
+        instruction: String,
+        field: String,
+    },
+    /// A slice reaches past the end of its field.
+    SliceOutOfRange {
+        instruction: String,
+        field: String,
+        begin: usize,
+        end: usize,
+        width: usize,
+    },
+    /// The slices of a field cover some of its bits more than once.
+    SliceOverlap {
+        instruction: String,
+        field: String,
+        bits: Vec<usize>,
+    },
+    /// The slices of a field leave some of its bits uncovered.
+    SliceGap {
+        instruction: String,
+        field: String,
+        bits: Vec<usize>,
+    },
+    /// The machine layout is `width` bits wide rather than `expected`.
+    LayoutWidth {
+        instruction: String,
+        width: usize,
+        expected: usize,
+    },
+    BitBeyondWidth {
+        instruction: String,
+        element: String,
+        bit: usize,
+        width: usize,
+    },
+    BitConflict {
+        instruction: String,
+        bit: usize,
+        first: String,
+        second: String,
+    },
+    UnclaimedBit {
+        instruction: String,
+        bit: usize,
+    },
+    /// An instruction has no constant layout element with a value.
+    NoOpcode {
+        instruction: String,
+    },
+    SharedOpcode {
+        first: String,
+        second: String,
+        opcode: u64,
+    },
+}
+
+/// Why a field is invalid, see [`SpecError::InvalidField`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldProblem {
+    SignedClass,
+    /// A signed field narrower than 2 bits.
+    NarrowSigned,
+    /// A radix on a signed or class field.
+    Radix,
+    /// Padding on a signed or class field.
+    Pad,
+    /// Bit names on a field other than a multi-bit unsigned integer.
+    BitNames,
+    /// A scale on a field other than a multi-bit unsigned integer.
+    Scale,
+    /// A scale of zero.
+    ZeroScale,
+    /// More bit names than the field has bits.
+    TooManyBitNames {
+        count: usize,
+        width: usize,
+    },
+    /// Allowed values on a signed or class field.
+    Allowed,
+    /// An allowed value that does not fit in the field.
+    AllowedOverflow {
+        value: u64,
+        width: usize,
+    },
+    /// A separate sign bit for a two's complement field.
+    SignedSignBit,
+    /// A separate sign bit for a field with a radix.
+    RadixSignBit,
+    /// A separate sign bit for a padded field.
+    PadSignBit,
+    /// A separate sign bit for a field with allowed values.
+    AllowedSignBit,
+    /// A separate sign bit for a scaled field.
+    ScaleSignBit,
+}
+
+impl std::fmt::Display for SpecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateCharacteristic { characteristic } => write!(
+                f,
+                "characteristic declared more than once: {characteristic}"
+            ),
+            Self::MissingInstructionWidth => {
+                write!(f, "instruction width characteristic required")
+            }
+            Self::UndefinedConstant { name } => {
+                write!(f, "instruction width: constant {name} not found")
+            }
+            Self::ConstantUsedBeforeDeclaration { name } => write!(
+                f,
+                "instruction width: constant {name} used before its \
+                declaration"
+            ),
+            Self::WidthOverflow { instruction: None } => {
+                write!(f, "instruction width must be less than 128 bits")
+            }
+            Self::WidthOverflow {
+                instruction: Some(instruction),
+            } => write!(
+                f,
+                "instruction {instruction}: width must be less than 128 bits"
+            ),
+            Self::InvalidStorage { .. } => write!(
+                f,
+                "forced storage must be one of u8, u16, u32, u64 or u128"
+            ),
+            Self::StorageOverflow {
+                storage,
+                instruction: None,
+                width,
+            } => write!(
+                f,
+                "forced storage u{storage} is narrower than the instruction \
+                width of {width} bits"
+            ),
+            Self::StorageOverflow {
+                storage,
+                instruction: Some(instruction),
+                width,
+            } => write!(
+                f,
+                "forced storage u{storage} is narrower than the width of \
+                instruction {instruction} of {width} bits"
+            ),
+            Self::DuplicateInstruction { name } => {
+                write!(f, "duplicate instruction {name}")
+            }
+            Self::ReservedInstructionName { name } => write!(
+                f,
+                "instruction {name}: name is reserved for generated code"
+            ),
+            Self::ClassWidth { class } => {
+                write!(f, "class {class}: width must be between 2 and 128 bits")
+            }
+            Self::DuplicateClassValue { class, value } => write!(
+                f,
+                "class {class}: value {value} declared more than once"
+            ),
+            Self::ClassValueOverflow {
+                class,
+                value,
+                number,
+                width,
+            } => write!(
+                f,
+                "class {class}: value {value} = {number} does not fit in \
+                {width} bits"
+            ),
+            Self::ClassValueCollision {
+                class,
+                first,
+                second,
+                number,
+            } => write!(
+                f,
+                "class {class}: {first} and {second} have the same value \
+                {number}"
+            ),
+            Self::IncompleteClass {
+                class,
+                count,
+                width,
+            } => write!(
+                f,
+                "class {class}: {count} values do not cover all {width}-bit \
+                values"
+            ),
+            Self::BaseCycle { instruction, chain } => write!(
+                f,
+                "{instruction}: base instruction cycle: {}",
+                chain.join(" -> ")
+            ),
+            Self::UnknownBase { instruction, base } => {
+                write!(f, "{instruction}: base instruction {base} not found")
+            }
+            Self::ParameterDefaultOrder { base, parameter } => write!(
+                f,
+                "base {base}: parameter {parameter} without a default \
+                follows a parameter with a default"
+            ),
+            Self::ParameterCount {
+                instruction,
+                base,
+                min,
+                max,
+                given,
+            } => {
+                write!(f, "{instruction}: base {base} expects {min}")?;
+                if min != max {
+                    write!(f, " to {max}")?;
+                }
+                write!(f, " parameters, got {given}")
+            }
+            Self::UnresolvedBaseParameter {
+                instruction,
+                base,
+                parameter,
+            } => write!(
+                f,
+                "{instruction}: base instruction {base}: unresolved generic \
+                parameter {parameter}"
+            ),
+            Self::UnresolvedParameter {
+                instruction,
+                element,
+                parameter,
+            } => write!(
+                f,
+                "{instruction}: field {element}: unresolved generic \
+                parameter {parameter}"
+            ),
+            Self::NonNumericField { instruction, field } => write!(
+                f,
+                "{instruction}: field {field}: fields can only be assigned \
+                numeric values"
+            ),
+            Self::NonNumericConstant {
+                instruction,
+                parameter,
+            } => write!(
+                f,
+                "{instruction}: machine_layout {parameter}: layout positions \
+                can only be assigned numeric values"
+            ),
+            Self::EmptyTimingRange {
+                instruction,
+                min,
+                max,
+            } => write!(
+                f,
+                "instruction {instruction}: multi timing range {min}..{max} \
+                is empty"
+            ),
+            Self::UnknownClass {
+                instruction,
+                field,
+                class,
+            } => {
+                write!(
+                    f,
+                    "{instruction}: field {field} class {class} not found"
+                )
+            }
+            Self::InvalidField {
+                instruction,
+                field,
+                problem,
+            } => match problem {
+                FieldProblem::SignedClass => write!(
+                    f,
+                    "{instruction}: class field {field} cannot be signed"
+                ),
+                FieldProblem::NarrowSigned => write!(
+                    f,
+                    "{instruction}: signed field {field} must be at least 2 \
+                    bits wide"
+                ),
+                FieldProblem::Radix => write!(
+                    f,
+                    "{instruction}: only unsigned integer field {field} may \
+                    have a radix"
+                ),
+                FieldProblem::Pad => write!(
+                    f,
+                    "{instruction}: only unsigned integer field {field} may \
+                    be padded"
+                ),
+                FieldProblem::BitNames => write!(
+                    f,
+                    "{instruction}: only unsigned integer field {field} wider \
+                    than one bit may name its bits"
+                ),
+                FieldProblem::Scale => write!(
+                    f,
+                    "{instruction}: only unsigned integer field {field} wider \
+                    than one bit may be scaled"
+                ),
+                FieldProblem::ZeroScale => {
+                    write!(f, "{instruction}: field {field} has a scale of 0")
+                }
+                FieldProblem::TooManyBitNames { count, width } => write!(
+                    f,
+                    "{instruction}: {count} bit names given for the \
+                    {width}-bit field {field}"
+                ),
+                FieldProblem::Allowed => write!(
+                    f,
+                    "{instruction}: only unsigned integer field {field} may \
+                    have allowed values"
+                ),
+                FieldProblem::AllowedOverflow { value, width } => write!(
+                    f,
+                    "{instruction}: allowed value {value} does not fit in the \
+                    {width}-bit field {field}"
+                ),
+                FieldProblem::SignedSignBit => write!(
+                    f,
+                    "instruction {instruction}: two's complement field \
+                    {field} cannot have a sign bit"
+                ),
+                FieldProblem::RadixSignBit => write!(
+                    f,
+                    "instruction {instruction}: sign-magnitude field {field} \
+                    cannot have a radix"
+                ),
+                FieldProblem::PadSignBit => write!(
+                    f,
+                    "instruction {instruction}: sign-magnitude field {field} \
+                    cannot be padded"
+                ),
+                FieldProblem::AllowedSignBit => write!(
+                    f,
+                    "instruction {instruction}: sign-magnitude field {field} \
+                    cannot have allowed values"
+                ),
+                FieldProblem::ScaleSignBit => write!(
+                    f,
+                    "instruction {instruction}: sign-magnitude field {field} \
+                    cannot be scaled"
+                ),
+            },
+            Self::ConstantOverflow {
+                instruction,
+                constant,
+                value,
+                width,
+            } => write!(
+                f,
+                "{instruction}: value {value} does not fit in the {width}-bit \
+                constant {constant}"
+            ),
+            Self::MultipleFills { instruction } => write!(
+                f,
+                "instruction {instruction}: more than one fill in machine \
+                layout"
+            ),
+            Self::PositionedFill { instruction } => write!(
+                f,
+                "instruction {instruction}: machine layout with bit positions \
+                cannot have a fill"
+            ),
+            Self::UnpositionedElement {
+                instruction,
+                element,
+            } => write!(
+                f,
+                "instruction {instruction}: machine layout element {element} \
+                has no bit position"
+            ),
+            Self::FillOverflow {
+                instruction,
+                width,
+                instruction_width,
+            } => write!(
+                f,
+                "instruction {instruction}: machine layout is {width} bits \
+                wide before filling, more than the instruction width of \
+                {instruction_width}"
+            ),
+            Self::DuplicateField { instruction, field } => {
+                write!(f, "instruction {instruction}: duplicate field {field}")
+            }
+            Self::UndefinedAssemblyField { instruction, field } => write!(
+                f,
+                "instruction {instruction}: assembly refers to undefined \
+                field {field}"
+            ),
+            Self::UndefinedFlagField {
+                instruction,
+                flag,
+                field,
+            } => write!(
+                f,
+                "instruction {instruction}: flag '{flag}' sets undefined \
+                field {field}"
+            ),
+            Self::UndefinedLayoutField { instruction, field } => write!(
+                f,
+                "instruction {instruction}: machine layout refers to \
+                undefined field {field}"
+            ),
+            Self::MissingLayout { instruction } => write!(
+                f,
+                "instruction {instruction}: has fields but no machine layout \
+                to place them in"
+            ),
+            Self::FlagFieldWidth {
+                instruction,
+                flag,
+                field,
+            } => write!(
+                f,
+                "instruction {instruction}: flag '{flag}' sets field {field}, \
+                which is not a 1-bit integer field"
+            ),
+            Self::ConditionFieldWidth { instruction, field } => write!(
+                f,
+                "instruction {instruction}: assembly group depends on field \
+                {field}, which is not a 1-bit integer field"
+            ),
+            Self::SliceOutOfRange {
+                instruction,
+                field,
+                begin,
+                end,
+                width,
+            } => write!(
+                f,
+                "instruction {instruction}: slice {field}[{begin}:{end}] is \
+                outside of the {width}-bit field"
+            ),
+            Self::SliceOverlap {
+                instruction,
+                field,
+                bits,
+            } => write!(
+                f,
+                "instruction {instruction}: field {field}: bits {} are \
+                covered by more than one slice",
+                bit_list(bits)
+            ),
+            Self::SliceGap {
+                instruction,
+                field,
+                bits,
+            } => write!(
+                f,
+                "instruction {instruction}: field {field}: bits {} are not \
+                covered by any slice",
+                bit_list(bits)
+            ),
+            Self::LayoutWidth {
+                instruction,
+                width,
+                expected,
+            } => write!(
+                f,
+                "instruction {instruction}: machine layout is {width} bits \
+                wide, expected {expected}"
+            ),
+            Self::BitBeyondWidth {
+                instruction,
+                element,
+                bit,
+                width,
+            } => write!(
+                f,
+                "instruction {instruction}: {element} claims bit {bit} beyond \
+                the instruction width of {width}"
+            ),
+            Self::BitConflict {
+                instruction,
+                bit,
+                first,
+                second,
+            } => write!(
+                f,
+                "instruction {instruction}: bit {bit} is claimed by both \
+                {first} and {second}"
+            ),
+            Self::UnclaimedBit { instruction, bit } => write!(
+                f,
+                "instruction {instruction}: bit {bit} is not claimed by any \
+                element"
+            ),
+            Self::NoOpcode { instruction } => {
+                write!(f, "instruction {instruction}: no opcode")
+            }
+            Self::SharedOpcode {
+                first,
+                second,
+                opcode,
+            } => write!(
+                f,
+                "instructions {first} and {second} share opcode {opcode:#x}"
+            ),
+        }
+    }
 }
 
+impl std::error::Error for SpecError {}
+
 #[derive(Debug, Clone)]
 pub struct Class {
     pub doc: String,
     pub name: String,
     pub width: usize,
+    /// Named values. When present they cover every value of the class.
+    pub values: Vec<ast::ClassValue>,
 }
 
 /// Concrete instruction. Base instruction elements fully incorporated.
@@ -30,16 +773,475 @@ pub struct Class {
 pub struct Instruction {
     pub doc: String,
     pub name: String,
+    /// Deprecation note, if the instruction is deprecated.
+    pub deprecated: Option<String>,
+    /// Width in bits, the spec's `instruction_width` unless overridden.
+    pub width: usize,
     pub timing: Timing,
     pub fields: Vec<Field>,
     pub assembly: Assembly,
     pub machine: Machine,
 }
 
+/// Type-erased view of a decoded instruction, suitable for consumers such as
+/// simulators that handle instructions uniformly.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DecodedView {
+    pub name: String,
+    /// Field names and values in declaration order. Signed fields are given
+    /// as the two's complement bits of their width, plus the sign bit for
+    /// sign-magnitude fields.
+    pub operands: Vec<(String, u128)>,
+    pub timing: Timing,
+}
+
 impl Instruction {
+    /// Check that the widths of the machine layout elements sum to the
+    /// instruction width, that every bit is claimed by exactly one element
+    /// and that every sliced field is covered exactly by its slices.
+    pub fn validate_layout(&self, instruction_width: usize) -> Result<()> {
+        let ranges = self.layout_ranges()?;
+        let total = ranges.iter().map(|(_, _, w)| w).sum::<usize>();
+        if total != instruction_width {
+            return Err(SpecError::LayoutWidth {
+                instruction: self.name.clone(),
+                width: total,
+                expected: instruction_width,
+            });
+        }
+        check_occupancy(&self.name, &ranges, instruction_width)?;
+        self.check_slices()
+    }
+
+    /// Put a machine layout whose elements declare their bit positions in
+    /// position order, reserving the bits no element claims as zeros.
+    fn resolve_positions(&mut self) -> Result<()> {
+        let positions = std::mem::take(&mut self.machine.positions);
+        if positions.iter().all(Option::is_none) {
+            return Ok(());
+        }
+        if self.machine.fill.is_some() {
+            return Err(SpecError::PositionedFill {
+                instruction: self.name.clone(),
+            });
+        }
+        if let Some(i) = positions.iter().position(Option::is_none) {
+            return Err(SpecError::UnpositionedElement {
+                instruction: self.name.clone(),
+                element: self.machine.layout[i].name(),
+            });
+        }
+        let ranges = self.layout_ranges()?;
+        let layout = std::mem::take(&mut self.machine.layout);
+        let mut docs = std::mem::take(&mut self.machine.docs);
+        docs.resize(layout.len(), String::default());
+        let mut elements: Vec<_> = positions
+            .into_iter()
+            .flatten()
+            .zip(ranges)
+            .zip(layout.into_iter().zip(docs))
+            .collect();
+        elements.sort_by_key(|((position, _), _)| *position);
+
+        let reserved = |width| MachineElement::Constant {
+            name: "_".to_owned(),
+            width,
+            value: None,
+        };
+        let mut next = 0;
+        let mut previous = String::default();
+        for ((position, (name, _, width)), (element, doc)) in elements {
+            if position < next {
+                return Err(SpecError::BitConflict {
+                    instruction: self.name.clone(),
+                    bit: position,
+                    first: previous,
+                    second: name,
+                });
+            }
+            if position > next {
+                self.machine.layout.push(reserved(position - next));
+                self.machine.docs.push(String::default());
+            }
+            self.machine.layout.push(element);
+            self.machine.docs.push(doc);
+            next = position + width;
+            previous = name;
+        }
+        if next < self.width {
+            self.machine.layout.push(reserved(self.width - next));
+            self.machine.docs.push(String::default());
+        }
+        Ok(())
+    }
+
+    /// Give the fill element of the machine layout, if any, the width that
+    /// makes the layout span the instruction. A fill with nothing left to
+    /// cover is dropped.
+    fn resolve_fill(&mut self) -> Result<()> {
+        let Some(index) = self.machine.fill.take() else {
+            return Ok(());
+        };
+        let rest = self
+            .layout_ranges()?
+            .iter()
+            .map(|(_, _, w)| w)
+            .sum::<usize>();
+        let Some(fill) = self.width.checked_sub(rest) else {
+            return Err(SpecError::FillOverflow {
+                instruction: self.name.clone(),
+                width: rest,
+                instruction_width: self.width,
+            });
+        };
+        if fill == 0 {
+            self.machine.layout.remove(index);
+            if index < self.machine.docs.len() {
+                self.machine.docs.remove(index);
+            }
+        } else if let MachineElement::Constant { width, .. } =
+            &mut self.machine.layout[index]
+        {
+            *width = fill;
+        }
+        Ok(())
+    }
+
+    /// Check that no two fields, including those inherited from base
+    /// instructions, share a name or alias.
+    fn check_field_names(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        for f in &self.fields {
+            for name in
+                std::iter::once(&f.name).chain(&f.aliases).chain(&f.bits)
+            {
+                if !names.insert(name.as_str()) {
+                    return Err(SpecError::DuplicateField {
+                        instruction: self.name.clone(),
+                        field: name.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the fields referenced by assembly syntax exist and that
+    /// each optional flag sets, and each conditional group depends on, a
+    /// 1-bit integer field. Generated code treats flag fields as booleans.
+    fn check_assembly_fields(&self) -> Result<()> {
+        fn check(
+            instr: &Instruction,
+            syntax: &[AssemblyElement],
+        ) -> Result<()> {
+            for ae in syntax {
+                match ae {
+                    AssemblyElement::Field { name }
+                        if instr.get_field(name).is_none() =>
+                    {
+                        return Err(SpecError::UndefinedAssemblyField {
+                            instruction: instr.name.clone(),
+                            field: name.clone(),
+                        });
+                    }
+                    AssemblyElement::OptionalFlag { name, field } => {
+                        let f = instr.get_field(field).ok_or_else(|| {
+                            SpecError::UndefinedFlagField {
+                                instruction: instr.name.clone(),
+                                flag: name.clone(),
+                                field: field.clone(),
+                            }
+                        })?;
+                        if f.width != 1 || f.class.is_some() {
+                            return Err(SpecError::FlagFieldWidth {
+                                instruction: instr.name.clone(),
+                                flag: name.clone(),
+                                field: field.clone(),
+                            });
+                        }
+                    }
+                    AssemblyElement::Optional { elements } => {
+                        check(instr, elements)?;
+                    }
+                    AssemblyElement::Conditional { field, elements } => {
+                        let f = instr.get_field(field).ok_or_else(|| {
+                            SpecError::UndefinedAssemblyField {
+                                instruction: instr.name.clone(),
+                                field: field.clone(),
+                            }
+                        })?;
+                        if f.width != 1 || f.class.is_some() {
+                            return Err(SpecError::ConditionFieldWidth {
+                                instruction: instr.name.clone(),
+                                field: field.clone(),
+                            });
+                        }
+                        check(instr, elements)?;
+                    }
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+        self.assembly.forms().try_for_each(|form| check(self, form))
+    }
+
+    /// Check that every field referenced by the machine layout exists, and
+    /// that an instruction with fields has a layout to place them in.
+    fn check_layout_fields(&self) -> Result<()> {
+        if self.machine.layout.is_empty() && !self.fields.is_empty() {
+            return Err(SpecError::MissingLayout {
+                instruction: self.name.clone(),
+            });
+        }
+        for me in &self.machine.layout {
+            match me {
+                MachineElement::Field { name }
+                | MachineElement::FieldSlice { name, .. }
+                | MachineElement::FieldNegate { name }
+                | MachineElement::FieldSign { name }
+                | MachineElement::OptionalFieldPresentTest { name }
+                | MachineElement::OptionalFieldAbsentTest { name }
+                    if self.get_field(name).is_none() =>
+                {
+                    return Err(SpecError::UndefinedLayoutField {
+                        instruction: self.name.clone(),
+                        field: name.clone(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Warnings for fields whose bits are not placed anywhere in the
+    /// machine layout.
+    pub fn lint_layout(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .filter(|f| {
+                !self.machine.layout.iter().any(|me| match me {
+                    MachineElement::Field { name }
+                    | MachineElement::FieldSlice { name, .. }
+                    | MachineElement::FieldNegate { name } => *name == f.name,
+                    _ => false,
+                })
+            })
+            .map(|f| {
+                format!(
+                    "instruction {}: field {} does not appear in the machine \
+                    layout",
+                    self.name, f.name
+                )
+            })
+            .collect()
+    }
+
+    /// Replace field aliases in assembly syntax with the names of the fields
+    /// they refer to.
+    fn resolve_aliases(&mut self) {
+        let names: HashMap<String, String> = self
+            .fields
+            .iter()
+            .flat_map(|f| f.aliases.iter().map(|a| (a.clone(), f.name.clone())))
+            .collect();
+        canonicalize_fields(&mut self.assembly.syntax, &names);
+        for form in &mut self.assembly.alternatives {
+            canonicalize_fields(form, &names);
+        }
+    }
+
+    /// Warnings for assembly fields that directly follow another numeric
+    /// element without a separating space, such as `[a]b`.
+    /// The parser cannot tell where one number ends and the next begins.
+    pub fn lint_assembly(&self) -> Vec<String> {
+        let numeric = |e: &AssemblyElement| {
+            matches!(
+                e,
+                AssemblyElement::Field { .. }
+                    | AssemblyElement::Optional { .. }
+                    | AssemblyElement::Conditional { .. }
+                    | AssemblyElement::NumberLiteral { .. }
+            )
+        };
+        let mut warnings: Vec<String> = self
+            .assembly
+            .forms()
+            .flat_map(|form| form.windows(2))
+            .filter_map(|pair| match pair {
+                [prev, AssemblyElement::Field { name }] if numeric(prev) => {
+                    Some(format!(
+                        "instruction {}: assembly field {name} directly \
+                        follows a numeric element and may not parse; \
+                        separate them with a space or string literal",
+                        self.name
+                    ))
+                }
+                _ => None,
+            })
+            .collect();
+        warnings.dedup();
+        warnings
+    }
+
+    /// Check that the slices of each sliced field, together with any whole
+    /// occurrences of it, cover every bit of the field exactly once.
+    fn check_slices(&self) -> Result<()> {
+        for f in &self.fields {
+            let mut sliced = false;
+            let mut coverage = vec![0usize; f.width];
+            for me in &self.machine.layout {
+                let bits = match me {
+                    MachineElement::FieldSlice {
+                        name, begin, end, ..
+                    } if *name == f.name => {
+                        sliced = true;
+                        *begin..*end + 1
+                    }
+                    MachineElement::Field { name }
+                    | MachineElement::FieldNegate { name }
+                        if *name == f.name =>
+                    {
+                        0..f.width
+                    }
+                    _ => continue,
+                };
+                for bit in bits {
+                    coverage[bit] += 1;
+                }
+            }
+            if !sliced {
+                continue;
+            }
+            let bits = |pred: fn(usize) -> bool| {
+                (0..f.width)
+                    .filter(|b| pred(coverage[*b]))
+                    .collect::<Vec<_>>()
+            };
+            let overlapping = bits(|n| n > 1);
+            if !overlapping.is_empty() {
+                return Err(SpecError::SliceOverlap {
+                    instruction: self.name.clone(),
+                    field: f.name.clone(),
+                    bits: overlapping,
+                });
+            }
+            let uncovered = bits(|n| n == 0);
+            if !uncovered.is_empty() {
+                return Err(SpecError::SliceGap {
+                    instruction: self.name.clone(),
+                    field: f.name.clone(),
+                    bits: uncovered,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Name, bit offset and bit width of each machine layout element.
+    pub(crate) fn layout_ranges(&self) -> Result<Vec<(String, usize, usize)>> {
+        let mut ranges = Vec::new();
+        let mut offset = 0;
+        for me in &self.machine.layout {
+            let width = match me {
+                MachineElement::Field { name }
+                | MachineElement::FieldNegate { name } => {
+                    self.layout_field(name)?.width
+                }
+                MachineElement::FieldSlice {
+                    name, begin, end, ..
+                } => {
+                    let field_width = self.layout_field(name)?.width;
+                    if *end >= field_width {
+                        return Err(SpecError::SliceOutOfRange {
+                            instruction: self.name.clone(),
+                            field: name.clone(),
+                            begin: *begin,
+                            end: *end,
+                            width: field_width,
+                        });
+                    }
+                    end - begin + 1
+                }
+                MachineElement::FieldSign { name }
+                    if self.get_field(name).is_some_and(|f| f.is_signed) =>
+                {
+                    return Err(
+                        self.invalid_field(name, FieldProblem::SignedSignBit)
+                    );
+                }
+                MachineElement::FieldSign { name }
+                    if self
+                        .get_field(name)
+                        .is_some_and(|f| f.radix != ast::Radix::Decimal) =>
+                {
+                    return Err(
+                        self.invalid_field(name, FieldProblem::RadixSignBit)
+                    );
+                }
+                MachineElement::FieldSign { name }
+                    if self.get_field(name).is_some_and(|f| f.pad != 0) =>
+                {
+                    return Err(
+                        self.invalid_field(name, FieldProblem::PadSignBit)
+                    );
+                }
+                MachineElement::FieldSign { name }
+                    if self.get_field(name).is_some_and(|f| f.scale != 1) =>
+                {
+                    return Err(
+                        self.invalid_field(name, FieldProblem::ScaleSignBit)
+                    );
+                }
+                MachineElement::FieldSign { name }
+                    if self
+                        .get_field(name)
+                        .is_some_and(|f| f.allowed.is_some()) =>
+                {
+                    return Err(
+                        self.invalid_field(name, FieldProblem::AllowedSignBit)
+                    );
+                }
+                MachineElement::FieldSign { .. }
+                | MachineElement::OptionalFieldPresentTest { .. }
+                | MachineElement::OptionalFieldAbsentTest { .. } => 1,
+                MachineElement::Constant { width, .. } => *width,
+            };
+            ranges.push((me.name(), offset, width));
+            offset += width;
+        }
+        Ok(ranges)
+    }
+
     pub(crate) fn get_field<'a>(&'a self, name: &str) -> Option<&'a Field> {
         self.fields.iter().find(|f| f.name == name)
     }
+
+    /// The field named by a machine layout element.
+    fn layout_field(&self, name: &str) -> Result<&Field> {
+        self.get_field(name)
+            .ok_or_else(|| SpecError::UndefinedLayoutField {
+                instruction: self.name.clone(),
+                field: name.to_owned(),
+            })
+    }
+
+    fn invalid_field(&self, field: &str, problem: FieldProblem) -> SpecError {
+        SpecError::InvalidField {
+            instruction: self.name.clone(),
+            field: field.to_owned(),
+            problem,
+        }
+    }
+
+    /// Returns true if the named field has a separate sign bit in the
+    /// machine layout.
+    pub(crate) fn is_sign_magnitude(&self, name: &str) -> bool {
+        self.machine.layout.iter().any(
+            |x| matches!(x, MachineElement::FieldSign { name: n } if n == name),
+        )
+    }
     fn resolve(
         instr: &ast::Instruction,
         ast: &ast::Ast,
@@ -48,41 +1250,104 @@ impl Instruction {
         let mut result = Self {
             doc: instr.doc.clone(),
             name: instr.name.clone(),
+            deprecated: instr.deprecated.clone(),
             ..Default::default()
         };
 
+        let mut chain = vec![instr.name.clone()];
+        result.apply(instr, &HashMap::new(), ast, classes, &mut chain)?;
+        result.resolve_aliases();
+
+        Ok(result)
+    }
+
+    /// Apply an instruction and, before it, its chain of base instructions.
+    /// The parameters of each base are resolved against `pmap`, the
+    /// parameters of the instruction naming it, so a parameter can be
+    /// forwarded down the chain. `chain` holds the names of the instructions
+    /// being applied and is used to detect cycles.
+    fn apply(
+        &mut self,
+        instr: &ast::Instruction,
+        pmap: &HashMap<String, ast::BaseParameter>,
+        ast: &ast::Ast,
+        classes: &HashMap<String, Class>,
+        chain: &mut Vec<String>,
+    ) -> Result<()> {
         if let Some(ref base) = instr.base {
-            let base_instr = ast.get_instruction(&base.name).ok_or(anyhow!(
-                "{}: base instruction {} not found",
-                instr.name,
-                base.name
-            ))?;
+            if chain.contains(&base.name) {
+                chain.push(base.name.clone());
+                return Err(SpecError::BaseCycle {
+                    instruction: self.name.clone(),
+                    chain: chain.clone(),
+                });
+            }
+            let base_instr =
+                ast.get_instruction(&base.name).ok_or_else(|| {
+                    SpecError::UnknownBase {
+                        instruction: instr.name.clone(),
+                        base: base.name.clone(),
+                    }
+                })?;
 
-            let pmap = Self::parameter_map(base_instr, base);
-            result.resolve_timing(base_instr, &pmap)?;
-            result.resolve_fields(base_instr, &pmap, classes)?;
-            result.resolve_assembly(base_instr, &pmap)?;
-            result.resolve_machine(base_instr, &pmap)?;
+            let base_pmap = Self::parameter_map(instr, base_instr, base, pmap)?;
+            chain.push(base.name.clone());
+            self.apply(base_instr, &base_pmap, ast, classes, chain)?;
+            chain.pop();
         }
 
-        let empty = HashMap::new();
-        result.resolve_timing(instr, &empty)?;
-        result.resolve_fields(instr, &empty, classes)?;
-        result.resolve_assembly(instr, &empty)?;
-        result.resolve_machine(instr, &empty)?;
+        self.resolve_timing(instr, pmap)?;
+        self.resolve_fields(instr, pmap, classes)?;
+        self.resolve_assembly(instr, pmap)?;
+        self.resolve_machine(instr, pmap)?;
 
-        Ok(result)
+        Ok(())
     }
 
     fn parameter_map(
+        instr: &ast::Instruction,
         base_instr: &ast::Instruction,
         base: &Base,
-    ) -> HashMap<String, ast::BaseParameter> {
+        pmap: &HashMap<String, ast::BaseParameter>,
+    ) -> Result<HashMap<String, ast::BaseParameter>> {
+        let params = &base_instr.parameters;
+        let required =
+            params.iter().take_while(|p| p.default.is_none()).count();
+        if let Some(p) = params[required..].iter().find(|p| p.default.is_none())
+        {
+            return Err(SpecError::ParameterDefaultOrder {
+                base: base.name.clone(),
+                parameter: p.name.clone(),
+            });
+        }
+        let given = base.parameters.len();
+        if given < required || given > params.len() {
+            return Err(SpecError::ParameterCount {
+                instruction: instr.name.clone(),
+                base: base.name.clone(),
+                min: required,
+                max: params.len(),
+                given,
+            });
+        }
         let mut m = HashMap::<String, ast::BaseParameter>::default();
-        for (i, param) in base_instr.parameters.iter().enumerate() {
-            m.insert(param.clone(), base.parameters[i].clone());
+        for (i, param) in params.iter().enumerate() {
+            let value = match base.parameters.get(i).or(param.default.as_ref())
+            {
+                Some(BaseParameter::Parameter(p)) => pmap
+                    .get(p.as_str())
+                    .cloned()
+                    .ok_or_else(|| SpecError::UnresolvedBaseParameter {
+                        instruction: instr.name.clone(),
+                        base: base.name.clone(),
+                        parameter: p.clone(),
+                    })?,
+                Some(v) => v.clone(),
+                None => unreachable!("arity checked above"),
+            };
+            m.insert(param.name.clone(), value);
         }
-        m
+        Ok(m)
     }
 
     fn resolve_timing(
@@ -93,6 +1358,15 @@ impl Instruction {
         if let Some(ref t) = instr.timing {
             self.timing = *t
         }
+        if let Timing::MultiRange(min, max) = self.timing {
+            if min > max {
+                return Err(SpecError::EmptyTimingRange {
+                    instruction: self.name.clone(),
+                    min,
+                    max,
+                });
+            }
+        }
         Ok(())
     }
 
@@ -108,42 +1382,13 @@ impl Instruction {
                 Some(v) => match v {
                     ast::FieldValue::NumericConstant(n) => Some(*n),
                     ast::FieldValue::GenericParameter(p) => {
-                        let value = pmap.get(p.as_str()).ok_or(anyhow!(
-                            "{}: field {}: unresolved generic parameter. \
-                            Context: {pmap:#?}",
-                            instr.name,
-                            f.name,
-                        ))?;
-                        let BaseParameter::Number(n) = value else {
-                            return Err(anyhow!(
-                                "{}: field {}: fields can only be assigned \
-                                numeric values",
-                                instr.name,
-                                f.name,
-                            ));
-                        };
-                        Some(*n)
+                        Some(field_parameter(instr, f, p, pmap)?)
                     }
                     ast::FieldValue::OptionalFieldValue(v) => {
                         match v.as_ref() {
                             ast::FieldValue::NumericConstant(n) => Some(*n),
                             ast::FieldValue::GenericParameter(p) => {
-                                let value =
-                                    pmap.get(p.as_str()).ok_or(anyhow!(
-                                        "{}: field {}: unresolved generic \
-                                        parameter. Context: {pmap:#?}",
-                                        instr.name,
-                                        f.name,
-                                    ))?;
-                                let BaseParameter::Number(n) = value else {
-                                    return Err(anyhow!(
-                                        "{}: field {}: fields can only be \
-                                        assigned numeric values",
-                                        instr.name,
-                                        f.name,
-                                    ));
-                                };
-                                Some(*n)
+                                Some(field_parameter(instr, f, p, pmap)?)
                             }
                             ast::FieldValue::OptionalFieldValue(_) => {
                                 panic!("nested optional fields not supported")
@@ -152,25 +1397,81 @@ impl Instruction {
                     }
                 },
             };
+            let invalid = |problem| SpecError::InvalidField {
+                instruction: instr.name.clone(),
+                field: f.name.clone(),
+                problem,
+            };
             let (width, class) = match &f.ty {
                 ast::FieldType::FixedWidth(w) => (*w, None),
                 ast::FieldType::Class(s) => {
                     let c = classes.get(s).ok_or_else(|| {
-                        anyhow!(
-                            "{}: field {} class {s} not found",
-                            instr.name,
-                            f.name
-                        )
+                        SpecError::UnknownClass {
+                            instruction: instr.name.clone(),
+                            field: f.name.clone(),
+                            class: s.clone(),
+                        }
                     })?;
                     (c.width, Some(s.clone()))
                 }
             };
+            if f.is_signed && class.is_some() {
+                return Err(invalid(FieldProblem::SignedClass));
+            }
+            if f.is_signed && width < 2 {
+                return Err(invalid(FieldProblem::NarrowSigned));
+            }
+            if f.radix != ast::Radix::Decimal
+                && (f.is_signed || class.is_some())
+            {
+                return Err(invalid(FieldProblem::Radix));
+            }
+            if f.pad != 0 && (f.is_signed || class.is_some()) {
+                return Err(invalid(FieldProblem::Pad));
+            }
+            if f.scale != 1 && (f.is_signed || class.is_some() || width < 2) {
+                return Err(invalid(FieldProblem::Scale));
+            }
+            if f.scale == 0 {
+                return Err(invalid(FieldProblem::ZeroScale));
+            }
+            if !f.bits.is_empty() {
+                if f.is_signed || class.is_some() || width < 2 {
+                    return Err(invalid(FieldProblem::BitNames));
+                }
+                if f.bits.len() > width {
+                    return Err(invalid(FieldProblem::TooManyBitNames {
+                        count: f.bits.len(),
+                        width,
+                    }));
+                }
+            }
+            if let Some(allowed) = &f.allowed {
+                if f.is_signed || class.is_some() {
+                    return Err(invalid(FieldProblem::Allowed));
+                }
+                if let Some(v) =
+                    allowed.iter().find(|v| width < 64 && **v >= 1 << width)
+                {
+                    return Err(invalid(FieldProblem::AllowedOverflow {
+                        value: *v,
+                        width,
+                    }));
+                }
+            }
             let field = Field {
                 doc: f.doc.clone(),
                 name: f.name.clone(),
                 width,
                 class,
                 value,
+                is_signed: f.is_signed,
+                radix: f.radix,
+                pad: f.pad,
+                scale: f.scale,
+                aliases: f.aliases.clone(),
+                bits: f.bits.clone(),
+                allowed: f.allowed.clone(),
             };
             self.fields.push(field);
         }
@@ -186,72 +1487,41 @@ impl Instruction {
         self.assembly
             .example
             .extend_from_slice(instr.assembly.example.as_slice());
-        for x in &instr.assembly.syntax {
-            match x {
-                ast::AssemblyElement::StringLiteral { value } => {
-                    self.assembly.syntax.push(AssemblyElement::StringLiteral {
-                        value: value.clone(),
-                    })
-                }
-
-                ast::AssemblyElement::NumberLiteral { value } => self
-                    .assembly
-                    .syntax
-                    .push(AssemblyElement::NumberLiteral { value: *value }),
+        if instr.assembly.syntax.is_empty() {
+            return Ok(());
+        }
+        let forms = std::iter::once(&instr.assembly.syntax)
+            .chain(&instr.assembly.alternatives)
+            .map(|form| resolve_assembly_elements(&instr.name, form, pmap))
+            .collect::<Result<Vec<_>>>()?;
+        // Each form extends each form inherited from base instructions.
+        let inherited: Vec<_> =
+            std::iter::once(std::mem::take(&mut self.assembly.syntax))
+                .chain(std::mem::take(&mut self.assembly.alternatives))
+                .collect();
+        let mut combined = inherited.iter().flat_map(|prefix| {
+            forms
+                .iter()
+                .map(move |form| [prefix.as_slice(), form].concat())
+        });
+        self.assembly.syntax = combined.next().unwrap_or_default();
+        self.assembly.alternatives = combined.collect();
 
-                ast::AssemblyElement::OptionalFlag { name, field } => {
-                    self.assembly.syntax.push(AssemblyElement::OptionalFlag {
-                        name: name.clone(),
-                        field: field.clone(),
-                    })
-                }
-
-                ast::AssemblyElement::Dot => {
-                    self.assembly.syntax.push(AssemblyElement::Dot)
-                }
-                ast::AssemblyElement::Comma => {
-                    self.assembly.syntax.push(AssemblyElement::Comma)
-                }
-                ast::AssemblyElement::Space => {
-                    self.assembly.syntax.push(AssemblyElement::Space)
-                }
-                ast::AssemblyElement::Field { name } => self
-                    .assembly
-                    .syntax
-                    .push(AssemblyElement::Field { name: name.clone() }),
-                ast::AssemblyElement::OptionalField { name, with_dot } => {
-                    self.assembly.syntax.push(AssemblyElement::OptionalField {
-                        name: name.clone(),
-                        with_dot: *with_dot,
-                    })
-                }
-                ast::AssemblyElement::Expansion { name } => {
-                    let value = pmap.get(name.as_str()).ok_or(anyhow!(
-                        "{}: field {name}: unresolved generic parameter. \
-                        Context: {pmap:#?}",
-                        instr.name,
-                    ))?;
-                    match value {
-                        BaseParameter::Text(v) => self.assembly.syntax.push(
-                            AssemblyElement::StringLiteral { value: v.clone() },
-                        ),
-                        BaseParameter::Number(v) => self
-                            .assembly
-                            .syntax
-                            .push(AssemblyElement::NumberLiteral { value: *v }),
-                    }
-                }
-            }
-        }
-
-        Ok(())
-    }
+        Ok(())
+    }
 
     fn resolve_machine(
         &mut self,
         instr: &ast::Instruction,
         pmap: &HashMap<String, ast::BaseParameter>,
     ) -> Result<()> {
+        self.machine
+            .docs
+            .extend_from_slice(instr.machine.docs.as_slice());
+        self.machine.positions.extend(
+            (0..instr.machine.layout.len())
+                .map(|i| instr.machine.positions.get(i).copied().flatten()),
+        );
         for x in &instr.machine.layout {
             match x {
                 ast::MachineElement::Field { name } => {
@@ -264,6 +1534,11 @@ impl Instruction {
                         name: name.clone(),
                     });
                 }
+                ast::MachineElement::FieldSign { name } => {
+                    self.machine
+                        .layout
+                        .push(MachineElement::FieldSign { name: name.clone() });
+                }
                 ast::MachineElement::OptionalFieldPresentTest { name } => {
                     self.machine.layout.push(
                         MachineElement::OptionalFieldPresentTest {
@@ -278,11 +1553,19 @@ impl Instruction {
                         },
                     );
                 }
-                ast::MachineElement::FieldSlice { name, begin, end } => {
+                ast::MachineElement::FieldSlice {
+                    name,
+                    begin,
+                    end,
+                    negate,
+                } => {
+                    // Slices written high bit first are normalized so the
+                    // low bit comes first.
                     self.machine.layout.push(MachineElement::FieldSlice {
                         name: name.clone(),
-                        begin: *begin,
-                        end: *end,
+                        begin: *begin.min(end),
+                        end: *begin.max(end),
+                        negate: *negate,
                     });
                 }
                 ast::MachineElement::Constant { name, width, value } => {
@@ -291,21 +1574,40 @@ impl Instruction {
                         Some(ast::MachineElementValue::NumericConstant(v)) => {
                             Some(*v)
                         }
+                        Some(ast::MachineElementValue::SignedConstant(v)) => {
+                            let fits = *width >= 64
+                                || (*width > 0 && *v >= -(1i64 << (width - 1)));
+                            if !fits {
+                                return Err(SpecError::ConstantOverflow {
+                                    instruction: instr.name.clone(),
+                                    constant: name.clone(),
+                                    value: *v,
+                                    width: *width,
+                                });
+                            }
+                            let mask =
+                                u64::MAX >> 64usize.saturating_sub(*width);
+                            Some(*v as u64 & mask)
+                        }
                         Some(ast::MachineElementValue::GenericParameter(p)) => {
-                            let value = pmap.get(p.as_str()).ok_or(anyhow!(
-                                "{}: field {name}: unresolved generic \
-                                parameter. Context: {pmap:#?}",
-                                instr.name,
-                            ))?;
+                            let value =
+                                pmap.get(p.as_str()).ok_or_else(|| {
+                                    SpecError::UnresolvedParameter {
+                                        instruction: instr.name.clone(),
+                                        element: name.clone(),
+                                        parameter: p.clone(),
+                                    }
+                                })?;
                             match value {
                                 BaseParameter::Number(n) => Some(*n),
-                                BaseParameter::Text(_) => {
-                                    return Err(anyhow!(
-                                        "{}: machine_layout {p}: layout \
-                                        positions can only be assigned numeric \
-                                        values",
-                                        instr.name,
-                                    ));
+                                BaseParameter::Text(_)
+                                | BaseParameter::Parameter(_) => {
+                                    return Err(
+                                        SpecError::NonNumericConstant {
+                                            instruction: instr.name.clone(),
+                                            parameter: p.clone(),
+                                        },
+                                    );
                                 }
                             }
                         }
@@ -316,6 +1618,19 @@ impl Instruction {
                         value,
                     });
                 }
+                ast::MachineElement::Fill { name } => {
+                    if self.machine.fill.is_some() {
+                        return Err(SpecError::MultipleFills {
+                            instruction: self.name.clone(),
+                        });
+                    }
+                    self.machine.fill = Some(self.machine.layout.len());
+                    self.machine.layout.push(MachineElement::Constant {
+                        name: name.clone(),
+                        width: 0,
+                        value: None,
+                    });
+                }
             }
         }
 
@@ -330,6 +1645,25 @@ pub struct Field {
     pub width: usize,
     pub class: Option<String>,
     pub value: Option<u64>,
+    /// Two's complement signed field.
+    pub is_signed: bool,
+    /// Radix used when emitting the field in assembly.
+    pub radix: ast::Radix,
+    /// Minimum number of digits used when emitting the field in assembly,
+    /// not counting any radix prefix. Zero for no padding.
+    pub pad: usize,
+    /// Factor the field is multiplied by in assembly. Emitting multiplies
+    /// the field value by it and parsing divides the operand by it. One for
+    /// no scaling.
+    pub scale: u64,
+    /// Alternative names for the field. Assembly syntax may refer to the
+    /// field by any of them.
+    pub aliases: Vec<String>,
+    /// Names of the individual bits of the field, least significant first.
+    /// Each gets its own boolean accessors in generated code.
+    pub bits: Vec<String>,
+    /// Values the field may hold, if not every value that fits.
+    pub allowed: Option<Vec<u64>>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -345,24 +1679,59 @@ pub struct ClassIndex(usize);
 #[derive(Debug, Default, Clone)]
 pub struct Assembly {
     pub syntax: Vec<AssemblyElement>,
+    /// Further forms the instruction may be written in. They parse like
+    /// `syntax`, which remains the form assembly is emitted in.
+    pub alternatives: Vec<Vec<AssemblyElement>>,
     pub example: Vec<ast::AssemblyExample>,
 }
 
+impl Assembly {
+    /// The canonical syntax followed by its alternatives.
+    pub fn forms(&self) -> impl Iterator<Item = &Vec<AssemblyElement>> {
+        std::iter::once(&self.syntax).chain(&self.alternatives)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum AssemblyElement {
-    StringLiteral { value: String },
-    NumberLiteral { value: u64 },
-    OptionalFlag { name: String, field: String },
-    OptionalField { name: String, with_dot: bool },
+    StringLiteral {
+        value: String,
+    },
+    NumberLiteral {
+        value: u64,
+    },
+    OptionalFlag {
+        name: String,
+        field: String,
+    },
+    /// Elements present or absent as a whole.
+    Optional {
+        elements: Vec<AssemblyElement>,
+    },
+    /// Elements present exactly when a 1-bit flag field is set.
+    Conditional {
+        field: String,
+        elements: Vec<AssemblyElement>,
+    },
     Dot,
     Comma,
     Space,
-    Field { name: String },
+    Field {
+        name: String,
+    },
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct Machine {
     pub layout: Vec<MachineElement>,
+    /// Documentation of each layout element, empty for undocumented ones.
+    pub docs: Vec<String>,
+    /// Position in the layout of a fill element, a constant whose width is
+    /// not known until the instruction width is.
+    fill: Option<usize>,
+    /// Declared bit position of each layout element, until the layout is
+    /// put in position order.
+    positions: Vec<Option<usize>>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -370,14 +1739,23 @@ pub enum MachineElement {
     Field {
         name: String,
     },
+    /// Inclusive range of bits of a field, with `begin <= end`.
     FieldSlice {
         name: String,
         begin: usize,
         end: usize,
+        /// The slice holds the complement of the bits. Complementing a field
+        /// and then slicing it is the same as complementing each slice, so
+        /// negated and plain slices of one field may be mixed.
+        negate: bool,
     },
     FieldNegate {
         name: String,
     },
+    /// Sign bit of a sign-magnitude field.
+    FieldSign {
+        name: String,
+    },
     OptionalFieldPresentTest {
         name: String,
     },
@@ -395,12 +1773,9 @@ impl MachineElement {
     pub fn name(&self) -> String {
         match self {
             Self::Field { name } => name.clone(),
-            Self::FieldSlice {
-                name,
-                begin: _,
-                end: _,
-            } => name.clone(),
+            Self::FieldSlice { name, .. } => name.clone(),
             Self::FieldNegate { name } => name.clone(),
+            Self::FieldSign { name } => name.clone(),
             Self::OptionalFieldPresentTest { name } => name.clone(),
             Self::OptionalFieldAbsentTest { name } => name.clone(),
             Self::Constant {
@@ -412,13 +1787,391 @@ impl MachineElement {
     }
 }
 
+/// Check that a class has a usable width and that its named values, if any,
+/// are distinct and cover every value of the class exactly once.
+fn validate_class(c: &ast::Class) -> Result<()> {
+    if !(2..=128).contains(&c.width) {
+        return Err(SpecError::ClassWidth {
+            class: c.name.clone(),
+        });
+    }
+    if c.values.is_empty() {
+        return Ok(());
+    }
+    let mut seen = HashMap::new();
+    for (i, v) in c.values.iter().enumerate() {
+        if c.values[..i].iter().any(|x| x.name == v.name) {
+            return Err(SpecError::DuplicateClassValue {
+                class: c.name.clone(),
+                value: v.name.clone(),
+            });
+        }
+        if v.value >> c.width.min(63) != 0 {
+            return Err(SpecError::ClassValueOverflow {
+                class: c.name.clone(),
+                value: v.name.clone(),
+                number: v.value,
+                width: c.width,
+            });
+        }
+        if let Some(other) = seen.insert(v.value, &v.name) {
+            return Err(SpecError::ClassValueCollision {
+                class: c.name.clone(),
+                first: other.clone(),
+                second: v.name.clone(),
+                number: v.value,
+            });
+        }
+    }
+    if c.width >= 64 || c.values.len() as u64 != 1 << c.width {
+        return Err(SpecError::IncompleteClass {
+            class: c.name.clone(),
+            count: c.values.len(),
+            width: c.width,
+        });
+    }
+    Ok(())
+}
+
+/// Rename the fields referenced by assembly elements according to `names`.
+fn canonicalize_fields(
+    syntax: &mut [AssemblyElement],
+    names: &HashMap<String, String>,
+) {
+    for x in syntax {
+        let name = match x {
+            AssemblyElement::Field { name } => name,
+            AssemblyElement::OptionalFlag { field, .. } => field,
+            AssemblyElement::Optional { elements } => {
+                canonicalize_fields(elements, names);
+                continue;
+            }
+            AssemblyElement::Conditional { field, elements } => {
+                canonicalize_fields(elements, names);
+                field
+            }
+            _ => continue,
+        };
+        if let Some(canonical) = names.get(name.as_str()) {
+            *name = canonical.clone();
+        }
+    }
+}
+
+/// Warn about base instructions that no instruction builds on, directly or
+/// through other bases, and about base parameters nothing in the base uses.
+fn lint_bases(ast: &ast::Ast) -> Vec<String> {
+    let mut used = HashSet::new();
+    for instr in ast.instructions.iter().filter(|i| !i.is_base()) {
+        let mut base = instr.base.as_ref();
+        while let Some(b) = base {
+            if !used.insert(b.name.as_str()) {
+                break;
+            }
+            base = ast.get_instruction(&b.name).and_then(|i| i.base.as_ref());
+        }
+    }
+
+    let mut warnings = Vec::new();
+    for instr in ast.instructions.iter().filter(|i| i.is_base()) {
+        if !used.contains(instr.name.as_str()) {
+            warnings
+                .push(format!("base instruction {} is never used", instr.name));
+        }
+        let referenced = parameter_references(instr);
+        for p in &instr.parameters {
+            if !referenced.contains(p.name.as_str()) {
+                warnings.push(format!(
+                    "base instruction {}: parameter {} is never used",
+                    instr.name, p.name
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+/// Names of the parameters an instruction expands in its fields, assembly
+/// syntax and machine layout, or forwards to its own base.
+fn parameter_references(instr: &ast::Instruction) -> HashSet<&str> {
+    fn assembly<'a>(
+        syntax: &'a [ast::AssemblyElement],
+        refs: &mut HashSet<&'a str>,
+    ) {
+        for e in syntax {
+            match e {
+                ast::AssemblyElement::Expansion { name } => {
+                    refs.insert(name);
+                }
+                ast::AssemblyElement::Optional { elements }
+                | ast::AssemblyElement::Conditional { elements, .. } => {
+                    assembly(elements, refs)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut refs = HashSet::new();
+    for f in &instr.fields {
+        match &f.value {
+            Some(ast::FieldValue::GenericParameter(p)) => {
+                refs.insert(p.as_str());
+            }
+            Some(ast::FieldValue::OptionalFieldValue(v)) => {
+                if let ast::FieldValue::GenericParameter(p) = v.as_ref() {
+                    refs.insert(p.as_str());
+                }
+            }
+            _ => {}
+        }
+    }
+    assembly(&instr.assembly.syntax, &mut refs);
+    for me in &instr.machine.layout {
+        if let ast::MachineElement::Constant {
+            value: Some(ast::MachineElementValue::GenericParameter(p)),
+            ..
+        } = me
+        {
+            refs.insert(p);
+        }
+    }
+    for p in instr.base.iter().flat_map(|b| &b.parameters) {
+        if let BaseParameter::Parameter(p) = p {
+            refs.insert(p);
+        }
+    }
+    refs
+}
+
+/// The numeric value of the parameter `p` a field of `instr` is assigned.
+fn field_parameter(
+    instr: &ast::Instruction,
+    f: &ast::Field,
+    p: &str,
+    pmap: &HashMap<String, ast::BaseParameter>,
+) -> Result<u64> {
+    match pmap.get(p) {
+        Some(BaseParameter::Number(n)) => Ok(*n),
+        Some(_) => Err(SpecError::NonNumericField {
+            instruction: instr.name.clone(),
+            field: f.name.clone(),
+        }),
+        None => Err(SpecError::UnresolvedParameter {
+            instruction: instr.name.clone(),
+            element: f.name.clone(),
+            parameter: p.to_owned(),
+        }),
+    }
+}
+
+/// Resolve assembly elements of the instruction named `name`, replacing
+/// expansions with the values of the parameters in `pmap`.
+fn resolve_assembly_elements(
+    name: &str,
+    syntax: &[ast::AssemblyElement],
+    pmap: &HashMap<String, ast::BaseParameter>,
+) -> Result<Vec<AssemblyElement>> {
+    syntax
+        .iter()
+        .map(|x| {
+            Ok(match x {
+                ast::AssemblyElement::StringLiteral { value } => {
+                    AssemblyElement::StringLiteral {
+                        value: value.clone(),
+                    }
+                }
+                ast::AssemblyElement::NumberLiteral { value } => {
+                    AssemblyElement::NumberLiteral { value: *value }
+                }
+                ast::AssemblyElement::OptionalFlag { name, field } => {
+                    AssemblyElement::OptionalFlag {
+                        name: name.clone(),
+                        field: field.clone(),
+                    }
+                }
+                ast::AssemblyElement::Optional { elements } => {
+                    AssemblyElement::Optional {
+                        elements: resolve_assembly_elements(
+                            name, elements, pmap,
+                        )?,
+                    }
+                }
+                ast::AssemblyElement::Conditional { field, elements } => {
+                    AssemblyElement::Conditional {
+                        field: field.clone(),
+                        elements: resolve_assembly_elements(
+                            name, elements, pmap,
+                        )?,
+                    }
+                }
+                ast::AssemblyElement::Dot => AssemblyElement::Dot,
+                ast::AssemblyElement::Comma => AssemblyElement::Comma,
+                ast::AssemblyElement::Space => AssemblyElement::Space,
+                ast::AssemblyElement::Field { name } => {
+                    AssemblyElement::Field { name: name.clone() }
+                }
+                ast::AssemblyElement::Expansion { name: param } => {
+                    let value = pmap.get(param.as_str()).ok_or_else(|| {
+                        SpecError::UnresolvedParameter {
+                            instruction: name.to_owned(),
+                            element: param.clone(),
+                            parameter: param.clone(),
+                        }
+                    })?;
+                    match value {
+                        BaseParameter::Text(v) => {
+                            AssemblyElement::StringLiteral { value: v.clone() }
+                        }
+                        BaseParameter::Number(v) => {
+                            AssemblyElement::NumberLiteral { value: *v }
+                        }
+                        BaseParameter::Parameter(_) => {
+                            unreachable!("parameters resolved in parameter_map")
+                        }
+                    }
+                }
+            })
+        })
+        .collect()
+}
+
+/// Warn about constants that nothing refers to, which are often misspelled
+/// characteristics.
+fn lint_constants(ast: &ast::Ast) -> Vec<String> {
+    let used = match ast.instruction_width() {
+        Some(ast::CharacteristicValue::NamedConstant(name)) => Some(name),
+        _ => None,
+    };
+    ast.constants
+        .iter()
+        .filter(|c| used != Some(&c.name))
+        .map(|c| format!("constant {} is never used", c.name))
+        .collect()
+}
+
+/// Format a sorted list of bits, collapsing runs into ranges.
+fn bit_list(bits: &[usize]) -> String {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for &bit in bits {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == bit => *end = bit,
+            _ => runs.push((bit, bit)),
+        }
+    }
+    runs.iter()
+        .map(|(begin, end)| {
+            if begin == end {
+                begin.to_string()
+            } else {
+                format!("{begin}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Check that no bit in `0..instruction_width` is claimed by two differently
+/// named layout elements, and that every bit is claimed. Repeated appearances
+/// of the same field may share bits.
+fn check_occupancy(
+    instr: &str,
+    ranges: &[(String, usize, usize)],
+    instruction_width: usize,
+) -> Result<()> {
+    let mut occupancy: Vec<Option<&str>> = vec![None; instruction_width];
+    for (name, offset, width) in ranges {
+        for bit in *offset..offset + width {
+            let Some(slot) = occupancy.get_mut(bit) else {
+                return Err(SpecError::BitBeyondWidth {
+                    instruction: instr.to_owned(),
+                    element: name.clone(),
+                    bit,
+                    width: instruction_width,
+                });
+            };
+            match slot {
+                Some(other) if *other != name.as_str() => {
+                    return Err(SpecError::BitConflict {
+                        instruction: instr.to_owned(),
+                        bit,
+                        first: (*other).to_owned(),
+                        second: name.clone(),
+                    });
+                }
+                _ => *slot = Some(name.as_str()),
+            }
+        }
+    }
+    if let Some(bit) = occupancy.iter().position(Option::is_none) {
+        return Err(SpecError::UnclaimedBit {
+            instruction: instr.to_owned(),
+            bit,
+        });
+    }
+    Ok(())
+}
+
 pub fn form_spec(ast: &ast::Ast) -> Result<Spec> {
-    let instruction_width = ast
+    for (i, c) in ast.characteristics.iter().enumerate() {
+        let kind = std::mem::discriminant(c);
+        if ast.characteristics[..i]
+            .iter()
+            .any(|x| std::mem::discriminant(x) == kind)
+        {
+            return Err(SpecError::DuplicateCharacteristic {
+                characteristic: format!("{c:?}"),
+            });
+        }
+    }
+
+    let instruction_width = match ast
         .instruction_width()
-        .ok_or(anyhow!("instruction width characteristic required"))?;
+        .ok_or(SpecError::MissingInstructionWidth)?
+    {
+        ast::CharacteristicValue::NumericConstant(w) => *w,
+        ast::CharacteristicValue::NamedConstant(name) => {
+            let constant = ast.get_constant(name).ok_or_else(|| {
+                SpecError::UndefinedConstant { name: name.clone() }
+            })?;
+            // Constants resolve in declaration order, so one declared after
+            // the characteristic is not defined yet.
+            let position = ast
+                .characteristics
+                .iter()
+                .zip(&ast.characteristic_positions)
+                .find_map(|(c, position)| {
+                    matches!(c, ast::Characteristic::InstructionWidth(_))
+                        .then_some(*position)
+                });
+            if position.is_some_and(|p| p < constant.position) {
+                return Err(SpecError::ConstantUsedBeforeDeclaration {
+                    name: name.clone(),
+                });
+            }
+            constant
+                .value
+                .try_into()
+                .map_err(|_| SpecError::WidthOverflow { instruction: None })?
+        }
+    };
 
     if instruction_width > 128 {
-        return Err(anyhow!("instruction width must be less than 128 bits"));
+        return Err(SpecError::WidthOverflow { instruction: None });
+    }
+
+    let force_storage = ast.force_storage();
+    if let Some(storage) = force_storage {
+        if ![8, 16, 32, 64, 128].contains(&storage) {
+            return Err(SpecError::InvalidStorage { storage });
+        }
+        if storage < instruction_width {
+            return Err(SpecError::StorageOverflow {
+                storage,
+                instruction: None,
+                width: instruction_width,
+            });
+        }
     }
 
     let mut instructions = Vec::new();
@@ -428,29 +2181,85 @@ pub fn form_spec(ast: &ast::Ast) -> Result<Spec> {
         .classes
         .iter()
         .map(|c| {
-            (
+            validate_class(c)?;
+            Ok((
                 c.name.clone(),
                 Class {
                     name: c.name.clone(),
                     doc: c.doc.clone(),
                     width: c.width,
+                    values: c.values.clone(),
                 },
-            )
+            ))
         })
-        .collect::<HashMap<_, _>>();
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    // Generated code declares these types next to the instruction structs.
+    const RESERVED: [&str; 3] = ["Decoder", "DisassembleIter", "Instruction"];
+    let mut names = HashSet::new();
+    for ast_instr in &ast.instructions {
+        if RESERVED.contains(&ast_instr.name.as_str()) {
+            return Err(SpecError::ReservedInstructionName {
+                name: ast_instr.name.clone(),
+            });
+        }
+        if !names.insert(ast_instr.name.as_str()) {
+            return Err(SpecError::DuplicateInstruction {
+                name: ast_instr.name.clone(),
+            });
+        }
+    }
 
     for ast_instr in &ast.instructions {
         if ast_instr.is_base() {
             continue;
         }
-        let instr = Instruction::resolve(ast_instr, ast, &classes)?;
+        let mut instr = Instruction::resolve(ast_instr, ast, &classes)?;
+        instr.width = ast_instr.width.unwrap_or(instruction_width);
+        if instr.width > 128 {
+            return Err(SpecError::WidthOverflow {
+                instruction: Some(instr.name.clone()),
+            });
+        }
+        if let Some(storage) = force_storage {
+            if storage < instr.width {
+                return Err(SpecError::StorageOverflow {
+                    storage,
+                    instruction: Some(instr.name.clone()),
+                    width: instr.width,
+                });
+            }
+        }
+        instr.check_field_names()?;
+        instr.check_layout_fields()?;
+        instr.resolve_positions()?;
+        instr.resolve_fill()?;
+        instr.check_assembly_fields()?;
+        instr.validate_layout(instr.width)?;
         instructions.push(instr);
     }
 
+    let mut warnings: Vec<String> = lint_constants(ast)
+        .into_iter()
+        .chain(lint_bases(ast))
+        .chain(instructions.iter().flat_map(Instruction::lint_assembly))
+        .chain(instructions.iter().flat_map(Instruction::lint_layout))
+        .collect();
+    // Only base instructions, or none at all, generate no code.
+    if instructions.is_empty() {
+        warnings.push("spec declares no concrete instructions".to_owned());
+    }
+
     Ok(Spec {
+        doc: ast.doc.clone(),
         instruction_width,
+        force_storage,
+        endianness: ast.endianness().unwrap_or_default(),
+        name: ast.name().map(str::to_owned),
+        version: ast.version().map(str::to_owned),
         instructions,
         classes,
+        warnings,
     })
 }
 
@@ -460,6 +2269,852 @@ mod test {
     use crate::parse;
     use std::fs::read_to_string;
 
+    #[test]
+    fn duplicate_characteristic() {
+        let text = read_to_string("testcase/characteristics.isf").unwrap();
+        let text = format!("{text}instruction_width = 32;\n");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse characteristics");
+        assert_eq!(ast.characteristics.len(), 2);
+        assert!(form_spec(&ast).is_err());
+    }
+
+    #[test]
+    fn force_storage_spec() {
+        let text = read_to_string("testcase/force-storage.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse force storage");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(spec.instruction_width, 16);
+        assert_eq!(spec.force_storage, Some(64));
+
+        let text = text.replace("u64", "u8");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse force storage");
+        assert!(form_spec(&ast).is_err());
+    }
+
+    #[test]
+    fn name_and_version() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let spec = form_spec(&parse::parse(&mut s).unwrap()).unwrap();
+        assert_eq!(spec.name.as_deref(), Some("binop"));
+        assert_eq!(spec.version.as_deref(), Some("1.0"));
+
+        let text = read_to_string("testcase/add.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let spec = form_spec(&parse::parse(&mut s).unwrap()).unwrap();
+        assert_eq!((spec.name, spec.version), (None, None));
+    }
+
+    #[test]
+    fn duplicate_names() {
+        let text = read_to_string("testcase/add.isf").unwrap();
+        let text =
+            format!("{text}{}", &text[text.find("/// Add values").unwrap()..]);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse add");
+        let err = form_spec(&ast).unwrap_err();
+        assert_eq!(err.to_string(), "duplicate instruction Add");
+
+        for name in ["Decoder", "DisassembleIter", "Instruction"] {
+            let text = read_to_string("testcase/add.isf").unwrap();
+            let text =
+                text.replace("instruction Add", &format!("instruction {name}"));
+            let mut s: &str = text.as_str();
+            let ast = parse::parse(&mut s).expect("parse add");
+            assert_eq!(
+                form_spec(&ast).unwrap_err(),
+                SpecError::ReservedInstructionName {
+                    name: name.to_owned()
+                }
+            );
+        }
+
+        let text = read_to_string("testcase/add.isf").unwrap();
+        let text = text.replace("src2: 5,", "src1: 5,");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse add");
+        let err = form_spec(&ast).unwrap_err();
+        assert_eq!(err.to_string(), "instruction Add: duplicate field src1");
+
+        let text = read_to_string("testcase/alias.isf").unwrap();
+        let text = text.replace("(alias rd)", "(alias rs)");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse alias");
+        let err = form_spec(&ast).unwrap_err();
+        assert_eq!(err.to_string(), "instruction AddImm: duplicate field rs");
+    }
+
+    #[test]
+    fn layout_width_mismatch() {
+        let text = read_to_string("testcase/add.isf").unwrap();
+        let text = text.replace("src2,\n    _: 3,", "src2,\n    _: 2,");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse add");
+        let err = form_spec(&ast).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "instruction Add: machine layout is 31 bits wide, expected 32"
+        );
+    }
+
+    #[test]
+    fn constant_order() {
+        let text = read_to_string("testcase/word-size.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse word size");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(spec.instruction_width, 32);
+        assert!(spec.warnings.is_empty());
+
+        let text = text.replacen(
+            "word_size = 32;\ninstruction_width = word_size;",
+            "instruction_width = word_size;\nword_size = 32;",
+            1,
+        );
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse word size");
+        assert_eq!(
+            form_spec(&ast).unwrap_err(),
+            SpecError::ConstantUsedBeforeDeclaration {
+                name: "word_size".to_owned()
+            }
+        );
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "instruction width: constant word_size used before its declaration"
+        );
+
+        let text = text.replacen("word_size = 32", "word_size_ = 32", 1);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse word size");
+        assert_eq!(
+            form_spec(&ast).unwrap_err(),
+            SpecError::UndefinedConstant {
+                name: "word_size".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn lint_unused_constants() {
+        let text = read_to_string("testcase/add.isf").unwrap();
+        let text = text.replacen(
+            "instruction_width = 32;",
+            "instruction_width = 32;\ninstruction_widht = 32;",
+            1,
+        );
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse add");
+        assert_eq!(
+            form_spec(&ast).expect("form spec").warnings,
+            ["constant instruction_widht is never used"]
+        );
+    }
+
+    #[test]
+    fn mixed_width() {
+        let text = read_to_string("testcase/mixed-width.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse mixed width");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(spec.instruction_width, 16);
+        assert_eq!(spec.instructions[0].width, 16);
+        assert_eq!(spec.instructions[1].width, 32);
+
+        let text = text.replace("width 32", "width 24");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse mixed width");
+        let err = form_spec(&ast).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "instruction Ldi: machine layout is 32 bits wide, expected 24"
+        );
+    }
+
+    #[test]
+    fn layout_occupancy() {
+        let ranges = |r: &[(&str, usize, usize)]| {
+            r.iter()
+                .map(|(n, o, w)| (n.to_string(), *o, *w))
+                .collect::<Vec<_>>()
+        };
+
+        let ok = ranges(&[("opcode", 0, 7), ("dst", 7, 5), ("dst", 7, 5)]);
+        assert!(check_occupancy("Add", &ok, 12).is_ok());
+
+        let overlap = ranges(&[("opcode", 0, 7), ("dst", 6, 5)]);
+        assert_eq!(
+            check_occupancy("Add", &overlap, 11)
+                .unwrap_err()
+                .to_string(),
+            "instruction Add: bit 6 is claimed by both opcode and dst"
+        );
+
+        let gap = ranges(&[("opcode", 0, 7), ("dst", 8, 4)]);
+        assert_eq!(
+            check_occupancy("Add", &gap, 12).unwrap_err().to_string(),
+            "instruction Add: bit 7 is not claimed by any element"
+        );
+    }
+
+    #[test]
+    fn class_values() {
+        let text = read_to_string("testcase/class.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse class");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(spec.classes["Reg"].values.len(), 4);
+        assert!(spec.classes["Imm"].values.is_empty());
+
+        let text = text.replace("    r2 = 2,\n", "");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse class");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "class Reg: 3 values do not cover all 2-bit values"
+        );
+    }
+
+    #[test]
+    fn allowed_values() {
+        let text = read_to_string("testcase/allowed.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse allowed");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(
+            spec.instructions[0].fields[0].allowed,
+            Some(vec![0, 1, 2, 3, 4])
+        );
+
+        let text = text.replace("{0, 1, 2, 3, 4}", "{0, 8}");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse allowed");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "Branch: allowed value 8 does not fit in the 3-bit field cond"
+        );
+    }
+
+    #[test]
+    fn negative_constant() {
+        let text = read_to_string("testcase/add.isf").unwrap();
+        let text = text.replacen("_: 3,", "_: 3 = -1,", 1);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse add");
+        assert_eq!(crate::fmt::format(&ast).matches("= -1").count(), 1);
+        let spec = form_spec(&ast).expect("form spec");
+        assert!(spec.instructions[0].machine.layout.contains(
+            &MachineElement::Constant {
+                name: "_".to_owned(),
+                width: 3,
+                value: Some(0b111),
+            }
+        ));
+
+        let text = text.replace("= -1", "= -5");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse add");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "Add: value -5 does not fit in the 3-bit constant _"
+        );
+    }
+
+    #[test]
+    fn field_bits() {
+        let text = read_to_string("testcase/field-bits.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse field bits");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(spec.instructions[0].fields[0].bits.len(), 4);
+
+        let bad = text.replace("ovf}", "ovf, sat}");
+        let mut s: &str = bad.as_str();
+        let ast = parse::parse(&mut s).expect("parse field bits");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "SetFlags: 5 bit names given for the 4-bit field flags"
+        );
+
+        let bad = text.replace("zero,", "flags,");
+        let mut s: &str = bad.as_str();
+        let ast = parse::parse(&mut s).expect("parse field bits");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "instruction SetFlags: duplicate field flags"
+        );
+    }
+
+    #[test]
+    fn radix_field() {
+        let text = read_to_string("testcase/signed.isf").unwrap();
+        let text = text.replacen(" signed", " signed hex", 1);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse signed");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "Branch: only unsigned integer field offset may have a radix"
+        );
+    }
+
+    #[test]
+    fn fill() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let expected = form_spec(&parse::parse(&mut s).unwrap()).unwrap();
+
+        let filled = text.replacen("_: 3\n}", "_: fill\n}", 1);
+        let mut s: &str = filled.as_str();
+        let spec = form_spec(&parse::parse(&mut s).unwrap()).unwrap();
+        for (a, b) in spec.instructions.iter().zip(&expected.instructions) {
+            assert_eq!(a.machine.layout, b.machine.layout);
+        }
+
+        // A fill with nothing to cover is dropped.
+        let empty = filled.replacen("src2,", "src2, _: 3,", 1);
+        let mut s: &str = empty.as_str();
+        let spec = form_spec(&parse::parse(&mut s).unwrap()).unwrap();
+        for (a, b) in spec.instructions.iter().zip(&expected.instructions) {
+            assert_eq!(a.machine.layout, b.machine.layout);
+        }
+
+        for (text, error) in [
+            (
+                filled.replacen("_: 3,", "_: fill,", 1),
+                "instruction Add: more than one fill in machine layout",
+            ),
+            (
+                filled.replacen("_: 3,", "_: 7,", 1),
+                "instruction Add: machine layout is 33 bits wide before \
+                filling, more than the instruction width of 32",
+            ),
+        ] {
+            let mut s: &str = text.as_str();
+            let ast = parse::parse(&mut s).expect("parse binop");
+            assert_eq!(form_spec(&ast).unwrap_err().to_string(), error);
+        }
+    }
+
+    #[test]
+    fn flag_field() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        for (from, to, error) in [
+            (
+                "= sign_extend]",
+                "= sign_ext]",
+                "instruction Add: flag '.sx' sets undefined field sign_ext",
+            ),
+            (
+                "sign_extend: 1",
+                "sign_extend: 2",
+                "instruction Add: flag '.sx' sets field sign_extend, which \
+                is not a 1-bit integer field",
+            ),
+        ] {
+            let text = text.replacen(from, to, 1);
+            let mut s: &str = text.as_str();
+            let ast = parse::parse(&mut s).expect("parse binop");
+            assert_eq!(form_spec(&ast).unwrap_err().to_string(), error);
+        }
+    }
+
+    #[test]
+    fn condition_field() {
+        let text = read_to_string("testcase/conditional.isf").unwrap();
+        for (from, to, error) in [
+            (
+                "[pred =>",
+                "[predicate =>",
+                "instruction PredAdd: assembly refers to undefined field \
+                predicate",
+            ),
+            (
+                "[pred =>",
+                "[src1 =>",
+                "instruction PredAdd: assembly group depends on field src1, \
+                which is not a 1-bit integer field",
+            ),
+        ] {
+            let text = text.replacen(from, to, 1);
+            let mut s: &str = text.as_str();
+            let ast = parse::parse(&mut s).expect("parse conditional");
+            assert_eq!(form_spec(&ast).unwrap_err().to_string(), error);
+        }
+    }
+
+    #[test]
+    fn undefined_assembly_field() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        let text = text.replacen("'r'src2", "'r'src3", 1);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse binop");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "instruction Add: assembly refers to undefined field src3"
+        );
+    }
+
+    #[test]
+    fn structured_errors() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        let text = text.replacen("BinOp<'add', 2>", "Binop<'add', 2>", 1);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse binop");
+        assert_eq!(
+            form_spec(&ast).unwrap_err(),
+            SpecError::UnknownBase {
+                instruction: "Add".to_owned(),
+                base: "Binop".to_owned(),
+            }
+        );
+
+        let text = read_to_string("testcase/signed.isf").unwrap();
+        let text = text.replacen(" signed", " signed pad 3", 1);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse signed");
+        assert!(matches!(
+            form_spec(&ast).unwrap_err(),
+            SpecError::InvalidField {
+                problem: FieldProblem::Pad,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn positioned_layout() {
+        let text = read_to_string("testcase/positions.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse positions");
+        let spec = form_spec(&ast).expect("form spec");
+        let machine = &spec.instructions[0].machine;
+        assert_eq!(
+            machine
+                .layout
+                .iter()
+                .map(MachineElement::name)
+                .collect::<Vec<_>>(),
+            ["opcode", "offset", "_", "base", "src", "offset"]
+        );
+        assert_eq!(machine.docs[0], "Major opcode");
+
+        let overlap = text.replace("base @ 15", "base @ 11");
+        let mut s: &str = overlap.as_str();
+        let ast = parse::parse(&mut s).expect("parse positions");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "instruction Store: bit 11 is claimed by both offset and base"
+        );
+
+        let mixed = text.replace("src @ 20", "src");
+        let mut s: &str = mixed.as_str();
+        let ast = parse::parse(&mut s).expect("parse positions");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "instruction Store: machine layout element src has no bit position"
+        );
+    }
+
+    #[test]
+    fn undefined_layout_field() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        let text = text.replacen("    _: 3\n}", "    src3?,\n    _: 2\n}", 1);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse binop");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "instruction Add: machine layout refers to undefined field src3"
+        );
+    }
+
+    #[test]
+    fn lint_unplaced_field() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        let text = text.replacen("    src2,\n    _: 3\n}", "    _: 8\n}", 1);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse binop");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(
+            spec.warnings,
+            [
+                "instruction Add: field src2 does not appear in the machine \
+                layout",
+                "instruction Sub: field src2 does not appear in the machine \
+                layout",
+            ]
+        );
+    }
+
+    #[test]
+    fn scale_field() {
+        let text = read_to_string("testcase/signed.isf").unwrap();
+        let text = text.replacen(" signed", " signed scale 2", 1);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse signed");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "Branch: only unsigned integer field offset wider than one bit \
+            may be scaled"
+        );
+
+        let text = read_to_string("testcase/scale.isf").unwrap();
+        let text = text.replacen("scale 2", "scale 0", 1);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse scale");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "AddPair: field dst has a scale of 0"
+        );
+    }
+
+    #[test]
+    fn pad_field() {
+        let text = read_to_string("testcase/signed.isf").unwrap();
+        let text = text.replacen(" signed", " signed pad 3", 1);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse signed");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "Branch: only unsigned integer field offset may be padded"
+        );
+    }
+
+    #[test]
+    fn multi_timing() {
+        let text = read_to_string("testcase/multi-timing.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse multi timing");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(spec.instructions[0].timing, Timing::Multi);
+        assert_eq!(spec.instructions[1].timing, Timing::MultiUpTo(4));
+        assert_eq!(spec.instructions[2].timing, Timing::MultiRange(3, 5));
+        assert_eq!(spec.instructions[2].timing.to_string(), "3 to 5 cycles");
+
+        let text = text.replace("3..5", "5..3");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse multi timing");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "instruction Divide: multi timing range 5..3 is empty"
+        );
+    }
+
+    #[test]
+    fn timing_units() {
+        let text = read_to_string("testcase/timing-units.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse timing units");
+        let spec = form_spec(&ast).expect("form spec");
+        let timings: Vec<_> = spec
+            .instructions
+            .iter()
+            .map(|i| (i.timing, i.timing.to_string()))
+            .collect();
+        assert_eq!(
+            timings,
+            [
+                (Timing::Nanos(3), "3 ns".to_owned()),
+                (Timing::Stages(2), "2 stages".to_owned()),
+                (Timing::Cycle(1), "1 cycle".to_owned()),
+            ]
+        );
+
+        let text = text.replace("3 ns", "3 ms");
+        let mut s: &str = text.as_str();
+        assert!(parse::parse(&mut s).is_err());
+    }
+
+    #[test]
+    fn instructions_by_opcode() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse binop");
+        let spec = form_spec(&ast).expect("form spec");
+        let names: Vec<_> = spec
+            .instructions_by_opcode()
+            .unwrap()
+            .into_iter()
+            .map(|(opcode, i)| (opcode, i.name.as_str()))
+            .collect();
+        assert_eq!(names, [(2, "Add"), (3, "Sub")]);
+
+        let text = read_to_string("testcase/nested-base.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse nested base");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(
+            spec.instructions_by_opcode().unwrap_err().to_string(),
+            "instructions Add and Xor share opcode 0x33"
+        );
+    }
+
+    #[test]
+    fn nested_base() {
+        let text = read_to_string("testcase/nested-base.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse nested base");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(spec.instructions.len(), 2);
+        let xor = &spec.instructions[1];
+        assert_eq!(xor.timing, Timing::Cycle(1));
+        assert_eq!(
+            xor.fields
+                .iter()
+                .map(|f| f.name.as_str())
+                .collect::<Vec<_>>(),
+            ["dst", "src1", "src2"]
+        );
+        assert_eq!(
+            xor.machine.layout[0],
+            MachineElement::Constant {
+                name: "opcode".to_owned(),
+                width: 7,
+                value: Some(0x33),
+            }
+        );
+        assert_eq!(
+            xor.machine.layout[2],
+            MachineElement::Constant {
+                name: "funct".to_owned(),
+                width: 3,
+                value: Some(4),
+            }
+        );
+    }
+
+    #[test]
+    fn base_cycle() {
+        let text = "instruction_width = 8;
+            /// a
+            instruction A: B<1> { }
+            /// b
+            instruction B<x>: C<$x> { }
+            /// c
+            instruction C<y>: B<$y> { }";
+        let mut s: &str = text;
+        let ast = parse::parse(&mut s).expect("parse base cycle");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "A: base instruction cycle: A -> B -> C -> B"
+        );
+
+        let text = "instruction_width = 8;
+            /// a
+            instruction A: B<1> { }
+            /// b
+            instruction B<x>: B<$x> { }";
+        let mut s: &str = text;
+        let ast = parse::parse(&mut s).expect("parse base cycle");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "A: base instruction cycle: A -> B -> B"
+        );
+    }
+
+    #[test]
+    fn base_arity() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        let short = text.replace("BinOp<'add', 2>", "BinOp<'add'>");
+        let mut s: &str = short.as_str();
+        let ast = parse::parse(&mut s).expect("parse binop");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "Add: base BinOp expects 2 parameters, got 1"
+        );
+
+        let text =
+            text.replace("BinOp<name, opcode>", "BinOp<name, opcode = 2>");
+        let defaulted = text.replace("BinOp<'add', 2>", "BinOp<'add'>");
+        let mut s: &str = defaulted.as_str();
+        let ast = parse::parse(&mut s).expect("parse binop");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(
+            spec.instructions[0].machine.layout[0],
+            MachineElement::Constant {
+                name: "opcode".to_owned(),
+                width: 7,
+                value: Some(2),
+            }
+        );
+
+        let long = text.replace("BinOp<'add', 2>", "BinOp<'add', 2, 3>");
+        let mut s: &str = long.as_str();
+        let ast = parse::parse(&mut s).expect("parse binop");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "Add: base BinOp expects 1 to 2 parameters, got 3"
+        );
+
+        let text = text.replace("<name, opcode = 2>", "<name = 'add', opcode>");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse binop");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "base BinOp: parameter opcode without a default follows a \
+            parameter with a default"
+        );
+    }
+
+    #[test]
+    fn slice_order() {
+        let text = read_to_string("testcase/slice-add-msb.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse slice add msb");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(
+            spec.instructions[0].machine.layout[6],
+            MachineElement::FieldSlice {
+                name: "src".to_owned(),
+                begin: 7,
+                end: 13,
+                negate: false,
+            }
+        );
+
+        let text = text.replace("src[13:7]", "src[14:7]");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse slice add msb");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "instruction SliceAddMsb: slice src[7:14] is outside of the \
+            14-bit field"
+        );
+    }
+
+    #[test]
+    fn slice_coverage() {
+        let text = read_to_string("testcase/slice-add.isf").unwrap();
+        let gap = text
+            .replace("src[0:6],", "src[0:4],")
+            .replace("_: 2,", "_: 4,");
+        let mut s: &str = gap.as_str();
+        let ast = parse::parse(&mut s).expect("parse slice add");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "instruction SliceAdd: field src: bits 5-6 are not covered by \
+            any slice"
+        );
+
+        let overlap =
+            text.replace("src[0:6],", "src[0:8],").replace("_: 2,", "");
+        let mut s: &str = overlap.as_str();
+        let ast = parse::parse(&mut s).expect("parse slice add");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "instruction SliceAdd: field src: bits 7-8 are covered by more \
+            than one slice"
+        );
+    }
+
+    #[test]
+    fn lint_adjacent_fields() {
+        let text = read_to_string("testcase/add-field-opt.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse add field opt");
+        assert!(form_spec(&ast).expect("form spec").warnings.is_empty());
+
+        let text = text.replace("[a] b", "[a]b");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse add field opt");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(
+            spec.warnings,
+            [
+                "instruction AddOptField: assembly field b directly follows a \
+            numeric element and may not parse; separate them with a space \
+            or string literal"
+            ]
+        );
+    }
+
+    #[test]
+    fn alternative_syntax() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        let text = text.replace(
+            "'r'dst 'r'src1 'r'src2;",
+            "'r'dst 'r'src1 'r'src2 | $name 'r'dst 'r'src1;",
+        );
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse binop");
+        let spec = form_spec(&ast).expect("form spec");
+        let add = &spec.instructions[0].assembly;
+        assert_eq!(add.syntax.len(), 11);
+        assert_eq!(add.alternatives.len(), 1);
+        assert_eq!(add.alternatives[0].len(), 7);
+        assert_eq!(
+            add.alternatives[0][..2],
+            [
+                AssemblyElement::StringLiteral {
+                    value: "add".to_owned()
+                },
+                AssemblyElement::Space,
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_layout() {
+        let text = read_to_string("testcase/add.isf").unwrap();
+        let text = format!("{}}}\n", &text[..text.find("  machine:").unwrap()]);
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse add");
+        assert_eq!(
+            form_spec(&ast).unwrap_err().to_string(),
+            "instruction Add: has fields but no machine layout to place them \
+            in"
+        );
+    }
+
+    #[test]
+    fn lint_no_instructions() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        let text = &text[..text.find("/// Add values").unwrap()];
+        let mut s: &str = text;
+        let ast = parse::parse(&mut s).expect("parse binop");
+        let spec = form_spec(&ast).expect("form spec");
+        assert!(spec.instructions.is_empty());
+        assert_eq!(
+            spec.warnings,
+            [
+                "base instruction BinOp is never used",
+                "spec declares no concrete instructions",
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_unused_bases() {
+        let text = read_to_string("testcase/binop.isf").unwrap();
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse binop");
+        assert!(form_spec(&ast).expect("form spec").warnings.is_empty());
+
+        let text = text
+            .replace("BinOp<name, opcode>", "BinOp<name, opcode, funct>")
+            .replace("BinOp<'add', 2>", "BinOp<'add', 2, 0>")
+            .replace("BinOp<'sub', 3>", "BinOp<'sub', 3, 0>");
+        let unused = text[text.find("/// A base").unwrap()..]
+            .split("instruction Add")
+            .next()
+            .unwrap()
+            .replace("BinOp<", "Unused<");
+        let text = format!("{text}\n{unused}");
+        let mut s: &str = text.as_str();
+        let ast = parse::parse(&mut s).expect("parse binop");
+        let spec = form_spec(&ast).expect("form spec");
+        assert_eq!(
+            spec.warnings,
+            [
+                "base instruction BinOp: parameter funct is never used",
+                "base instruction Unused is never used",
+                "base instruction Unused: parameter funct is never used",
+            ]
+        );
+    }
+
     #[test]
     fn binop_spec() {
         let text = read_to_string("testcase/binop.isf").unwrap();
@@ -1,6 +1,7 @@
 #![rustfmt::skip]
 /// An instruction
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
 pub struct SliceAdd(u32);
 impl Default for SliceAdd {
     fn default() -> Self {
@@ -10,52 +11,355 @@ impl Default for SliceAdd {
     }
 }
 impl SliceAdd {
+    /// Width of this instruction in bits.
+    pub const WIDTH: usize = 32;
+    /// Timing of this instruction.
+    pub const TIMING: isf::Timing = isf::Timing::Cycle(1usize);
+    /// Bits of the machine word held by constant fields.
+    pub const OPCODE_MASK: u32 = 0x7f;
+    /// Value of the bits in [`Self::OPCODE_MASK`] for this instruction.
+    pub const OPCODE_MATCH: u32 = 0x2;
+    /// Value of the constant `opcode` field.
+    pub const OPCODE: u8 = 2;
+    /// Bits of the machine word holding the `dst` field.
+    pub const DST_MASK: u32 = 0x1f00;
+    /// Offset of the lowest bit of the `dst` field in the machine word.
+    pub const DST_SHIFT: usize = 8;
+    /// Bits of the machine word holding the `src` field.
+    pub const SRC_MASK: u32 = 0xfe7f0000;
+    /// Bits of the machine word holding the `sign_extend` field.
+    pub const SIGN_EXTEND_MASK: u32 = 0x80;
+    /// Offset of the lowest bit of the `sign_extend` field in the machine word.
+    pub const SIGN_EXTEND_SHIFT: usize = 7;
     pub fn get_dst(&self) -> u8 {
-        isf::bits::get_u5_u32(self.0, 8usize)
+        (isf::bits::get_bits_u32(self.0, 8usize, 5usize) as u8)
     }
     pub fn get_opcode(&self) -> u8 {
-        isf::bits::get_u7_u32(self.0, 0usize)
+        (isf::bits::get_bits_u32(self.0, 0usize, 7usize) as u8)
     }
     pub fn get_sign_extend(&self) -> bool {
         isf::bits::get_bit_u32(self.0, 7usize)
     }
     pub fn get_src(&self) -> u16 {
-        let mut result = isf::bits::get_u7_u32(self.0, 16usize) as u16;
-        result |= (isf::bits::get_u7_u32(self.0, 25usize) as u16) << 7usize;
+        let mut result = (isf::bits::get_bits_u32(self.0, 16usize, 7usize) as u8) as u16;
+        result
+            |= ((isf::bits::get_bits_u32(self.0, 25usize, 7usize) as u8) as u16)
+                << 7usize;
         result
     }
     pub fn set_dst(&mut self, value: u8) {
-        self.0 = isf::bits::set_u5_u32(self.0, 8usize, value);
+        self.0 = isf::bits::set_bits_u32(self.0, 8usize, 5usize, u32::from(value));
+    }
+    pub fn try_set_dst(&mut self, value: u8) -> Result<(), isf::FieldOverflowError> {
+        let magnitude = value as u128;
+        if magnitude.checked_shr(5u32).unwrap_or(0) != 0 {
+            return Err(isf::FieldOverflowError {
+                field: "dst",
+                width: 5usize,
+                value: magnitude,
+                allowed: &[],
+            });
+        }
+        self.set_dst(value);
+        Ok(())
     }
     fn set_opcode(&mut self, value: u8) {
-        self.0 = isf::bits::set_u7_u32(self.0, 0usize, value);
+        self.0 = isf::bits::set_bits_u32(self.0, 0usize, 7usize, u32::from(value));
     }
     pub fn set_sign_extend(&mut self, value: bool) {
         self.0 = isf::bits::set_bit_u32(self.0, 7usize, value);
     }
     pub fn set_src(&mut self, value: u16) {
-        self.0 = isf::bits::set_u7_u32(self.0, 16usize, (value >> 0usize) as u8);
-        self.0 = isf::bits::set_u7_u32(self.0, 25usize, (value >> 7usize) as u8);
+        self.0 = isf::bits::set_bits_u32(
+            self.0,
+            16usize,
+            7usize,
+            u32::from((value >> 0usize) as u8),
+        );
+        self.0 = isf::bits::set_bits_u32(
+            self.0,
+            25usize,
+            7usize,
+            u32::from((value >> 7usize) as u8),
+        );
+    }
+    pub fn try_set_src(&mut self, value: u16) -> Result<(), isf::FieldOverflowError> {
+        let magnitude = value as u128;
+        if magnitude.checked_shr(14u32).unwrap_or(0) != 0 {
+            return Err(isf::FieldOverflowError {
+                field: "src",
+                width: 14usize,
+                value: magnitude,
+                allowed: &[],
+            });
+        }
+        self.set_src(value);
+        Ok(())
+    }
+    /// An instruction with the given field values and its constant
+    /// fields set, or an error for the first value that does not fit
+    /// its field.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        dst: u8,
+        src: u16,
+        sign_extend: bool,
+    ) -> Result<Self, isf::FieldOverflowError> {
+        let mut result = Self::default();
+        result.try_set_dst(dst)?;
+        result.try_set_src(src)?;
+        result.set_sign_extend(sign_extend);
+        Ok(result)
+    }
+    /// The backing integer of this instruction, in host byte order
+    /// and without masking to the instruction width.
+    pub fn raw(&self) -> u32 {
+        self.0
+    }
+    /// Construct an instruction from its backing integer, in host
+    /// byte order, without checking its constant fields. Use
+    /// [`isf::MachineInstruction::parse_machine`] to validate.
+    pub fn from_raw_unchecked(raw: u32) -> Self {
+        Self(raw)
+    }
+    /// Width of this instruction in bits, [`Self::WIDTH`].
+    pub fn width_bits(&self) -> usize {
+        Self::WIDTH
+    }
+    /// Placement of this instruction's fields in the machine layout.
+    pub fn fields() -> &'static [isf::FieldInfo] {
+        &[
+            isf::FieldInfo {
+                name: "sign_extend",
+                offset: 7usize,
+                width: 1usize,
+                flag: true,
+            },
+            isf::FieldInfo {
+                name: "dst",
+                offset: 8usize,
+                width: 5usize,
+                flag: false,
+            },
+            isf::FieldInfo {
+                name: "src",
+                offset: 16usize,
+                width: 7usize,
+                flag: false,
+            },
+            isf::FieldInfo {
+                name: "src",
+                offset: 25usize,
+                width: 7usize,
+                flag: false,
+            },
+        ]
+    }
+    /// Whether the constant fields of a machine word hold this
+    /// instruction's values. Cheaper than
+    /// [`isf::MachineInstruction::parse_machine`], which also checks
+    /// field values.
+    pub const fn matches(data: u32) -> bool {
+        u32::from_le(data) & Self::OPCODE_MASK == Self::OPCODE_MATCH
+    }
+    /// The machine encoding of this instruction as exactly as many
+    /// bytes as its width needs, in the byte order of the spec.
+    pub fn emit_machine_bytes(&self) -> [u8; 4] {
+        let bytes = self.0.to_le_bytes();
+        let mut result = [0; 4];
+        result.copy_from_slice(&bytes[..4]);
+        result
+    }
+    /// Parse an instruction from bytes laid out as
+    /// [`Self::emit_machine_bytes`] produces them. `None` if `data`
+    /// is of another length or does not hold this instruction.
+    pub fn parse_machine_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() != 4 {
+            return None;
+        }
+        let mut bytes = [0; 4];
+        bytes[..4].copy_from_slice(data);
+        let value = u32::from_le_bytes(bytes).to_le();
+        isf::MachineInstruction::parse_machine(value).ok()
+    }
+    /// Parse a machine instruction as
+    /// [`isf::MachineInstruction::parse_machine`] does, additionally
+    /// requiring reserved bits to be zero.
+    pub fn parse_machine_strict(data: u32) -> Result<Self, isf::FieldMismatchError> {
+        let result = <Self as isf::MachineInstruction<u32>>::parse_machine(data)?;
+        let value = u32::from_le(data);
+        let found = (value >> 13usize) & 0x7;
+        if found != 0 {
+            return Err(isf::FieldMismatchError {
+                field: "_",
+                expected: 0,
+                allowed: &[],
+                found: found as u64,
+            });
+        }
+        let found = (value >> 23usize) & 0x3;
+        if found != 0 {
+            return Err(isf::FieldMismatchError {
+                field: "_",
+                expected: 0,
+                allowed: &[],
+                found: found as u64,
+            });
+        }
+        Ok(result)
+    }
+    /// Every legal encoding of this instruction, in increasing order of
+    /// the backing integer. Fields take each of their values while
+    /// constant fields hold theirs and reserved bits are zero. There are
+    /// two to the power of the total field width of them, so this is
+    /// only tractable for instructions with few field bits.
+    pub fn all_encodings() -> impl Iterator<Item = Self> {
+        const FIELD_MASK: u32 = 0xfe7f1f80;
+        core::iter::successors(
+                Some::<u32>(0),
+                |bits| {
+                    let next = bits.wrapping_sub(FIELD_MASK) & FIELD_MASK;
+                    (next != 0).then_some(next)
+                },
+            )
+            .map(|bits| Self(Self::OPCODE_MATCH | bits))
+    }
+    /// The name and value of each field and named constant, in
+    /// machine layout order. Signed fields are given as the two's
+    /// complement bits of their width, plus the sign bit for
+    /// sign-magnitude fields.
+    pub fn debug_fields(&self) -> Vec<(String, u128)> {
+        vec![
+            ("opcode".to_owned(), u128::from((self.0 >> 0usize) & 0x7f)), ("sign_extend"
+            .to_owned(), self.get_sign_extend() as u128), ("dst".to_owned(), self
+            .get_dst() as u128), ("src".to_owned(), self.get_src() as u128)
+        ]
+    }
+    /// Type-erased view of this instruction's operands and timing.
+    pub fn view(&self) -> isf::spec::DecodedView {
+        isf::spec::DecodedView {
+            name: "SliceAdd".to_owned(),
+            operands: vec![
+                ("dst".to_owned(), self.get_dst() as u128), ("src".to_owned(), self
+                .get_src() as u128), ("sign_extend".to_owned(), self.get_sign_extend() as
+                u128)
+            ],
+            timing: Self::TIMING,
+        }
     }
     fn parse_assembly_impl(text: &mut &str) -> winnow::PResult<Self> {
         use winnow::Parser;
         let input = text;
         let mut result = Self::default();
-        let _ = "add".parse_next(input)?;
+        let _ = "add"
+            .context(
+                winnow::error::StrContext::Expected(
+                    winnow::error::StrContextValue::StringLiteral("add"),
+                ),
+            )
+            .parse_next(input)?;
         let sign_extend: Result<
             &str,
             winnow::error::ErrMode<winnow::error::ContextError>,
         > = ".sx".parse_next(input);
         result.set_sign_extend(sign_extend.is_ok());
-        let _ = winnow::ascii::multispace0.parse_next(input)?;
-        let _ = "r".parse_next(input)?;
-        let dst: u64 = isf::parse::number_parser.parse_next(input)?;
-        result.set_dst(dst.try_into().unwrap());
-        let _ = winnow::ascii::multispace0.parse_next(input)?;
-        let src: u64 = isf::parse::number_parser.parse_next(input)?;
-        result.set_src(src.try_into().unwrap());
+        let _ = winnow::ascii::multispace1
+            .context(
+                winnow::error::StrContext::Expected(
+                    winnow::error::StrContextValue::Description("whitespace"),
+                ),
+            )
+            .parse_next(input)?;
+        let _ = "r"
+            .context(
+                winnow::error::StrContext::Expected(
+                    winnow::error::StrContextValue::StringLiteral("r"),
+                ),
+            )
+            .parse_next(input)?;
+        let start = winnow::stream::Stream::checkpoint(input);
+        let dst: u64 = isf::parse::number_parser
+            .context(winnow::error::StrContext::Label("dst"))
+            .context(
+                winnow::error::StrContext::Expected(
+                    winnow::error::StrContextValue::Description("number"),
+                ),
+            )
+            .parse_next(input)?;
+        dst.try_into()
+            .ok()
+            .and_then(|v| result.try_set_dst(v).ok())
+            .ok_or_else(|| {
+                use winnow::error::AddContext;
+                winnow::stream::Stream::reset(input, &start);
+                winnow::error::ErrMode::Backtrack(
+                    winnow::error::ContextError::new()
+                        .add_context(
+                            input,
+                            &start,
+                            winnow::error::StrContext::Label("dst"),
+                        )
+                        .add_context(
+                            input,
+                            &start,
+                            winnow::error::StrContext::Expected(
+                                winnow::error::StrContextValue::Description("5-bit value"),
+                            ),
+                        ),
+                )
+            })?;
+        let _ = winnow::ascii::multispace1
+            .context(
+                winnow::error::StrContext::Expected(
+                    winnow::error::StrContextValue::Description("whitespace"),
+                ),
+            )
+            .parse_next(input)?;
+        let start = winnow::stream::Stream::checkpoint(input);
+        let src: u64 = isf::parse::number_parser
+            .context(winnow::error::StrContext::Label("src"))
+            .context(
+                winnow::error::StrContext::Expected(
+                    winnow::error::StrContextValue::Description("number"),
+                ),
+            )
+            .parse_next(input)?;
+        src.try_into()
+            .ok()
+            .and_then(|v| result.try_set_src(v).ok())
+            .ok_or_else(|| {
+                use winnow::error::AddContext;
+                winnow::stream::Stream::reset(input, &start);
+                winnow::error::ErrMode::Backtrack(
+                    winnow::error::ContextError::new()
+                        .add_context(
+                            input,
+                            &start,
+                            winnow::error::StrContext::Label("src"),
+                        )
+                        .add_context(
+                            input,
+                            &start,
+                            winnow::error::StrContext::Expected(
+                                winnow::error::StrContextValue::Description("14-bit value"),
+                            ),
+                        ),
+                )
+            })?;
         Ok(result)
     }
+    /// Parse an assembly instruction from the front of text, returning
+    /// it with the text that follows it. Unlike `parse_assembly`, the
+    /// instruction need not span the whole text.
+    pub fn parse_assembly_partial(
+        text: &str,
+    ) -> Result<
+        (Self, &str),
+        winnow::error::ParseError<&str, winnow::error::ContextError>,
+    > {
+        use winnow::Parser;
+        (Self::parse_assembly_impl, winnow::combinator::rest).parse(text)
+    }
 }
 impl isf::AssemblyInstruction for SliceAdd {
     fn parse_assembly(
@@ -71,29 +375,155 @@ impl isf::AssemblyInstruction for SliceAdd {
         if self.get_sign_extend() {
             s += ".sx";
         }
-        s += " ";
+        if !s.is_empty() && !s.ends_with(' ') {
+            s += " ";
+        }
         s += "r";
         s += &format!("{}", self.get_dst());
-        s += " ";
+        if !s.is_empty() && !s.ends_with(' ') {
+            s += " ";
+        }
         s += &format!("{}", self.get_src());
+        s.truncate(s.trim_end().len());
         s
     }
 }
+impl std::fmt::Display for SliceAdd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&isf::AssemblyInstruction::emit_assembly(self))
+    }
+}
 impl isf::MachineInstruction<u32> for SliceAdd {
     fn parse_machine(data: u32) -> Result<Self, isf::FieldMismatchError> {
-        let perhaps = Self(data);
+        let perhaps = Self(u32::from_le(data));
         let found = perhaps.get_opcode().try_into().unwrap();
         let expected = 2u64;
         if found != expected {
             return Err(isf::FieldMismatchError {
-                field: "opcode".to_owned(),
+                field: "opcode",
                 expected,
+                allowed: &[],
                 found,
             });
         }
         Ok(perhaps)
     }
     fn emit_machine(&self) -> u32 {
-        self.0
+        self.0.to_le()
+    }
+}
+impl TryFrom<u32> for SliceAdd {
+    type Error = isf::FieldMismatchError;
+    fn try_from(data: u32) -> Result<Self, Self::Error> {
+        isf::MachineInstruction::parse_machine(data)
+    }
+}
+impl From<SliceAdd> for u32 {
+    fn from(value: SliceAdd) -> Self {
+        isf::MachineInstruction::emit_machine(&value)
+    }
+}
+impl isf::DecodedInstruction for SliceAdd {
+    fn name(&self) -> &'static str {
+        "SliceAdd"
+    }
+    fn assembly(&self) -> String {
+        isf::AssemblyInstruction::emit_assembly(self)
+    }
+    fn machine(&self) -> u128 {
+        u128::from(self.0)
+    }
+    fn view(&self) -> isf::spec::DecodedView {
+        SliceAdd::view(self)
+    }
+}
+/// Any instruction of this spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    SliceAdd(SliceAdd),
+}
+impl Instruction {
+    /// Emit the instruction in machine form.
+    pub fn emit_machine(&self) -> u32 {
+        match self {
+            Self::SliceAdd(i) => isf::MachineInstruction::emit_machine(i),
+        }
+    }
+    /// Emit the instruction in assembly form.
+    pub fn emit_assembly(&self) -> String {
+        match self {
+            Self::SliceAdd(i) => isf::AssemblyInstruction::emit_assembly(i),
+        }
+    }
+}
+/// Decode a machine word as the first instruction whose constant
+/// fields match, in spec order.
+pub fn decode(raw: u32) -> Option<Instruction> {
+    let value = u32::from_le(raw);
+    if value & SliceAdd::OPCODE_MASK == SliceAdd::OPCODE_MATCH {
+        if let Ok(i) = <SliceAdd as isf::MachineInstruction<u32>>::parse_machine(raw) {
+            return Some(Instruction::SliceAdd(i));
+        }
+    }
+    None
+}
+/// Iterator over the instructions in a byte buffer, created by
+/// [`disassemble`].
+#[derive(Debug, Clone)]
+pub struct DisassembleIter<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+impl Iterator for DisassembleIter<'_> {
+    type Item = Result<(usize, Instruction), isf::DecodeError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offset;
+        let chunk = self.bytes.get(offset..offset + 4usize)?;
+        self.offset += 4usize;
+        let raw = chunk.iter().rev().fold(0u128, |v, b| v << 8 | u128::from(*b));
+        let value = (raw as u32).to_le();
+        Some(decode(value).map(|i| (offset, i)).ok_or(isf::DecodeError { offset, raw }))
+    }
+}
+/// Decode the instructions in a byte buffer in order, yielding each
+/// with its byte offset. A trailing partial instruction is ignored.
+pub fn disassemble(bytes: &[u8]) -> DisassembleIter<'_> {
+    DisassembleIter {
+        bytes,
+        offset: 0,
+    }
+}
+/// Assemble a program with one instruction per line into machine
+/// words. Blank lines, `//` comments and trailing semicolons are
+/// ignored.
+pub fn assemble(source: &str) -> Result<Vec<u32>, isf::AssembleError> {
+    let mut words = Vec::new();
+    for (n, line) in source.lines().enumerate() {
+        let text = line.split("//").next().unwrap_or_default().trim();
+        let text = text.strip_suffix(';').unwrap_or(text).trim_end();
+        if text.is_empty() {
+            continue;
+        }
+        if let Ok(i) = <SliceAdd as isf::AssemblyInstruction>::parse_assembly(text) {
+            words.push(isf::MachineInstruction::emit_machine(&i));
+            continue;
+        }
+        return Err(isf::AssembleError {
+            line: n + 1,
+            text: text.to_owned(),
+        });
+    }
+    Ok(words)
+}
+/// Decoder for the instructions of this spec.
+#[derive(Debug, Default)]
+pub struct Decoder;
+impl isf::DecoderPlugin for Decoder {
+    fn try_decode(&self, word: u128) -> Option<Box<dyn isf::DecodedInstruction>> {
+        let data = u32::try_from(word).ok()?;
+        if let Ok(i) = <SliceAdd as isf::MachineInstruction<u32>>::parse_machine(data) {
+            return Some(Box::new(i));
+        }
+        None
     }
 }
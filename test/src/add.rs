@@ -23,5 +23,15 @@ fn add() -> Result<(), anyhow::Error> {
     assert_eq!(ap.get_src2(), 5);
 
     assert_eq!(a, ap);
+    assert_eq!(format!("{a}"), "add r3 r4 r5");
+
+    let counts = [a, ap, a].into_iter().fold(
+        std::collections::HashMap::<Add, usize>::new(),
+        |mut counts, i| {
+            *counts.entry(i).or_default() += 1;
+            counts
+        },
+    );
+    assert_eq!(counts[&a], 3);
     Ok(())
 }
@@ -0,0 +1,30 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+isf_macro::isf! {
+    path = "isf/testcase/alias.isf",
+    tests = true,
+}
+
+#[test]
+fn alias() -> Result<(), anyhow::Error> {
+    use isf::AssemblyInstruction;
+
+    let mut a = AddImm::parse_assembly("addi r1 r4 47")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!(a.get_dst(), 1);
+    assert_eq!(a.get_rd(), 1);
+    assert_eq!(a.get_src(), 4);
+    assert_eq!(a.get_rs(), 4);
+    assert_eq!(a.get_rs1(), 4);
+
+    a.set_rd(7);
+    assert_eq!(a.get_dst(), 7);
+    a.try_set_rs1(9)?;
+    assert_eq!(a.get_src(), 9);
+    assert!(a.try_set_rs(32).is_err());
+    assert_eq!(a.emit_assembly(), "addi r7 r9 47");
+
+    Ok(())
+}
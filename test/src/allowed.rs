@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf! {
+    path = "isf/testcase/allowed.isf",
+    tests = true,
+}
+
+#[test]
+fn allowed() -> Result<(), anyhow::Error> {
+    let mut b = Branch::default();
+    b.try_set_cond(4)?;
+    assert_eq!(b.get_cond(), 4);
+
+    let err = b.try_set_cond(5).unwrap_err();
+    assert_eq!(err.allowed, [0, 1, 2, 3, 4]);
+    assert_eq!(
+        err.to_string(),
+        "value 5 is not allowed in field cond, expected one of [0, 1, 2, 3, 4]"
+    );
+    assert_eq!(b.get_cond(), 4);
+    assert!(Branch::parse_assembly("b 6 47").is_err());
+
+    // A reserved condition is well formed but not a valid encoding.
+    b.set_cond(7);
+    let err = Branch::parse_machine(b.emit_machine()).unwrap_err();
+    assert_eq!(err.field, "cond");
+    assert_eq!(err.found, 7);
+    assert_eq!(err.expected, 0);
+    assert_eq!(err.allowed, [0, 1, 2, 3, 4]);
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf! {
+    path = "isf/testcase/alternatives.isf",
+    tests = true,
+}
+
+#[test]
+fn alternatives() -> Result<(), anyhow::Error> {
+    let short =
+        Jmp::parse_assembly("jmp 4").map_err(|e| anyhow::anyhow!("{e}"))?;
+    let long = Jmp::parse_assembly("jmp.rel +4")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!(short, long);
+    assert_eq!(long.get_offset(), 4);
+    assert_eq!(long.emit_assembly(), "jmp 4");
+
+    // Failures are reported against the canonical form.
+    let err = Jmp::parse_assembly_detailed("jmp x").unwrap_err();
+    assert_eq!(err.column, 5);
+    assert!(Jmp::parse_assembly("jmp.rel 4").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::MachineInstruction;
+
+mod allowed {
+    isf_macro::isf! {
+        path = "isf/testcase/allowed.isf",
+        arbitrary = true,
+    }
+}
+
+mod binop {
+    isf_macro::isf! {
+        path = "isf/testcase/binop.isf",
+        arbitrary = true,
+    }
+}
+
+mod class {
+    isf_macro::isf! {
+        path = "isf/testcase/class.isf",
+        arbitrary = true,
+    }
+}
+
+mod sign_magnitude {
+    isf_macro::isf! {
+        path = "isf/testcase/sign-magnitude.isf",
+        arbitrary = true,
+    }
+}
+
+mod signed {
+    isf_macro::isf! {
+        path = "isf/testcase/signed.isf",
+        arbitrary = true,
+    }
+}
+
+mod slice_add {
+    isf_macro::isf! {
+        path = "isf/testcase/slice-add.isf",
+        arbitrary = true,
+    }
+}
+
+#[test]
+fn arbitrary() -> Result<(), anyhow::Error> {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    // A simple deterministic byte stream in place of fuzzer input.
+    let data: Vec<u8> = (0..4096u32)
+        .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+        .collect();
+    let mut u = Unstructured::new(&data);
+
+    for _ in 0..64 {
+        let b = allowed::Branch::arbitrary(&mut u)?;
+        allowed::Branch::parse_machine(b.emit_machine()).unwrap();
+
+        let i = binop::Instruction::arbitrary(&mut u)?;
+        assert!(binop::decode(i.emit_machine()).is_some());
+
+        let l = class::Li::arbitrary(&mut u)?;
+        class::Li::parse_machine(l.emit_machine()).unwrap();
+
+        let i = sign_magnitude::Instruction::arbitrary(&mut u)?;
+        assert_eq!(sign_magnitude::decode(i.emit_machine()), Some(i));
+
+        let i = signed::Instruction::arbitrary(&mut u)?;
+        assert_eq!(signed::decode(i.emit_machine()), Some(i));
+
+        let i = slice_add::Instruction::arbitrary(&mut u)?;
+        assert_eq!(slice_add::decode(i.emit_machine()), Some(i));
+    }
+
+    Ok(())
+}
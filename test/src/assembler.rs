@@ -0,0 +1,31 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+isf_macro::isf!("isf/testcase/binop.isf");
+
+#[test]
+fn assembler() -> Result<(), anyhow::Error> {
+    let program = "
+        // compute things
+        add r3 r4 r5
+
+        sub r3 r4 r5; // and more
+    ";
+    assert_eq!(
+        assemble(program)?,
+        vec![
+            0b00000101_00000100_00000011_00000010,
+            0b00000101_00000100_00000011_00000011,
+        ]
+    );
+
+    let err = assemble("add r1 r2 r3\nmul r1 r2 r3\n").unwrap_err();
+    assert_eq!(err.line, 2);
+    assert_eq!(err.text, "mul r1 r2 r3");
+
+    let err = assemble("add r1 r2 r99999999999999999999\n").unwrap_err();
+    assert_eq!(err.line, 1);
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf!("isf/testcase/add.isf");
+
+#[test]
+fn assembly_error() {
+    let err = Add::parse_assembly_detailed("add r0 x4 r7").unwrap_err();
+    assert_eq!(err.offset, 7);
+    assert_eq!(err.column, 8);
+    assert_eq!(err.remaining, "x4 r7");
+    assert_eq!(err.to_string(), "expected `r` at column 8");
+
+    let err = Add::parse_assembly_detailed("add r0 r4 rx").unwrap_err();
+    assert_eq!(err.to_string(), "expected number for src2 at column 12");
+
+    let err = Add::parse_assembly_detailed("add r0 r40 r7").unwrap_err();
+    assert_eq!(err.to_string(), "expected 5-bit value for src1 at column 9");
+
+    let err = Add::parse_assembly_detailed("add r0 r99999999999999999999 r7")
+        .unwrap_err();
+    assert_eq!(err.to_string(), "expected number for src1 at column 9");
+
+    let err = Add::parse_assembly_detailed("sub r0 r4 r7").unwrap_err();
+    assert_eq!(err.to_string(), "expected `add` at column 1");
+
+    let err = Add::parse_assembly_detailed("add r0 r4 r7 r1").unwrap_err();
+    assert_eq!(err.to_string(), "unexpected input at column 13");
+}
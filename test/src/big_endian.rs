@@ -0,0 +1,27 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/big-endian.isf");
+
+#[test]
+fn big_endian() -> Result<(), anyhow::Error> {
+    // add r3 r4 r5 as stored in a big-endian image
+    let bytes = [0b00000101, 0b00000100, 0b00000011, 0b00000010];
+    let a = Add::parse_machine(u32::from_ne_bytes(bytes)).unwrap();
+    assert_eq!(a.get_opcode(), 2);
+    assert_eq!(a.get_dst(), 3);
+    assert_eq!(a.get_src1(), 4);
+    assert_eq!(a.get_src2(), 5);
+
+    let ap = Add::parse_assembly("add r3 r4 r5").unwrap();
+    assert_eq!(a, ap);
+    assert_eq!(ap.emit_machine().to_ne_bytes(), bytes);
+    assert_eq!(ap.emit_machine_bytes(), bytes);
+    assert_eq!(Add::parse_machine_bytes(&bytes), Some(ap));
+    assert_eq!(Add::parse_machine_bytes(&bytes[..3]), None);
+
+    Ok(())
+}
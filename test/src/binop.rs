@@ -46,3 +46,9 @@ fn add() -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+#[test]
+fn isa() {
+    assert_eq!(ISA_NAME, "binop");
+    assert_eq!(ISA_VERSION, "1.0");
+}
@@ -0,0 +1,29 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+isf_macro::isf! {
+    path = "isf/testcase/add.isf",
+    caseless_mnemonics = true,
+}
+
+#[test]
+fn caseless() -> Result<(), anyhow::Error> {
+    use isf::AssemblyInstruction;
+
+    for text in ["add r0 r4 r7", "ADD r0 r4 r7", "Add r0 r4 r7"] {
+        let add =
+            Add::parse_assembly(text).map_err(|e| anyhow::anyhow!("{e}"))?;
+        assert_eq!(add.emit_assembly(), "add r0 r4 r7");
+    }
+
+    let add = Add::parse_assembly("ADD.SX r0 r4 r7")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert!(add.get_sign_extend());
+    assert_eq!(add.emit_assembly(), "add.sx r0 r4 r7");
+
+    // Operand prefixes after the mnemonic are still matched exactly.
+    assert!(Add::parse_assembly("add R0 r4 r7").is_err());
+
+    Ok(())
+}
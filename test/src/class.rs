@@ -0,0 +1,27 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/class.isf");
+
+#[test]
+fn class() -> Result<(), anyhow::Error> {
+    let mut li = Li::parse_assembly("li r3 17").unwrap();
+    assert_eq!(li.get_dst(), Reg::StackPtr);
+    assert_eq!(li.get_imm(), Imm::try_from(17)?);
+    assert_eq!(li, Li::parse_machine(0b01000111_00000100).unwrap());
+
+    li.set_dst(Reg::R1);
+    assert_eq!(li.emit_assembly(), "li r1 17");
+    assert_eq!(u8::from(Reg::Zero), 0);
+    assert_eq!(Reg::try_from(2)?, Reg::R2);
+
+    assert!(Reg::try_from(4).is_err());
+    assert!(Imm::try_from(64).is_err());
+    assert!(li.try_set_imm(64).is_err());
+    assert!(Li::parse_assembly("li r4 17").is_err());
+
+    Ok(())
+}
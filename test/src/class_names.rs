@@ -0,0 +1,32 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf! {
+    path = "isf/testcase/class-names.isf",
+    class_names = true,
+    tests = true,
+}
+
+#[test]
+fn class_names() -> Result<(), anyhow::Error> {
+    let mut li = Li::parse_assembly("li sp 17").unwrap();
+    assert_eq!(li.get_dst(), Reg::Sp);
+    assert_eq!(li.emit_assembly(), "li sp 17");
+
+    li.set_dst(Reg::R1);
+    assert_eq!(li.emit_assembly(), "li r1 17");
+    assert_eq!(Li::parse_assembly("li 1 17").unwrap(), li);
+
+    assert_eq!(Reg::Zero.name(), Some("zero"));
+    assert_eq!(Reg::from_name("r2"), Some(Reg::R2));
+    assert_eq!(Reg::from_name("r4"), None);
+    assert_eq!(Imm::try_from(5)?.name(), None);
+
+    assert!(Li::parse_assembly("li r4 17").is_err());
+    assert!(Li::parse_assembly("li 4 17").is_err());
+
+    Ok(())
+}
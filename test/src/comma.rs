@@ -0,0 +1,28 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf! {
+    path = "isf/testcase/comma.isf",
+    tests = true,
+}
+
+#[test]
+fn comma() -> Result<(), anyhow::Error> {
+    for text in [
+        "add r1, r2, r3",
+        "add r1,r2,r3",
+        "add r1 ,r2 , r3",
+        "add r1,\tr2,  r3",
+    ] {
+        let a = Add::parse_assembly(text).unwrap();
+        assert_eq!(a.get_dst(), 1);
+        assert_eq!(a.get_src1(), 2);
+        assert_eq!(a.get_src2(), 3);
+        assert_eq!(a.emit_assembly(), "add r1, r2, r3");
+    }
+    assert!(Add::parse_assembly("add r1 r2, r3").is_err());
+    Ok(())
+}
@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf! {
+    path = "isf/testcase/conditional.isf",
+    tests = true,
+}
+
+#[test]
+fn conditional() -> Result<(), anyhow::Error> {
+    let a = PredAdd::parse_assembly("add r1, r2, r3")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!((a.get_pred(), a.get_src2(), a.get_src3()), (false, 3, 0));
+    assert_eq!(a.emit_assembly(), "add r1, r2, r3");
+
+    let a = PredAdd::parse_assembly("add.p r1, r2, r3, r4")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!((a.get_pred(), a.get_src2(), a.get_src3()), (true, 3, 4));
+    assert_eq!(a.emit_assembly(), "add.p r1, r2, r3, r4");
+
+    // The group is required with the flag and refused without it.
+    assert!(PredAdd::parse_assembly("add.p r1, r2, r3").is_err());
+    assert!(PredAdd::parse_assembly("add r1, r2, r3, r4").is_err());
+
+    // The register is emitted only with the flag, even when set.
+    let mut a = PredAdd::parse_assembly("add r1, r2, r3").unwrap();
+    a.set_src3(7);
+    assert_eq!(a.emit_assembly(), "add r1, r2, r3");
+    a.set_pred(true);
+    assert_eq!(a.emit_assembly(), "add.p r1, r2, r3, r7");
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf!("isf/testcase/add.isf");
+
+mod radix {
+    isf_macro::isf!("isf/testcase/radix.isf");
+}
+
+#[test]
+fn constructor() -> Result<(), anyhow::Error> {
+    let a = Add::new(3, 4, 5, true)?;
+    assert_eq!(a, Add::parse_assembly("add.sx r3 r4 r5").unwrap());
+    assert_eq!(a.get_opcode(), 2);
+
+    let e = Add::new(3, 32, 5, false).unwrap_err();
+    assert_eq!((e.field, e.width, e.value), ("src1", 5, 32));
+
+    // Fields with a default value are still arguments.
+    let l = radix::LoadMask::new(3, 0x1234, 0b11)?;
+    assert_eq!(l.emit_assembly(), "ldm r3 0x1234 0b11");
+    assert!(radix::LoadMask::new(3, 0x1234, 16).is_err());
+
+    Ok(())
+}
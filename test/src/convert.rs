@@ -0,0 +1,23 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/add.isf");
+
+#[test]
+fn convert() -> Result<(), anyhow::Error> {
+    let a = Add::parse_assembly("add r0 r4 r7")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let raw: u32 = a.into();
+    assert_eq!(raw, a.emit_machine());
+
+    let b: Add = raw.try_into().map_err(|e| anyhow::anyhow!("{e:?}"))?;
+    assert_eq!(a, b);
+
+    let err = Add::try_from(raw ^ 1).unwrap_err();
+    assert_eq!(err.field, "opcode");
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::DecoderPlugin;
+
+mod narrow {
+    isf_macro::isf!("isf/testcase/narrow.isf");
+}
+
+mod wide {
+    isf_macro::isf!("isf/testcase/wide.isf");
+}
+
+#[test]
+fn decoder() -> Result<(), anyhow::Error> {
+    let decoders: Vec<Box<dyn DecoderPlugin>> =
+        vec![Box::new(narrow::Decoder), Box::new(wide::Decoder)];
+
+    let decode =
+        |word: u128| decoders.iter().find_map(|d| d.try_decode(word)).unwrap();
+
+    let i = decode(0b101_00011);
+    assert_eq!(i.name(), "Inc");
+    assert_eq!(i.assembly(), "inc r5");
+    assert_eq!(i.machine(), 0b101_00011);
+
+    let raw = (0x12345 << 65) | (0x7ffff << 16) | (3 << 7) | 9;
+    let i = decode(raw);
+    assert_eq!(i.name(), "LoadWide");
+    assert_eq!(i.assembly(), "ldw r3 524287 74565");
+    assert_eq!(i.machine(), raw);
+
+    assert!(decoders.iter().all(|d| d.try_decode(0x7f).is_none()));
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/deprecated.isf");
+
+#[test]
+#[allow(deprecated)]
+fn deprecated() -> Result<(), anyhow::Error> {
+    let a = Add::parse_assembly("add r0 r4").unwrap();
+    let x = AddX::parse_assembly("addx r0 r4").unwrap();
+    assert_eq!(a.get_dst(), x.get_dst());
+    assert_eq!(a.get_src(), x.get_src());
+    assert_eq!(Add::parse_machine(a.emit_machine()).unwrap(), a);
+    Ok(())
+}
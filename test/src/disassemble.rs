@@ -0,0 +1,42 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod little {
+    isf_macro::isf!("isf/testcase/binop.isf");
+}
+
+mod big {
+    isf_macro::isf!("isf/testcase/big-endian.isf");
+}
+
+#[test]
+fn disassemble() -> Result<(), anyhow::Error> {
+    let bytes = [
+        0b00000010, 0b00000011, 0b00000100, 0b00000101, // add r3 r4 r5
+        0b00000011, 0b00000011, 0b00000100, 0b00000101, // sub r3 r4 r5
+        0b01111111, 0, 0, 0, // unrecognized
+        0b00000010, 0b00000011, // partial
+    ];
+    let decoded: Vec<_> = little::disassemble(&bytes)
+        .map(|r| r.map(|(offset, i)| (offset, i.emit_assembly())))
+        .collect();
+    assert_eq!(
+        decoded,
+        vec![
+            Ok((0, "add r3 r4 r5".to_owned())),
+            Ok((4, "sub r3 r4 r5".to_owned())),
+            Err(isf::DecodeError {
+                offset: 8,
+                raw: 0x7f
+            }),
+        ]
+    );
+
+    let bytes = [0b00000101, 0b00000100, 0b00000011, 0b00000010];
+    let (offset, i) = big::disassemble(&bytes).next().unwrap()?;
+    assert_eq!(offset, 0);
+    assert_eq!(i.emit_assembly(), "add r3 r4 r5");
+
+    Ok(())
+}
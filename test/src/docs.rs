@@ -0,0 +1,14 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+isf_macro::isf_docs!("isf/testcase/deprecated.isf");
+
+#[test]
+fn docs() {
+    assert!(DOCS_HTML.contains("<b>Deprecated:</b> use addx instead"));
+    assert_eq!(
+        DOCS_HTML,
+        isf::docgen::generate_docs("../isf/testcase/deprecated.isf").unwrap()
+    );
+}
@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::MachineInstruction;
+
+mod narrow {
+    isf_macro::isf!("isf/testcase/narrow.isf");
+}
+
+mod allowed {
+    isf_macro::isf!("isf/testcase/allowed.isf");
+}
+
+#[test]
+fn all_encodings() {
+    let all: Vec<_> = narrow::Inc::all_encodings().collect();
+    assert_eq!(all.len(), 8);
+    for (reg, i) in all.iter().enumerate() {
+        assert_eq!(i.get_opcode(), 3);
+        assert_eq!(usize::from(i.get_reg()), reg);
+        assert_eq!(narrow::Inc::parse_machine(i.emit_machine()).unwrap(), *i);
+    }
+}
+
+#[test]
+fn all_encodings_allowed() {
+    // Reserved conditions are skipped, reserved bits stay zero.
+    let mut count = 0;
+    for i in allowed::Branch::all_encodings() {
+        assert!(i.get_cond() <= 4);
+        assert!(allowed::Branch::parse_machine_strict(i.emit_machine()).is_ok());
+        count += 1;
+    }
+    assert_eq!(count, 5 << 15);
+}
@@ -0,0 +1,87 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod add {
+    isf_macro::isf! {
+        path = "isf/testcase/add.isf",
+        tests = true,
+    }
+}
+
+mod add_field_opt {
+    isf_macro::isf! {
+        path = "isf/testcase/add-field-opt.isf",
+        tests = true,
+    }
+}
+
+mod big_endian {
+    isf_macro::isf! {
+        path = "isf/testcase/big-endian.isf",
+        tests = true,
+    }
+}
+
+mod binop {
+    isf_macro::isf! {
+        path = "isf/testcase/binop.isf",
+        tests = true,
+    }
+}
+
+mod class {
+    isf_macro::isf! {
+        path = "isf/testcase/class.isf",
+        tests = true,
+    }
+}
+
+mod deprecated {
+    isf_macro::isf! {
+        path = "isf/testcase/deprecated.isf",
+        tests = true,
+    }
+}
+
+mod odd_width {
+    isf_macro::isf! {
+        path = "isf/testcase/odd-width.isf",
+        tests = true,
+    }
+}
+
+mod reset {
+    isf_macro::isf! {
+        path = "isf/testcase/reset.isf",
+        tests = true,
+    }
+}
+
+mod sign_magnitude {
+    isf_macro::isf! {
+        path = "isf/testcase/sign-magnitude.isf",
+        tests = true,
+    }
+}
+
+mod signed {
+    isf_macro::isf! {
+        path = "isf/testcase/signed.isf",
+        tests = true,
+    }
+}
+
+mod slice_add {
+    isf_macro::isf! {
+        path = "isf/testcase/slice-add.isf",
+        tests = true,
+    }
+}
+
+mod word_size {
+    isf_macro::isf! {
+        path = "isf/testcase/word-size.isf",
+        tests = true,
+    }
+}
@@ -0,0 +1,28 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/field-bits.isf");
+
+#[test]
+fn field_bits() -> Result<(), anyhow::Error> {
+    let s = SetFlags::parse_assembly("setf 9")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert!(s.get_carry());
+    assert!(!s.get_zero());
+    assert!(!s.get_neg());
+    assert!(s.get_ovf());
+
+    let mut s = SetFlags::default();
+    s.set_zero(true);
+    s.set_neg(true);
+    assert_eq!(s.get_flags(), 0b0110);
+    assert_eq!(s.emit_machine(), 0b0110 << 8 | 7);
+    s.set_zero(false);
+    assert_eq!(s.get_flags(), 0b0100);
+    assert_eq!(s.emit_assembly(), "setf 4");
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/add.isf");
+
+mod slice {
+    isf_macro::isf!("isf/testcase/slice-add.isf");
+}
+
+#[test]
+fn field_consts() -> Result<(), anyhow::Error> {
+    assert_eq!(Add::DST_SHIFT, 8);
+    assert_eq!(Add::DST_MASK, 0x1f << 8);
+    assert_eq!(Add::SIGN_EXTEND_SHIFT, 7);
+    assert_eq!(Add::SIGN_EXTEND_MASK, 1 << 7);
+
+    // Build an instruction with raw bit operations.
+    let raw = Add::OPCODE_MATCH
+        | (3 << Add::DST_SHIFT) & Add::DST_MASK
+        | (4 << Add::SRC1_SHIFT) & Add::SRC1_MASK
+        | (5 << Add::SRC2_SHIFT) & Add::SRC2_MASK
+        | Add::SIGN_EXTEND_MASK;
+    let a = Add::parse_machine(raw).unwrap();
+    assert_eq!(a, Add::parse_assembly("add r3 r4 r5").unwrap());
+    assert_eq!((raw & Add::SRC1_MASK) >> Add::SRC1_SHIFT, 4);
+
+    // A field split into slices has a mask covering every slice.
+    assert_eq!(slice::SliceAdd::SRC_MASK, 0x7f << 16 | 0x7f << 25);
+    assert_eq!(slice::SliceAdd::DST_SHIFT, 8);
+
+    Ok(())
+}
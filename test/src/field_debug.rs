@@ -0,0 +1,56 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf! {
+    path = "isf/testcase/add.isf",
+    field_debug = true,
+}
+
+mod signed {
+    isf_macro::isf! {
+        path = "isf/testcase/signed.isf",
+        field_debug = true,
+    }
+}
+
+#[test]
+fn field_debug() -> Result<(), anyhow::Error> {
+    let a = Add::parse_assembly("add.sx r3 r4 r5")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!(
+        format!("{a:?}"),
+        "Add { opcode: 2, sign_extend: 1, dst: 3, src1: 4, src2: 5 }"
+    );
+    assert_eq!(
+        a.debug_fields(),
+        vec![
+            ("opcode".to_owned(), 2),
+            ("sign_extend".to_owned(), 1),
+            ("dst".to_owned(), 3),
+            ("src1".to_owned(), 4),
+            ("src2".to_owned(), 5),
+        ]
+    );
+
+    let b = Add::from_raw_unchecked(a.raw() ^ 1);
+    assert_eq!(b.debug_fields()[0], ("opcode".to_owned(), 3));
+    assert_eq!(
+        format!("{:?}", Instruction::Add(a)),
+        "Add(Add { opcode: 2, sign_extend: 1, dst: 3, src1: 4, src2: 5 })"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn field_debug_signed() {
+    let b = signed::Branch::parse_assembly("b -3").unwrap();
+    assert_eq!(format!("{b:?}"), "Branch { opcode: 5, offset: -3 }");
+    assert_eq!(
+        b.debug_fields(),
+        vec![("opcode".to_owned(), 5), ("offset".to_owned(), 0x1fd)]
+    );
+}
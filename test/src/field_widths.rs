@@ -0,0 +1,31 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/field-widths.isf");
+
+#[test]
+fn field_widths() -> Result<(), anyhow::Error> {
+    let mut p = Pack::default();
+    p.set_lo(0x1ffff);
+    p.set_mid(0xfffff);
+    p.set_hi(0xff_ffff);
+    assert_eq!(p.emit_machine(), !0b010);
+    assert_eq!(Pack::parse_machine(p.emit_machine()).unwrap(), p);
+
+    let p = Pack::parse_assembly("pack 1 2 3")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!(p.emit_machine(), 5 | 1 << 3 | 2 << 20 | 3 << 40);
+    assert_eq!((p.get_lo(), p.get_mid(), p.get_hi()), (1, 2, 3));
+
+    let mut s = Split::default();
+    s.set_value((1 << 50) - 1);
+    assert_eq!(s.get_value(), (1 << 50) - 1);
+    assert_eq!(s.emit_machine(), !(0x7ff << 21) & !0b001);
+    s.set_value(0x3_0000_0004_0001);
+    assert_eq!(s.get_value(), 0x3_0000_0004_0001);
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::FieldInfo;
+
+isf_macro::isf!("isf/testcase/add.isf");
+
+#[test]
+fn fields() -> Result<(), anyhow::Error> {
+    let info = |name, offset, width, flag| FieldInfo {
+        name,
+        offset,
+        width,
+        flag,
+    };
+    assert_eq!(
+        Add::fields(),
+        [
+            info("sign_extend", 7, 1, true),
+            info("dst", 8, 5, false),
+            info("src1", 16, 5, false),
+            info("src2", 24, 5, false),
+        ]
+    );
+    Ok(())
+}
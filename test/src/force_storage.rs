@@ -0,0 +1,17 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/force-storage.isf");
+
+#[test]
+fn force_storage() -> Result<(), anyhow::Error> {
+    let m = Mov::parse_assembly("mov r1 r2").unwrap();
+    let raw: u64 = m.emit_machine();
+    assert_eq!(raw, 0b00100001_00000001);
+    assert_eq!(Mov::parse_machine(raw).unwrap(), m);
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+isf_macro::isf! {
+    path = "isf/testcase/binop.isf",
+    fuzz = true,
+}
+
+#[test]
+fn fuzz() -> Result<(), anyhow::Error> {
+    // too short to decode
+    fuzz_decode(&[]);
+    fuzz_decode(&[0x02, 0x03]);
+
+    // decodable add and sub
+    fuzz_decode(&0b00000101_00000100_00000011_00000010u32.to_ne_bytes());
+    fuzz_decode(&0b00000101_00000100_00000011_00000011u32.to_ne_bytes());
+
+    // undecodable opcode
+    fuzz_decode(&0x7fu32.to_ne_bytes());
+
+    // every opcode with all other bits set
+    for opcode in 0..0x80u32 {
+        fuzz_decode(&(u32::MAX << 7 | opcode).to_ne_bytes());
+    }
+
+    Ok(())
+}
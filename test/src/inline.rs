@@ -0,0 +1,35 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!(
+    inline = "
+instruction_width = 8;
+
+/// Increment a register
+instruction Inc {
+  timing: 1 cycle
+  fields:
+    /// The register to increment
+    reg: 3,
+
+  assembly:
+    'inc' 'r'reg;
+
+  machine:
+    opcode: 5 = 3,
+    reg,
+}
+"
+);
+
+#[test]
+fn inline() -> Result<(), anyhow::Error> {
+    let i =
+        Inc::parse_assembly("inc r5").map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!(i.get_reg(), 5);
+    assert_eq!(i.emit_machine(), 5 << 5 | 3);
+    Ok(())
+}
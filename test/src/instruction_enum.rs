@@ -0,0 +1,30 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf!("isf/testcase/binop.isf");
+
+#[test]
+fn instruction_enum() -> Result<(), anyhow::Error> {
+    let raw_add = 0b00000101_00000100_00000011_00000010;
+    let raw_sub = 0b00000101_00000100_00000011_00000011;
+
+    let a = decode(raw_add).unwrap();
+    assert_eq!(
+        a,
+        Instruction::Add(Add::parse_assembly("add r3 r4 r5").unwrap())
+    );
+    assert_eq!(a.emit_machine(), raw_add);
+    assert_eq!(a.emit_assembly(), "add r3 r4 r5");
+
+    let s = decode(raw_sub).unwrap();
+    assert!(matches!(s, Instruction::Sub(_)));
+    assert_eq!(s.emit_machine(), raw_sub);
+    assert_eq!(s.emit_assembly(), "sub r3 r4 r5");
+
+    assert_eq!(decode(0x7f), None);
+
+    Ok(())
+}
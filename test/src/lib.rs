@@ -7,8 +7,124 @@ mod add;
 #[cfg(test)]
 mod add_field_opt;
 #[cfg(test)]
+mod alias;
+#[cfg(test)]
+mod allowed;
+#[cfg(test)]
+mod alternatives;
+#[cfg(all(test, feature = "arbitrary"))]
+mod arbitrary;
+#[cfg(test)]
+mod assembler;
+#[cfg(test)]
+mod assembly_error;
+#[cfg(test)]
+mod big_endian;
+#[cfg(test)]
 mod binop;
 #[cfg(test)]
+mod caseless;
+#[cfg(test)]
+mod class;
+#[cfg(test)]
+mod class_names;
+#[cfg(test)]
+mod comma;
+#[cfg(test)]
+mod conditional;
+#[cfg(test)]
+mod constructor;
+#[cfg(test)]
+mod convert;
+#[cfg(test)]
+mod decoder;
+#[cfg(test)]
+mod deprecated;
+#[cfg(test)]
+mod disassemble;
+#[cfg(test)]
+mod docs;
+#[cfg(test)]
+mod encodings;
+#[cfg(test)]
+mod examples;
+#[cfg(test)]
+mod field_bits;
+#[cfg(test)]
+mod field_consts;
+#[cfg(test)]
+mod field_debug;
+#[cfg(test)]
+mod field_widths;
+#[cfg(test)]
+mod fields;
+#[cfg(test)]
+mod force_storage;
+#[cfg(test)]
+mod fuzz;
+#[cfg(test)]
+mod inline;
+#[cfg(test)]
+mod instruction_enum;
+#[cfg(test)]
+mod mixed_width;
+#[cfg(test)]
+mod narrow;
+#[cfg(test)]
+mod negate_slice;
+#[cfg(test)]
+mod nested_base;
+#[cfg(test)]
+mod no_std;
+#[cfg(test)]
+mod odd_width;
+#[cfg(test)]
+mod opcode;
+#[cfg(test)]
+mod optional_group;
+#[cfg(test)]
+mod ord;
+#[cfg(test)]
+mod overflow;
+#[cfg(test)]
+mod pad;
+#[cfg(test)]
+mod partial;
+#[cfg(test)]
+mod positions;
+#[cfg(test)]
+mod radix;
+#[cfg(test)]
+mod raw;
+#[cfg(test)]
+mod reset;
+#[cfg(test)]
+mod round_trip;
+#[cfg(test)]
+mod scale;
+#[cfg(test)]
+mod serde;
+#[cfg(test)]
+mod sign_magnitude;
+#[cfg(test)]
+mod signed;
+#[cfg(test)]
 mod slice_add;
 #[cfg(test)]
 mod slice_add_contiguous;
+#[cfg(test)]
+mod slice_add_msb;
+#[cfg(test)]
+mod storage_accessors;
+#[cfg(test)]
+mod strict;
+#[cfg(test)]
+mod timing;
+#[cfg(test)]
+mod view;
+#[cfg(test)]
+mod whitespace;
+#[cfg(test)]
+mod wide;
+#[cfg(test)]
+mod wildcard;
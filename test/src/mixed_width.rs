@@ -0,0 +1,49 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/mixed-width.isf");
+
+#[test]
+fn mixed_width() -> Result<(), anyhow::Error> {
+    assert_eq!(Mov::WIDTH, 16);
+    assert_eq!(Ldi::WIDTH, 32);
+
+    let mov =
+        Mov::parse_assembly("mov r1 r2").map_err(|e| anyhow::anyhow!("{e}"))?;
+    let ldi = Ldi::parse_assembly("ldi r3 4660")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let _: u16 = mov.emit_machine();
+    assert_eq!(ldi.emit_machine(), 0x12_3432);
+
+    let words = assemble("mov r1 r2\nldi r3 4660\n")?;
+    assert_eq!(words, vec![0x0211, 0x12_3432]);
+    assert_eq!(decode(words[0]), Some(Instruction::Mov(mov)));
+    assert_eq!(decode(words[1]), Some(Instruction::Ldi(ldi)));
+    assert_eq!(Instruction::Mov(mov).emit_machine(), 0x0211);
+
+    let bytes = [
+        0x11, 0x02, // mov r1 r2
+        0x32, 0x34, 0x12, 0x00, // ldi r3 4660
+        0x0f, 0x00, // unrecognized
+        0x11, // partial
+    ];
+    let decoded: Vec<_> = disassemble(&bytes)
+        .map(|r| r.map(|(offset, i)| (offset, i.emit_assembly())))
+        .collect();
+    assert_eq!(
+        decoded,
+        vec![
+            Ok((0, "mov r1 r2".to_owned())),
+            Ok((2, "ldi r3 4660".to_owned())),
+            Err(isf::DecodeError {
+                offset: 6,
+                raw: 0x0f
+            }),
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/narrow.isf");
+
+#[test]
+fn narrow() -> Result<(), anyhow::Error> {
+    let i = Inc::parse_assembly("inc r5").unwrap();
+    assert_eq!(i.get_opcode(), 3);
+    assert_eq!(i.get_reg(), 5);
+
+    let raw: u8 = i.emit_machine();
+    assert_eq!(raw, 0b101_00011);
+    assert_eq!(Inc::parse_machine(raw).unwrap(), i);
+    assert_eq!(i.emit_assembly(), "inc r5");
+    assert_eq!(std::mem::size_of::<Inc>(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/negate-slice.isf");
+
+#[test]
+fn negate_slice() -> Result<(), anyhow::Error> {
+    let mut a = NegateSlice::parse_assembly("neg r4 0xa5 0x3").unwrap();
+    assert_eq!(a.get_imm(), 0xa5);
+    assert_eq!(a.get_mask(), 0x3);
+
+    // Only the negated slice is stored complemented.
+    let raw = a.raw();
+    assert_eq!((raw >> 12) & 0xf, !0x5 & 0xf);
+    assert_eq!((raw >> 20) & 0xf, 0xa);
+    assert_eq!((raw >> 24) & 0xf, !0x3 & 0xf);
+
+    a.set_imm(0x3c);
+    a.set_mask(0xe);
+    assert_eq!(a.get_imm(), 0x3c);
+    assert_eq!(a.get_mask(), 0xe);
+
+    let b = NegateSlice::parse_machine(a.emit_machine()).unwrap();
+    assert_eq!(b, a);
+    assert_eq!(b.to_string(), "neg r4 60 14");
+
+    Ok(())
+}
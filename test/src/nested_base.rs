@@ -0,0 +1,28 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf! {
+    path = "isf/testcase/nested-base.isf",
+    tests = true,
+}
+
+#[test]
+fn nested_base() -> Result<(), anyhow::Error> {
+    let x = Xor::parse_assembly("xor r1 r2 r3").unwrap();
+    assert_eq!(x.get_dst(), 1);
+    assert_eq!(x.get_src1(), 2);
+    assert_eq!(x.get_src2(), 3);
+    assert_eq!(
+        x.emit_machine(),
+        0x33 | 1 << 7 | 4 << 12 | 2 << 15 | 3 << 20
+    );
+
+    let a = Add::parse_machine(0x33 | 5 << 7).unwrap();
+    assert_eq!(a.get_dst(), 5);
+    assert!(Add::parse_machine(0x33 | 4 << 12).is_err());
+    assert_eq!(Add::TIMING, isf::Timing::Cycle(1));
+    Ok(())
+}
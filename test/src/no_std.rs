@@ -0,0 +1,41 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::MachineInstruction;
+
+mod add {
+    isf_macro::isf! {
+        path = "isf/testcase/add.isf",
+        no_std = true,
+    }
+}
+
+mod class {
+    isf_macro::isf! {
+        path = "isf/testcase/class.isf",
+        no_std = true,
+    }
+}
+
+#[test]
+fn no_std() -> Result<(), anyhow::Error> {
+    let mut a = add::Add::default();
+    a.set_dst(1);
+    a.try_set_src1(4)?;
+    a.set_src2(7);
+    let machine = a.emit_machine();
+    let b = add::Add::parse_machine(machine).unwrap();
+    assert_eq!(b.get_dst(), 1);
+    assert_eq!(b.get_src1(), 4);
+    assert_eq!(b.get_src2(), 7);
+    assert_eq!(add::decode(machine), Some(add::Instruction::Add(b)));
+
+    let mut li = class::Li::default();
+    li.set_dst(class::Reg::StackPtr);
+    li.try_set_imm(17)?;
+    let li = class::Li::parse_machine(li.emit_machine()).unwrap();
+    assert_eq!(li.get_dst(), class::Reg::StackPtr);
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{DecodedInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/odd-width.isf");
+
+#[test]
+fn odd_width() -> Result<(), anyhow::Error> {
+    let raw = u64::MAX << 9 | 1;
+    let x = Xfer::parse_machine(raw).unwrap();
+    assert_eq!(x.get_src(), 0x7ffff);
+    assert_eq!(x.get_dst(), 0x7ffff);
+    assert_eq!(x.emit_machine() >> 47, 0);
+    assert_eq!(x.emit_machine(), raw & ((1 << 47) - 1));
+    assert_eq!(x.machine() >> 47, 0);
+    assert_eq!(Xfer::WIDTH, 47);
+    assert_eq!(x.width_bits(), 47);
+
+    let bytes = x.emit_machine_bytes();
+    assert_eq!(bytes, [0x01, 0xfe, 0xff, 0xff, 0xff, 0x7f]);
+    let parsed = Xfer::parse_machine_bytes(&bytes).unwrap();
+    assert_eq!(parsed.emit_machine(), x.emit_machine());
+    assert_eq!(Xfer::parse_machine_bytes(&[0; 6]), None);
+    assert_eq!(Xfer::parse_machine_bytes(&[0; 8]), None);
+    Ok(())
+}
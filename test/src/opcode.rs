@@ -0,0 +1,54 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::MachineInstruction;
+
+mod binop {
+    isf_macro::isf!("isf/testcase/binop.isf");
+}
+
+mod nested_base {
+    isf_macro::isf!("isf/testcase/nested-base.isf");
+}
+
+#[test]
+fn opcode() -> Result<(), anyhow::Error> {
+    use binop::{Add, Sub};
+    assert_eq!(Add::OPCODE_MASK, 0x7f);
+    assert_eq!(Add::OPCODE_MATCH, 2);
+    assert_eq!(Sub::OPCODE_MASK, 0x7f);
+    assert_eq!(Sub::OPCODE_MATCH, 3);
+
+    // opcode and function code are both constant
+    use nested_base::{Add as AluAdd, Xor};
+    assert_eq!(AluAdd::OPCODE_MASK, 0x7f | 0b111 << 12);
+    assert_eq!(AluAdd::OPCODE_MATCH, 0x33);
+    assert_eq!(Xor::OPCODE_MATCH, 0x33 | 4 << 12);
+
+    // constant fields are available as consts
+    assert_eq!(Add::OPCODE, 2);
+    assert_eq!(Add::default().get_opcode(), Add::OPCODE);
+    const NAMES: [(u8, &str); 2] = [(Add::OPCODE, "add"), (Sub::OPCODE, "sub")];
+    assert_eq!(NAMES[1], (3, "sub"));
+    assert_eq!(Xor::FUNCT, 4);
+    assert_eq!(Xor::OPCODE, 0x33);
+
+    // every instruction matches its own opcode
+    let x = Xor::default().emit_machine();
+    assert_eq!(x & Xor::OPCODE_MASK, Xor::OPCODE_MATCH);
+    assert_ne!(x & AluAdd::OPCODE_MASK, AluAdd::OPCODE_MATCH);
+
+    // matching checks only the constant fields
+    assert!(Xor::matches(x));
+    assert!(!AluAdd::matches(x));
+    assert!(Xor::matches(x | 0x1f << 7));
+    const MATCHES: [bool; 2] = [Add::matches(2), Add::matches(3)];
+    assert_eq!(MATCHES, [true, false]);
+    assert!(matches!(
+        nested_base::decode(x),
+        Some(nested_base::Instruction::Xor(_))
+    ));
+
+    Ok(())
+}
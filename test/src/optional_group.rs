@@ -0,0 +1,43 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf! {
+    path = "isf/testcase/optional-group.isf",
+    tests = true,
+}
+
+#[test]
+fn optional_group() -> Result<(), anyhow::Error> {
+    let b =
+        Branch::parse_assembly("b 100").map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!(
+        (b.get_cond(), b.get_offset(), b.get_likely()),
+        (0, 100, false)
+    );
+    assert_eq!(b.emit_assembly(), "b 100");
+
+    let b = Branch::parse_assembly("b.cc 3 100")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!(
+        (b.get_cond(), b.get_offset(), b.get_likely()),
+        (3, 100, false)
+    );
+    assert_eq!(b.emit_assembly(), "b.cc 3 100");
+
+    let b = Branch::parse_assembly("b.cc 3.+ 100")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!(
+        (b.get_cond(), b.get_offset(), b.get_likely()),
+        (3, 100, true)
+    );
+    assert_eq!(b.emit_assembly(), "b.cc 3.+ 100");
+
+    // A group that does not match as a whole is skipped, leaving the
+    // condition to be read as the offset.
+    assert!(Branch::parse_assembly("b.cc 100").is_err());
+
+    Ok(())
+}
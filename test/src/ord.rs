@@ -0,0 +1,38 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf! {
+    path = "isf/testcase/binop.isf",
+    ord = true,
+}
+
+#[test]
+fn ord() -> Result<(), anyhow::Error> {
+    let parse = |text: &str| {
+        Add::parse_assembly(text).map_err(|e| anyhow::anyhow!("{e}"))
+    };
+    let mut adds = vec![
+        parse("add r3 r4 r5")?,
+        parse("add r1 r4 r5")?,
+        parse("add r3 r4 r5")?,
+    ];
+    adds.sort();
+    adds.dedup();
+    let text: Vec<_> = adds.iter().map(|a| a.emit_assembly()).collect();
+    assert_eq!(text, ["add r1 r4 r5", "add r3 r4 r5"]);
+    assert!(adds[0] < adds[1]);
+
+    let mut all = [
+        Instruction::Sub(Sub::default()),
+        Instruction::Add(adds[1]),
+        Instruction::Add(adds[0]),
+    ];
+    all.sort();
+    assert_eq!(all[0], Instruction::Add(adds[0]));
+    assert_eq!(all[2], Instruction::Sub(Sub::default()));
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf!("isf/testcase/add.isf");
+
+mod flag {
+    isf_macro::isf!("isf/testcase/flag-operand.isf");
+}
+
+#[test]
+fn overflow() -> Result<(), anyhow::Error> {
+    let mut a = Add::default();
+    a.try_set_dst(31)?;
+    assert_eq!(a.get_dst(), 31);
+
+    let err = a.try_set_dst(32).unwrap_err();
+    assert_eq!(err.field, "dst");
+    assert_eq!(err.width, 5);
+    assert_eq!(err.value, 32);
+    assert_eq!(a.get_dst(), 31);
+
+    assert!(Add::parse_assembly("add r32 r4 r5").is_err());
+    assert!(Add::parse_assembly("add r3 r4 r300").is_err());
+    assert!(Add::parse_assembly("add r99999999999999999999 r1 r2").is_err());
+    assert!(Add::parse_assembly("add r0x1ffffffffffffffff r1 r2").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn flag_overflow() {
+    let p = flag::Probe::parse_assembly("probe 7 1").unwrap();
+    assert!(p.get_enable());
+    assert_eq!(p.emit_assembly(), "probe 7 1");
+    assert!(flag::Probe::parse_assembly("probe 7 2").is_err());
+}
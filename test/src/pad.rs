@@ -0,0 +1,28 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf! {
+    path = "isf/testcase/pad.isf",
+    tests = true,
+}
+
+#[test]
+fn pad() -> Result<(), anyhow::Error> {
+    let l = LoadMask::parse_assembly("ldm r03 0x0012 0b0011").unwrap();
+    assert_eq!(l.get_dst(), 3);
+    assert_eq!(l.get_imm(), 0x12);
+    assert_eq!(l.get_mask(), 0b11);
+    assert_eq!(l.emit_assembly(), "ldm r03 0x0012 0b0011");
+
+    // unpadded operands are still accepted
+    let l = LoadMask::parse_assembly("ldm r3 18 3").unwrap();
+    assert_eq!(l.emit_assembly(), "ldm r03 0x0012 0b0011");
+
+    // padding is a minimum, wider values are emitted in full
+    let l = LoadMask::parse_assembly("ldm r31 0x1234 0b11").unwrap();
+    assert_eq!(l.emit_assembly(), "ldm r31 0x1234 0b0011");
+    Ok(())
+}
@@ -0,0 +1,30 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf!("isf/testcase/add.isf");
+
+#[test]
+fn partial() -> Result<(), anyhow::Error> {
+    let line = "add r3 r4 r5; add.sx r1 r2 r3 // done";
+    let (a, rest) = Add::parse_assembly_partial(line).unwrap();
+    assert_eq!(a, Add::parse_assembly("add r3 r4 r5").unwrap());
+    assert_eq!(rest, "; add.sx r1 r2 r3 // done");
+
+    let (b, rest) =
+        Add::parse_assembly_partial(rest.trim_start_matches("; ")).unwrap();
+    assert_eq!(b, Add::parse_assembly("add.sx r1 r2 r3").unwrap());
+    assert_eq!(rest, " // done");
+
+    // All of the text may be taken.
+    assert_eq!(
+        Add::parse_assembly_partial("add r3 r4 r5").unwrap(),
+        (a, "")
+    );
+
+    let e = Add::parse_assembly_partial("add r3 r4; add r5 r6 r7").unwrap_err();
+    assert_eq!(e.offset(), 9);
+    Ok(())
+}
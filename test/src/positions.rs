@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, FieldInfo, MachineInstruction};
+
+isf_macro::isf! {
+    path = "isf/testcase/positions.isf",
+    tests = true,
+}
+
+#[test]
+fn positions() -> Result<(), anyhow::Error> {
+    let s = Store::parse_assembly("st r2 r8 100").unwrap();
+    assert_eq!(s.emit_machine(), 3 << 25 | 2 << 20 | 8 << 15 | 4 << 7 | 35);
+    assert_eq!(Store::parse_machine(s.emit_machine()).unwrap(), s);
+
+    // bits 12 to 14 are claimed by no element and reserved
+    let reserved = s.emit_machine() | 1 << 12;
+    assert!(Store::parse_machine(reserved).is_ok());
+    assert!(Store::parse_machine_strict(reserved).is_err());
+
+    assert_eq!(
+        Store::fields()
+            .iter()
+            .map(|f: &FieldInfo| (f.name, f.offset, f.width))
+            .collect::<Vec<_>>(),
+        [
+            ("offset", 7, 5),
+            ("base", 15, 5),
+            ("src", 20, 5),
+            ("offset", 25, 7),
+        ]
+    );
+    Ok(())
+}
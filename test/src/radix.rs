@@ -0,0 +1,26 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf! {
+    path = "isf/testcase/radix.isf",
+    tests = true,
+}
+
+#[test]
+fn radix() -> Result<(), anyhow::Error> {
+    let l = LoadMask::parse_assembly("ldm r3 0x1234 0b11").unwrap();
+    assert_eq!(l.get_dst(), 3);
+    assert_eq!(l.get_imm(), 0x1234);
+    assert_eq!(l.get_mask(), 0b11);
+    assert_eq!(l.emit_assembly(), "ldm r3 0x1234 0b11");
+
+    // operands in other radixes are still accepted
+    let l = LoadMask::parse_assembly("ldm r3 4660 3").unwrap();
+    assert_eq!(l.emit_assembly(), "ldm r3 0x1234 0b11");
+
+    assert_eq!(LoadMask::default().emit_assembly(), "ldm r0 0x0 0b1111");
+    Ok(())
+}
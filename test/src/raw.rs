@@ -0,0 +1,33 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::MachineInstruction;
+
+isf_macro::isf!("isf/testcase/add.isf");
+
+#[test]
+fn raw() -> Result<(), anyhow::Error> {
+    let raw = 0b00000101_00000100_00000011_10000010;
+    let a = Add::parse_machine(raw).unwrap();
+    assert_eq!(a.raw(), raw);
+    assert_eq!(Add::from_raw_unchecked(raw), a);
+
+    // a malformed opcode is kept as is
+    let bad = Add::from_raw_unchecked(raw | 0x7f);
+    assert_eq!(bad.get_opcode(), 0x7f);
+    assert_eq!(bad.raw(), raw | 0x7f);
+    assert!(Add::parse_machine(bad.emit_machine()).is_err());
+    Ok(())
+}
+
+#[test]
+fn raw_slice() {
+    let raw = [0b00000101_00000100_00000011_10000010u32; 2];
+    // Instructions are laid out as their backing integers.
+    let adds: &[Add] = unsafe {
+        std::slice::from_raw_parts(raw.as_ptr().cast::<Add>(), raw.len())
+    };
+    assert_eq!(adds[1], Add::parse_machine(raw[0]).unwrap());
+    assert_eq!(std::mem::align_of::<Add>(), std::mem::align_of::<u32>());
+}
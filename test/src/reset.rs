@@ -0,0 +1,18 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf!("isf/testcase/reset.isf");
+
+#[test]
+fn reset() -> Result<(), anyhow::Error> {
+    let m = SetMode::default();
+    assert_eq!(m.get_opcode(), 9);
+    assert_eq!(m.get_mode(), 1);
+    assert!(m.get_enable());
+    assert_eq!(m.get_reg(), 0);
+    assert_eq!(m, SetMode::parse_assembly("mode 1 1 r0").unwrap());
+    Ok(())
+}
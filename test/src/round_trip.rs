@@ -0,0 +1,209 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Round trip every combination of a handful of representative values for
+//! each field through both the machine and the assembly encoding.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+use std::fmt::Debug;
+
+fn check<I, S>(instr: I)
+where
+    I: AssemblyInstruction + MachineInstruction<S> + PartialEq + Debug,
+    S: Copy + Debug,
+{
+    let machine = instr.emit_machine();
+    assert_eq!(
+        I::parse_machine(machine).ok().as_ref(),
+        Some(&instr),
+        "machine {machine:?}",
+    );
+    let text = instr.emit_assembly();
+    assert_eq!(
+        I::parse_assembly(&text).ok().as_ref(),
+        Some(&instr),
+        "assembly {text:?}",
+    );
+}
+
+/// Check every combination of the given setter values, starting from the
+/// default instruction.
+macro_rules! sweep {
+    ($instr:expr;) => {
+        check($instr)
+    };
+    ($instr:expr; $setter:ident: $values:expr $(, $rest:ident: $more:expr)* $(,)?) => {
+        for value in $values {
+            let mut instr = $instr;
+            instr.$setter(value);
+            sweep!(instr; $($rest: $more),*);
+        }
+    };
+}
+
+mod add {
+    use super::*;
+    isf_macro::isf!("isf/testcase/add.isf");
+
+    #[test]
+    fn round_trip() {
+        sweep!(Add::default();
+            set_dst: [0, 1, 0b10101, 31],
+            set_src1: [0, 1, 0b01010, 31],
+            set_src2: [0, 0b10000, 31],
+            set_sign_extend: [false, true],
+        );
+    }
+}
+
+mod big_endian {
+    use super::*;
+    isf_macro::isf!("isf/testcase/big-endian.isf");
+
+    #[test]
+    fn round_trip() {
+        sweep!(Add::default();
+            set_dst: [0, 1, 0b10101, 31],
+            set_src1: [0, 1, 0b01010, 31],
+            set_src2: [0, 0b10000, 31],
+        );
+    }
+}
+
+mod field_widths {
+    use super::*;
+    isf_macro::isf!("isf/testcase/field-widths.isf");
+
+    #[test]
+    fn round_trip() {
+        sweep!(Pack::default();
+            set_lo: [0, 1, 0x1_5555, 0x1_ffff],
+            set_mid: [0, 1, 0xa_aaaa, 0xf_ffff],
+            set_hi: [0, 1, 0x80_0000, 0xff_ffff],
+        );
+        sweep!(Split::default();
+            set_value: [0, 1, 1 << 18, 0x2_aaaa_aaaa_aaaa, (1 << 50) - 1],
+        );
+    }
+}
+
+mod mixed_width {
+    use super::*;
+    isf_macro::isf!("isf/testcase/mixed-width.isf");
+
+    #[test]
+    fn round_trip() {
+        sweep!(Mov::default();
+            set_dst: [0, 1, 0b1010, 15],
+            set_src: [0, 0b0101, 15],
+        );
+        sweep!(Ldi::default();
+            set_dst: [0, 1, 15],
+            set_imm: [0, 1, 0x8000, 0xffff],
+        );
+    }
+}
+
+mod narrow {
+    use super::*;
+    isf_macro::isf!("isf/testcase/narrow.isf");
+
+    #[test]
+    fn round_trip() {
+        sweep!(Inc::default(); set_reg: 0..8);
+    }
+}
+
+mod odd_width {
+    use super::*;
+    isf_macro::isf!("isf/testcase/odd-width.isf");
+
+    #[test]
+    fn round_trip() {
+        sweep!(Xfer::default();
+            set_src: [0, 1, 0x5_5555, 0x7_ffff],
+            set_dst: [0, 1, 0x2_aaaa, 0x7_ffff],
+        );
+    }
+}
+
+mod radix {
+    use super::*;
+    isf_macro::isf!("isf/testcase/radix.isf");
+
+    #[test]
+    fn round_trip() {
+        sweep!(LoadMask::default();
+            set_dst: [0, 1, 31],
+            set_imm: [0, 1, 0x1234, 0xffff],
+            set_mask: [0, 0b1001, 15],
+        );
+    }
+}
+
+mod sign_magnitude {
+    use super::*;
+    isf_macro::isf!("isf/testcase/sign-magnitude.isf");
+
+    #[test]
+    fn round_trip() {
+        sweep!(AddImm::default();
+            set_dst: [0, 1, 31],
+            set_imm: [0, 1, -1, 42, -42, 255, -255],
+        );
+    }
+}
+
+mod signed {
+    use super::*;
+    isf_macro::isf!("isf/testcase/signed.isf");
+
+    #[test]
+    fn round_trip() {
+        sweep!(Branch::default(); set_offset: [0, 1, -1, -3, 255, -256]);
+    }
+}
+
+mod slice_add {
+    use super::*;
+    isf_macro::isf!("isf/testcase/slice-add.isf");
+
+    #[test]
+    fn round_trip() {
+        sweep!(SliceAdd::default();
+            set_dst: [0, 1, 31],
+            set_src: [0, 1, 0x7f, 0x80, 0x2aaa, 0x3fff],
+            set_sign_extend: [false, true],
+        );
+    }
+}
+
+mod slice_add_msb {
+    use super::*;
+    isf_macro::isf!("isf/testcase/slice-add-msb.isf");
+
+    #[test]
+    fn round_trip() {
+        sweep!(SliceAddMsb::default();
+            set_dst: [0, 1, 31],
+            set_src: [0, 1, 0x7f, 0x80, 0x1555, 0x3fff],
+            set_sign_extend: [false, true],
+        );
+    }
+}
+
+mod wide {
+    use super::*;
+    isf_macro::isf!("isf/testcase/wide.isf");
+
+    #[test]
+    fn round_trip() {
+        sweep!(LoadWide::default();
+            set_dst: [0, 1, 31],
+            set_lo: [0, 1, 0x5_5555, 0x7_ffff],
+            set_hi: [0, 0x4_0000, 0x7_ffff],
+            set_zero_extend: [false, true],
+        );
+    }
+}
@@ -0,0 +1,32 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf! {
+    path = "isf/testcase/scale.isf",
+    tests = true,
+}
+
+#[test]
+fn scale() -> Result<(), anyhow::Error> {
+    let a = AddPair::parse_assembly("addp r2 r6 0x10").unwrap();
+    assert_eq!(a.get_dst(), 1);
+    assert_eq!(a.get_src(), 3);
+    assert_eq!(a.get_offset(), 4);
+    assert_eq!(a.emit_assembly(), "addp r2 r6 0x10");
+
+    // the largest encodable operands scale past the field width
+    let mut a = AddPair::default();
+    a.set_dst(15);
+    a.set_offset(0xff);
+    assert_eq!(a.emit_assembly(), "addp r30 r0 0x3fc");
+    assert_eq!(AddPair::parse_assembly("addp r30 r0 0x3fc").unwrap(), a);
+
+    // operands must be multiples of the scale and fit once divided
+    assert!(AddPair::parse_assembly("addp r3 r6 0x10").is_err());
+    assert!(AddPair::parse_assembly("addp r2 r6 0x12").is_err());
+    assert!(AddPair::parse_assembly("addp r32 r6 0x10").is_err());
+    Ok(())
+}
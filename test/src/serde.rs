@@ -0,0 +1,22 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf! {
+    path = "isf/testcase/add.isf",
+    serde = true,
+}
+
+#[test]
+fn serde() -> Result<(), anyhow::Error> {
+    let a = Add::parse_assembly("add r3 r4 r5").unwrap();
+    let json = serde_json::to_string(&a)?;
+    assert_eq!(json, a.emit_machine().to_string());
+
+    let ad: Add = serde_json::from_str(&json)?;
+    assert_eq!(a, ad);
+
+    Ok(())
+}
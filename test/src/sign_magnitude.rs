@@ -0,0 +1,34 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/sign-magnitude.isf");
+
+#[test]
+fn sign_magnitude() -> Result<(), anyhow::Error> {
+    let raw = 0b00000000_00101010_00000011_10000100;
+    let a = AddImm::parse_machine(raw).unwrap();
+    assert_eq!(a.get_opcode(), 4);
+    assert_eq!(a.get_dst(), 3);
+    assert_eq!(a.get_imm(), -42);
+
+    let mut ap = AddImm::parse_assembly("addi r3 -42").unwrap();
+    assert_eq!(a, ap);
+    assert_eq!(ap.emit_assembly(), "addi r3 -42");
+    assert_eq!(ap.emit_machine(), raw);
+
+    ap.set_imm(255);
+    assert_eq!(ap.get_imm(), 255);
+    assert_eq!(ap.emit_machine(), 0b00000000_11111111_00000011_00000100);
+
+    ap.set_imm(-255);
+    assert_eq!(ap.get_imm(), -255);
+    assert_eq!(ap.emit_machine(), 0b00000000_11111111_00000011_10000100);
+
+    assert!(AddImm::parse_assembly("addi r3 18446744073709551615").is_err());
+    assert!(AddImm::parse_assembly("addi r3 -9223372036854775808").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/signed.isf");
+
+#[test]
+fn signed() -> Result<(), anyhow::Error> {
+    let b = Branch::parse_machine(0x1ff << 7 | 5).unwrap();
+    assert_eq!(b.get_offset(), -1);
+
+    let bp = Branch::parse_assembly("b -3").unwrap();
+    assert_eq!(bp.get_offset(), -3);
+    assert_eq!(bp.emit_machine(), 0x1fd << 7 | 5);
+    assert_eq!(bp.emit_assembly(), "b -3");
+
+    let mut b = Branch::default();
+    b.set_offset(255);
+    assert_eq!(b.get_offset(), 255);
+    b.set_offset(-256);
+    assert_eq!(b.get_offset(), -256);
+    assert!(b.try_set_offset(256).is_err());
+    assert!(b.try_set_offset(-257).is_err());
+    assert!(Branch::parse_assembly("b 256").is_err());
+    assert!(Branch::parse_assembly("b 18446744073709551615").is_err());
+    assert!(Branch::parse_assembly("b -9223372036854775808").is_err());
+
+    Ok(())
+}
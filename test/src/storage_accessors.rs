@@ -0,0 +1,55 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf! {
+    path = "isf/testcase/add.isf",
+    storage_accessors = true,
+    fuzz = true,
+}
+
+mod slice {
+    isf_macro::isf! {
+        path = "isf/testcase/slice-add.isf",
+        storage_accessors = true,
+    }
+}
+
+mod allowed {
+    isf_macro::isf! {
+        path = "isf/testcase/allowed.isf",
+        storage_accessors = true,
+    }
+}
+
+#[test]
+fn storage_accessors() -> Result<(), anyhow::Error> {
+    let mut a = Add::parse_assembly("add r0 r4 r7")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let sum: u32 = a.get_src1() + a.get_src2() + a.get_opcode();
+    assert_eq!(sum, 13);
+    let narrow: u8 = a.get_src1_narrow();
+    assert_eq!(narrow, 4);
+
+    a.set_dst(3);
+    a.set_src2_narrow(9);
+    assert_eq!(a.emit_assembly(), "add r3 r4 r9");
+    assert!(a.try_set_dst(1 << 5).is_err());
+    assert_eq!(a.get_dst(), 3);
+    assert!(!a.get_sign_extend());
+
+    let mut s = slice::SliceAdd::default();
+    s.set_src(0x3fff);
+    assert_eq!(s.get_src(), 0x3fff_u32);
+    assert_eq!(s.get_src_narrow(), 0x3fff_u16);
+
+    let mut b = allowed::Branch::default();
+    assert!(b.try_set_cond(5).is_err());
+    b.set_cond(4);
+    let raw = b.emit_machine();
+    assert!(allowed::Branch::parse_machine(raw).is_ok());
+
+    Ok(())
+}
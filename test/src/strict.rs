@@ -0,0 +1,28 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/add.isf");
+
+#[test]
+fn strict() -> Result<(), anyhow::Error> {
+    let a = Add::parse_assembly("add r0 r4 r7")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let raw = a.emit_machine();
+    let b =
+        Add::parse_machine_strict(raw).map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!(a, b);
+
+    let dirty = raw | (1 << 22);
+    assert!(Add::parse_machine(dirty).is_ok());
+
+    let err = Add::parse_machine_strict(dirty).unwrap_err();
+    assert_eq!(err.field, "_");
+    assert_eq!(err.expected, 0);
+    assert!(err.allowed.is_empty());
+    assert_eq!(err.found, 2);
+
+    Ok(())
+}
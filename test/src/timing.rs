@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::Timing;
+
+isf_macro::isf!("isf/testcase/multi-timing.isf");
+
+mod binop {
+    isf_macro::isf!("isf/testcase/binop.isf");
+}
+
+mod units {
+    isf_macro::isf!("isf/testcase/timing-units.isf");
+}
+
+#[test]
+fn timing() -> Result<(), anyhow::Error> {
+    assert_eq!(Wait::TIMING, Timing::Multi);
+    assert_eq!(Load::TIMING, Timing::MultiUpTo(4));
+    assert_eq!(Divide::TIMING, Timing::MultiRange(3, 5));
+    assert_eq!(binop::Add::TIMING, Timing::Async);
+    assert_eq!(units::Fence::TIMING, Timing::Nanos(3));
+    assert_eq!(units::Mul::TIMING, Timing::Stages(2));
+    assert_eq!(units::Nop::TIMING, Timing::Cycle(1));
+
+    // timing is available in const contexts
+    const CYCLES: usize = match binop::Sub::TIMING {
+        Timing::Cycle(n) => n,
+        _ => 0,
+    };
+    assert_eq!(CYCLES, 47);
+
+    assert_eq!(Load::default().view().timing, Load::TIMING);
+    Ok(())
+}
@@ -0,0 +1,55 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{ast::Timing, AssemblyInstruction, DecodedInstruction};
+
+isf_macro::isf!("isf/testcase/add.isf");
+
+mod sign_magnitude {
+    isf_macro::isf!("isf/testcase/sign-magnitude.isf");
+}
+
+mod signed {
+    isf_macro::isf!("isf/testcase/signed.isf");
+}
+
+#[test]
+fn view() -> Result<(), anyhow::Error> {
+    let a = Add::parse_assembly("add r3 r4 r5").unwrap();
+    let view = a.view();
+    assert_eq!(view.name, "Add");
+    assert_eq!(view.timing, Timing::Cycle(1));
+    assert_eq!(
+        view.operands,
+        vec![
+            ("dst".to_owned(), 3),
+            ("src1".to_owned(), 4),
+            ("src2".to_owned(), 5),
+            ("sign_extend".to_owned(), 0),
+        ]
+    );
+
+    let decoded: Box<dyn DecodedInstruction> = Box::new(a);
+    assert_eq!(decoded.view(), view);
+
+    Ok(())
+}
+
+#[test]
+fn view_signed() {
+    // Sign-magnitude operands are the two's complement bits of the 8-bit
+    // field plus its sign bit.
+    let a = sign_magnitude::AddImm::parse_assembly("addi r3 -3").unwrap();
+    assert_eq!(
+        a.view().operands,
+        vec![("dst".to_owned(), 3), ("imm".to_owned(), 0x1fd)]
+    );
+}
+
+#[test]
+fn view_twos_complement() {
+    // Signed operands are the two's complement bits of the 9-bit field.
+    let b = signed::Branch::parse_assembly("b -3").unwrap();
+    assert_eq!(b.view().operands, vec![("offset".to_owned(), 0x1fd)]);
+}
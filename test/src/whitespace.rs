@@ -0,0 +1,45 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+mod add {
+    isf_macro::isf!("isf/testcase/add.isf");
+}
+
+mod add_field_opt {
+    isf_macro::isf!("isf/testcase/add-field-opt.isf");
+}
+
+#[test]
+fn whitespace() -> Result<(), anyhow::Error> {
+    use add::Add;
+
+    // runs of whitespace are accepted and emitted as a single space
+    let a = Add::parse_assembly("add   r3 \t r4\n r5").unwrap();
+    assert_eq!(a.emit_assembly(), "add r3 r4 r5");
+    let a = Add::parse_assembly("add.sx  r3 r4 r5").unwrap();
+    assert_eq!(a.emit_assembly(), "add.sx r3 r4 r5");
+
+    // a mnemonic must be separated from its operands
+    assert!(Add::parse_assembly("addr3 r4 r5").is_err());
+    assert!(Add::parse_assembly("add.sxr3 r4 r5").is_err());
+    assert!(Add::parse_assembly("add r3r4 r5").is_err());
+
+    // leading and trailing whitespace is not part of an instruction
+    assert!(Add::parse_assembly(" add r3 r4 r5").is_err());
+    assert!(Add::parse_assembly("add r3 r4 r5 ").is_err());
+
+    // absent optional fields leave no extra spaces behind
+    use add_field_opt::AddOptField;
+    let mut a = AddOptField::default();
+    a.set_sign_extend(false);
+    a.set_src1(1);
+    a.set_b(1);
+    assert_eq!(a.emit_assembly(), "add r0 r1 1");
+    let a = AddOptField::parse_assembly("add r0 r1.3  2 1").unwrap();
+    assert_eq!(a.emit_assembly(), "add r0 r1.3 2 1");
+
+    Ok(())
+}
@@ -0,0 +1,25 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::{AssemblyInstruction, MachineInstruction};
+
+isf_macro::isf!("isf/testcase/wide.isf");
+
+#[test]
+fn wide() -> Result<(), anyhow::Error> {
+    let a = LoadWide::parse_assembly("ldw.zx r3 0x7ffff 0x12345").unwrap();
+    assert_eq!(a.get_opcode(), 9);
+    assert_eq!(a.get_dst(), 3);
+    assert_eq!(a.get_lo(), 0x7ffff);
+    assert_eq!(a.get_hi(), 0x12345);
+    assert!(a.get_zero_extend());
+
+    let raw: u128 =
+        (0x12345 << 65) | (1 << 64) | (0x7ffff << 16) | (3 << 7) | 9;
+    assert_eq!(a.emit_machine(), raw);
+    assert_eq!(LoadWide::parse_machine(raw).unwrap(), a);
+    assert_eq!(a.emit_assembly(), "ldw.zx r3 524287 74565");
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use isf::AssemblyInstruction;
+
+isf_macro::isf! {
+    path = "isf/testcase/binop.isf",
+    wildcards = true,
+}
+
+#[test]
+fn wildcard() -> Result<(), anyhow::Error> {
+    let (add, wildcards) = Add::parse_assembly_pattern("add r? r4 r5")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!(wildcards, ["dst"]);
+    assert_eq!((add.get_dst(), add.get_src1(), add.get_src2()), (0, 4, 5));
+
+    let (add, wildcards) = Add::parse_assembly_pattern("add.sx r? r? r?")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert_eq!(wildcards, ["dst", "src1", "src2"]);
+    assert!(add.get_sign_extend());
+
+    let (_, wildcards) = Add::parse_assembly_pattern("add r1 r2 r3")
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    assert!(wildcards.is_empty());
+
+    // Plain parsing does not accept wildcards.
+    assert!(Add::parse_assembly("add r? r4 r5").is_err());
+    Ok(())
+}
+
+mod group {
+    isf_macro::isf! {
+        path = "isf/testcase/optional-group.isf",
+        wildcards = true,
+    }
+
+    #[test]
+    fn wildcard_group() -> Result<(), anyhow::Error> {
+        let (b, wildcards) = Branch::parse_assembly_pattern("b.cc ?.+ ?")
+            .map_err(|e| anyhow::anyhow!("{e}"))?;
+        assert_eq!(wildcards, ["cond", "offset"]);
+        assert!(b.get_likely());
+
+        let (_, wildcards) = Branch::parse_assembly_pattern("b ?")
+            .map_err(|e| anyhow::anyhow!("{e}"))?;
+        assert_eq!(wildcards, ["offset"]);
+        Ok(())
+    }
+}
+
+mod alternatives {
+    isf_macro::isf! {
+        path = "isf/testcase/alternatives.isf",
+        wildcards = true,
+    }
+
+    #[test]
+    fn wildcard_alternatives() -> Result<(), anyhow::Error> {
+        let (_, wildcards) = Jmp::parse_assembly_pattern("jmp.rel +?")
+            .map_err(|e| anyhow::anyhow!("{e}"))?;
+        assert_eq!(wildcards, ["offset"]);
+        Ok(())
+    }
+}